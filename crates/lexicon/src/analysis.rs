@@ -0,0 +1,208 @@
+//! Queries over the types defined in a Lexicon [`Document`], for code
+//! generation.
+
+use crate::schema::{Definition, Document, Node, Nsid, Schema, String, Token, TypeId};
+use crate::Set;
+
+/// A string type with a list of values, from [`string_enums`].
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct StringEnum {
+    /// Where the string is defined, like `app.bsky.feed.defs#threadgate.allow`
+    /// (using the same syntax as [`SchemaChange`][crate::SchemaChange]
+    /// paths).
+    pub path: std::string::String,
+    /// The values of the string.
+    pub values: Vec<std::string::String>,
+    /// Whether the string must be one of `values` (from an `enum`), rather
+    /// than it merely being the values known so far (from `knownValues`).
+    pub closed: bool,
+}
+
+impl StringEnum {
+    fn of(path: &str, string: &String) -> Option<Self> {
+        let (values, closed) = match (&string.values, &string.known_values) {
+            (Some(values), _) => (values, true),
+            (None, Some(values)) => (values, false),
+            (None, None) => return None,
+        };
+
+        Some(Self {
+            path: path.to_owned(),
+            values: values.clone(),
+            closed,
+        })
+    }
+}
+
+/// Every string type in `document` with `enum` or `knownValues`, in the
+/// order they appear.
+///
+/// This includes top-level definitions, object properties, array items
+/// (with a path ending in `[]`), query parameters (`?name`), and the
+/// properties of input and output bodies. A string with both lists is
+/// closed, and listed with its `enum` values.
+///
+/// ```
+/// use atprose_lexicon::{schema::Document, string_enums};
+///
+/// let document: Document = serde_json::from_str(r#"{
+///     "lexicon": 1,
+///     "id": "com.example.defs",
+///     "defs": {"status": {"type": "string", "knownValues": ["active", "away"]}}
+/// }"#).unwrap();
+///
+/// let enums: Vec<_> = string_enums(&document).collect();
+/// assert_eq!("com.example.defs#status", enums[0].path);
+/// assert_eq!(vec!["active", "away"], enums[0].values);
+/// assert!(!enums[0].closed);
+/// ```
+pub fn string_enums(document: &Document) -> impl Iterator<Item = StringEnum> {
+    let mut enums = Vec::new();
+    document.walk(|path, node| {
+        if let Node::String(string) = node {
+            enums.extend(StringEnum::of(path, string));
+        }
+    });
+
+    enums.into_iter()
+}
+
+impl Token {
+    /// The value of the token defined as `name` in the document `nsid`: its
+    /// type ID, like `app.bsky.feed.defs#requestLess`.
+    ///
+    /// A token named `main` is identified by the NSID alone.
+    pub fn as_type_id(nsid: &Nsid, name: &str) -> TypeId {
+        TypeId::of(nsid, name)
+    }
+}
+
+/// The [type IDs][Token::as_type_id] of every token defined in `schema`.
+///
+/// An open union's value may have a `$type` which isn't one of its refs;
+/// when those refs are tokens, a validator can check that the `$type` is at
+/// least a known token.
+///
+/// ```
+/// use atprose_lexicon::{schema::{Document, Schema, TypeId}, tokens};
+///
+/// let document: Document = serde_json::from_str(r#"{
+///     "lexicon": 1,
+///     "id": "com.example.defs",
+///     "defs": {"like": {"type": "token"}, "status": {"type": "string"}}
+/// }"#).unwrap();
+/// let schema: Schema = [document].into_iter().collect();
+///
+/// let tokens = tokens(&schema);
+/// assert!(tokens.contains(&"com.example.defs#like".parse::<TypeId>().unwrap()));
+/// assert!(!tokens.contains(&"com.example.defs#status".parse::<TypeId>().unwrap()));
+/// ```
+pub fn tokens(schema: &Schema) -> Set<TypeId> {
+    schema
+        .values()
+        .flat_map(|document| {
+            document.defs.iter().filter_map(|(name, def)| match def {
+                Definition::Token(_) => Some(Token::as_type_id(&document.id, name)),
+                _ => None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{string_enums, tokens, StringEnum};
+    use crate::schema::{Described, Document, Nsid, Schema, Token, TypeId};
+
+    #[test]
+    fn test_string_enums() {
+        let document: Document = serde_json::from_str(
+            r#"{
+                "lexicon": 1,
+                "id": "com.example.thing",
+                "defs": {
+                    "main": {
+                        "type": "record",
+                        "key": "tid",
+                        "record": {
+                            "type": "object",
+                            "properties": {
+                                "name": {"type": "string"},
+                                "kind": {"type": "string", "enum": ["a", "b"], "knownValues": ["a"]},
+                                "tags": {"type": "array", "items": {"type": "string", "knownValues": ["x"]}}
+                            }
+                        }
+                    },
+                    "list": {
+                        "type": "query",
+                        "parameters": {"type": "params", "properties": {
+                            "sort": {"type": "string", "knownValues": ["new", "top"]},
+                            "limit": {"type": "integer"}
+                        }},
+                        "output": {"encoding": "application/json", "schema": {
+                            "type": "object",
+                            "properties": {"state": {"type": "string", "enum": ["done"]}}
+                        }}
+                    },
+                    "level": {"type": "string", "enum": ["low", "high"]}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let expected = [
+            ("com.example.thing#main.kind", &["a", "b"][..], true),
+            ("com.example.thing#main.tags[]", &["x"], false),
+            ("com.example.thing#list?sort", &["new", "top"], false),
+            ("com.example.thing#list.output.state", &["done"], true),
+            ("com.example.thing#level", &["low", "high"], true),
+        ]
+        .map(|(path, values, closed)| StringEnum {
+            path: path.to_owned(),
+            values: values.iter().map(|&value| value.to_owned()).collect(),
+            closed,
+        });
+
+        assert_eq!(
+            expected.to_vec(),
+            string_enums(&document).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_tokens() {
+        let documents = [
+            r#"{"lexicon": 1, "id": "com.example.defs", "defs": {
+                "less": {"type": "token", "description": "Show less."},
+                "more": {"type": "token"},
+                "view": {"type": "object", "properties": {}}
+            }}"#,
+            r#"{"lexicon": 1, "id": "com.example.marker", "defs": {
+                "main": {"type": "token"}
+            }}"#,
+        ];
+        let schema: Schema = documents
+            .into_iter()
+            .map(|json| serde_json::from_str::<Document>(json).unwrap())
+            .collect();
+
+        let expected: serde_json::Value = serde_json::from_str(documents[0]).unwrap();
+        let defs = &schema[0];
+        assert_eq!("token", defs.defs["less"].kind());
+        assert_eq!(Some("Show less."), defs.defs["less"].description());
+        assert_eq!(expected, serde_json::to_value(defs).unwrap());
+
+        let defs: Nsid = "com.example.defs".parse().unwrap();
+        assert_eq!(
+            "com.example.marker",
+            Token::as_type_id(&"com.example.marker".parse().unwrap(), "main").to_string()
+        );
+
+        let expected: Vec<TypeId> = vec![
+            Token::as_type_id(&defs, "less"),
+            Token::as_type_id(&defs, "more"),
+            "com.example.marker".parse().unwrap(),
+        ];
+        assert_eq!(expected, tokens(&schema).into_iter().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,829 @@
+//! Compare two versions of a Lexicon [`Schema`] for backward compatibility.
+//!
+//! ```
+//! use atprose_lexicon::{diff_documents, schema::Document};
+//!
+//! # fn main() -> Result<(), serde_json::Error> {
+//! let old: Document = serde_json::from_str(r#"{
+//!     "lexicon": 1,
+//!     "id": "com.example.thing",
+//!     "defs": {"main": {"type": "object", "properties": {"name": {"type": "string"}}}}
+//! }"#)?;
+//! let new: Document = serde_json::from_str(r#"{
+//!     "lexicon": 1,
+//!     "id": "com.example.thing",
+//!     "defs": {"main": {"type": "object", "properties": {}}}
+//! }"#)?;
+//!
+//! let changes = diff_documents(&old, &new);
+//! assert_eq!(changes.len(), 1);
+//! assert!(changes[0].is_breaking());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+use crate::schema::{
+    Array, ArrayItem, Blob, Body, BodySchema, Boolean, Bytes, Definition, Document, Integer, Nsid,
+    Object, ParameterArray, ParameterArrayItem, ParameterValue, Parameters, Procedure, Property,
+    Query, QuerySchema, Record, RecordDefinition, RecordKey, Ref, RefTarget, Schema, String,
+    TypeId, Union,
+};
+
+/// A single difference between two versions of a schema.
+///
+/// Each change is classified by [`is_breaking`][SchemaChange::is_breaking]:
+/// removing or narrowing anything that existing data or clients may rely on is
+/// breaking, while additions of optional shapes and relaxed constraints are
+/// not.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum SchemaChange {
+    /// A new document was added to the schema.
+    DocumentAdded(Nsid),
+    /// A document was removed from the schema.
+    DocumentRemoved(Nsid),
+    /// A new definition was added to a document.
+    DefinitionAdded(TypeId),
+    /// A definition was removed from a document.
+    DefinitionRemoved(TypeId),
+    /// The lexicon type at `path` changed (e.g., from `string` to `integer`).
+    TypeChanged { path: std::string::String },
+    /// An object property or query parameter was added.
+    PropertyAdded {
+        path: std::string::String,
+        required: bool,
+    },
+    /// An object property or query parameter was removed.
+    PropertyRemoved { path: std::string::String },
+    /// An existing property became required (or stopped being required).
+    RequirementChanged {
+        path: std::string::String,
+        required: bool,
+    },
+    /// An existing property became nullable (or stopped being nullable).
+    NullabilityChanged {
+        path: std::string::String,
+        nullable: bool,
+    },
+    /// A record's key format changed.
+    RecordKeyChanged {
+        path: std::string::String,
+        from: RecordKey,
+        to: RecordKey,
+    },
+    /// A `ref` now points to a different type.
+    RefChanged {
+        path: std::string::String,
+        from: RefTarget,
+        to: RefTarget,
+    },
+    /// A type was added to a union's refs.
+    UnionOptionAdded {
+        path: std::string::String,
+        target: RefTarget,
+        closed: bool,
+    },
+    /// A type was removed from a union's refs.
+    UnionOptionRemoved {
+        path: std::string::String,
+        target: RefTarget,
+    },
+    /// The encoding of an XRPC input or output body changed.
+    EncodingChanged {
+        path: std::string::String,
+        from: std::string::String,
+        to: std::string::String,
+    },
+    /// A constraint (e.g., `maxLength`) now accepts fewer values.
+    Narrowed {
+        path: std::string::String,
+        constraint: &'static str,
+    },
+    /// A constraint (e.g., `maxLength`) now accepts more values.
+    Relaxed {
+        path: std::string::String,
+        constraint: &'static str,
+    },
+    /// A definition of a type this crate doesn't model
+    /// ([`Unsupported`][Definition::Unsupported]) changed, in a way which
+    /// can't be classified.
+    UnsupportedChanged { path: std::string::String },
+}
+
+impl SchemaChange {
+    /// Whether this change may break existing data or clients.
+    pub fn is_breaking(&self) -> bool {
+        use SchemaChange::*;
+
+        match self {
+            DocumentAdded(_) | DefinitionAdded(_) | Relaxed { .. } => false,
+            UnsupportedChanged { .. } => false,
+            DocumentRemoved(_) | DefinitionRemoved(_) | TypeChanged { .. } => true,
+            PropertyAdded { required, .. } => *required,
+            PropertyRemoved { .. } => true,
+            RequirementChanged { required, .. } => *required,
+            NullabilityChanged { nullable, .. } => !nullable,
+            RecordKeyChanged { .. } | RefChanged { .. } => true,
+            UnionOptionAdded { closed, .. } => *closed,
+            UnionOptionRemoved { .. } | EncodingChanged { .. } | Narrowed { .. } => true,
+        }
+    }
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SchemaChange::*;
+
+        match self {
+            DocumentAdded(id) => write!(f, "{id}: document added"),
+            DocumentRemoved(id) => write!(f, "{id}: document removed"),
+            DefinitionAdded(id) => write!(f, "{id}: definition added"),
+            DefinitionRemoved(id) => write!(f, "{id}: definition removed"),
+            TypeChanged { path } => write!(f, "{path}: type changed"),
+            PropertyAdded {
+                path,
+                required: true,
+            } => write!(f, "{path}: required property added"),
+            PropertyAdded { path, .. } => write!(f, "{path}: optional property added"),
+            PropertyRemoved { path } => write!(f, "{path}: property removed"),
+            RequirementChanged {
+                path,
+                required: true,
+            } => write!(f, "{path}: property became required"),
+            RequirementChanged { path, .. } => write!(f, "{path}: property became optional"),
+            NullabilityChanged {
+                path,
+                nullable: true,
+            } => write!(f, "{path}: property became nullable"),
+            NullabilityChanged { path, .. } => write!(f, "{path}: property became non-nullable"),
+            RecordKeyChanged { path, from, to } => {
+                write!(f, "{path}: record key changed from {from} to {to}")
+            }
+            RefChanged { path, from, to } => write!(f, "{path}: ref changed from {from} to {to}"),
+            UnionOptionAdded { path, target, .. } => write!(f, "{path}: union ref {target} added"),
+            UnionOptionRemoved { path, target } => {
+                write!(f, "{path}: union ref {target} removed")
+            }
+            EncodingChanged { path, from, to } => {
+                write!(f, "{path}: encoding changed from {from} to {to}")
+            }
+            Narrowed { path, constraint } => write!(f, "{path}: {constraint} narrowed"),
+            Relaxed { path, constraint } => write!(f, "{path}: {constraint} relaxed"),
+            UnsupportedChanged { path } => write!(f, "{path}: unsupported definition changed"),
+        }
+    }
+}
+
+/// Compare two schemas, listing every change needed to get from `old` to `new`.
+pub fn diff(old: &Schema, new: &Schema) -> Vec<SchemaChange> {
+    let mut diff = Diff::default();
+
+    for (id, old) in old {
+        match new.get(id) {
+            Some(new) => diff.document(old, new),
+            None => diff.push(SchemaChange::DocumentRemoved(id.clone())),
+        }
+    }
+
+    for id in new.keys().filter(|id| !old.contains_key(*id)) {
+        diff.push(SchemaChange::DocumentAdded(id.clone()));
+    }
+
+    diff.changes
+}
+
+/// Compare two versions of a single document.
+pub fn diff_documents(old: &Document, new: &Document) -> Vec<SchemaChange> {
+    let mut diff = Diff::default();
+    diff.document(old, new);
+    diff.changes
+}
+
+#[derive(Default)]
+struct Diff {
+    changes: Vec<SchemaChange>,
+}
+
+impl Diff {
+    fn push(&mut self, change: SchemaChange) {
+        self.changes.push(change);
+    }
+
+    fn narrowed(&mut self, path: &str, constraint: &'static str) {
+        self.push(SchemaChange::Narrowed {
+            path: path.to_owned(),
+            constraint,
+        });
+    }
+
+    fn relaxed(&mut self, path: &str, constraint: &'static str) {
+        self.push(SchemaChange::Relaxed {
+            path: path.to_owned(),
+            constraint,
+        });
+    }
+
+    fn type_changed(&mut self, path: &str) {
+        self.push(SchemaChange::TypeChanged {
+            path: path.to_owned(),
+        });
+    }
+
+    fn document(&mut self, old: &Document, new: &Document) {
+        for (name, old_def) in &old.defs {
+            let id = TypeId::of(&new.id, name);
+
+            match new.defs.get(name) {
+                Some(new_def) => {
+                    let path = format!("{}#{name}", new.id);
+                    self.definition(&path, old_def, new_def);
+                }
+                None => self.push(SchemaChange::DefinitionRemoved(id)),
+            }
+        }
+
+        for name in new.defs.keys().filter(|name| !old.defs.contains_key(*name)) {
+            self.push(SchemaChange::DefinitionAdded(TypeId::of(&new.id, name)));
+        }
+    }
+
+    fn definition(&mut self, path: &str, old: &Definition, new: &Definition) {
+        use Definition as D;
+
+        match (old, new) {
+            (D::Record(old), D::Record(new)) => self.record(path, old, new),
+            (D::Query(old), D::Query(new)) => self.query(path, old, new),
+            (D::Procedure(old), D::Procedure(new)) => self.procedure(path, old, new),
+            (D::Array(old), D::Array(new)) => self.array(path, old, new),
+            (D::Object(old), D::Object(new)) => self.object(path, old, new),
+            (D::Blob(old), D::Blob(new)) => self.blob(path, old, new),
+            (D::Boolean(old), D::Boolean(new)) => self.boolean(path, old, new),
+            (D::Bytes(old), D::Bytes(new)) => self.bytes(path, old, new),
+            (D::Integer(old), D::Integer(new)) => self.integer(path, old, new),
+            (D::String(old), D::String(new)) => self.string(path, old, new),
+            (D::Ref(old), D::Ref(new)) => self.reference(path, old, new),
+            (D::Union(old), D::Union(new)) => self.union(path, old, new),
+            (D::Link(_), D::Link(_))
+            | (D::Unknown(_), D::Unknown(_))
+            | (D::Token(_), D::Token(_)) => {}
+            (
+                D::Unsupported {
+                    type_name: old_type,
+                    raw: old,
+                },
+                D::Unsupported {
+                    type_name: new_type,
+                    raw: new,
+                },
+            ) if old_type == new_type => {
+                if old != new {
+                    self.push(SchemaChange::UnsupportedChanged {
+                        path: path.to_owned(),
+                    });
+                }
+            }
+            _ => self.type_changed(path),
+        }
+    }
+
+    fn property(&mut self, path: &str, old: &Property, new: &Property) {
+        use Property as P;
+
+        match (old, new) {
+            (P::Blob(old), P::Blob(new)) => self.blob(path, old, new),
+            (P::Boolean(old), P::Boolean(new)) => self.boolean(path, old, new),
+            (P::Bytes(old), P::Bytes(new)) => self.bytes(path, old, new),
+            (P::Integer(old), P::Integer(new)) => self.integer(path, old, new),
+            (P::String(old), P::String(new)) => self.string(path, old, new),
+            (P::Array(old), P::Array(new)) => self.array(path, old, new),
+            (P::Ref(old), P::Ref(new)) => self.reference(path, old, new),
+            (P::Union(old), P::Union(new)) => self.union(path, old, new),
+            (P::Link(_), P::Link(_)) | (P::Unknown(_), P::Unknown(_)) => {}
+            _ => self.type_changed(path),
+        }
+    }
+
+    fn array_item(&mut self, path: &str, old: &ArrayItem, new: &ArrayItem) {
+        use ArrayItem as A;
+
+        match (old, new) {
+            (A::Blob(old), A::Blob(new)) => self.blob(path, old, new),
+            (A::Boolean(old), A::Boolean(new)) => self.boolean(path, old, new),
+            (A::Bytes(old), A::Bytes(new)) => self.bytes(path, old, new),
+            (A::Integer(old), A::Integer(new)) => self.integer(path, old, new),
+            (A::String(old), A::String(new)) => self.string(path, old, new),
+            (A::Ref(old), A::Ref(new)) => self.reference(path, old, new),
+            (A::Union(old), A::Union(new)) => self.union(path, old, new),
+            (A::Link(_), A::Link(_)) | (A::Unknown(_), A::Unknown(_)) => {}
+            _ => self.type_changed(path),
+        }
+    }
+
+    fn record(&mut self, path: &str, old: &Record, new: &Record) {
+        if old.key != new.key {
+            self.push(SchemaChange::RecordKeyChanged {
+                path: path.to_owned(),
+                from: old.key.clone(),
+                to: new.key.clone(),
+            });
+        }
+
+        let (RecordDefinition::Object(old), RecordDefinition::Object(new)) = (&old.def, &new.def);
+        self.object(path, old, new);
+    }
+
+    fn object(&mut self, path: &str, old: &Object, new: &Object) {
+        for (name, old_prop) in &old.properties {
+            let path = format!("{path}.{name}");
+
+            let Some(new_prop) = new.properties.get(name) else {
+                self.push(SchemaChange::PropertyRemoved { path });
+                continue;
+            };
+
+            let required = new.required.contains(name);
+            if old.required.contains(name) != required {
+                self.push(SchemaChange::RequirementChanged {
+                    path: path.clone(),
+                    required,
+                });
+            }
+
+            let nullable = new.nullable.contains(name);
+            if old.nullable.contains(name) != nullable {
+                self.push(SchemaChange::NullabilityChanged {
+                    path: path.clone(),
+                    nullable,
+                });
+            }
+
+            self.property(&path, old_prop, new_prop);
+        }
+
+        for name in new.properties.keys() {
+            if !old.properties.contains_key(name) {
+                self.push(SchemaChange::PropertyAdded {
+                    path: format!("{path}.{name}"),
+                    required: new.required.contains(name),
+                });
+            }
+        }
+    }
+
+    fn array(&mut self, path: &str, old: &Array, new: &Array) {
+        self.lower(path, "minLength", old.min_length, new.min_length);
+        self.upper(path, "maxLength", old.max_length, new.max_length);
+        self.array_item(&format!("{path}[]"), &old.items, &new.items);
+    }
+
+    fn blob(&mut self, path: &str, old: &Blob, new: &Blob) {
+        self.values(path, "accept", old.accept.as_deref(), new.accept.as_deref());
+        self.upper(path, "maxSize", old.max_size, new.max_size);
+    }
+
+    fn boolean(&mut self, path: &str, old: &Boolean, new: &Boolean) {
+        self.fixed(path, "const", old.value.as_ref(), new.value.as_ref());
+    }
+
+    fn bytes(&mut self, path: &str, old: &Bytes, new: &Bytes) {
+        self.lower(path, "minLength", old.min_length, new.min_length);
+        self.upper(path, "maxLength", old.max_length, new.max_length);
+    }
+
+    fn integer(&mut self, path: &str, old: &Integer, new: &Integer) {
+        self.fixed(path, "const", old.value.as_ref(), new.value.as_ref());
+        self.values(path, "enum", old.values.as_deref(), new.values.as_deref());
+        self.lower(path, "minimum", old.minimum, new.minimum);
+        self.upper(path, "maximum", old.maximum, new.maximum);
+    }
+
+    fn string(&mut self, path: &str, old: &String, new: &String) {
+        self.fixed(path, "format", old.format.as_ref(), new.format.as_ref());
+        self.fixed(path, "const", old.value.as_ref(), new.value.as_ref());
+        self.values(path, "enum", old.values.as_deref(), new.values.as_deref());
+        self.lower(path, "minLength", old.min_length, new.min_length);
+        self.upper(path, "maxLength", old.max_length, new.max_length);
+        self.lower(path, "minGraphemes", old.min_graphemes, new.min_graphemes);
+        self.upper(path, "maxGraphemes", old.max_graphemes, new.max_graphemes);
+    }
+
+    fn reference(&mut self, path: &str, old: &Ref, new: &Ref) {
+        if old.target != new.target {
+            self.push(SchemaChange::RefChanged {
+                path: path.to_owned(),
+                from: old.target.clone(),
+                to: new.target.clone(),
+            });
+        }
+    }
+
+    fn union(&mut self, path: &str, old: &Union, new: &Union) {
+        let (was_closed, closed) = (old.closed.unwrap_or(false), new.closed.unwrap_or(false));
+        match (was_closed, closed) {
+            (false, true) => self.narrowed(path, "closed"),
+            (true, false) => self.relaxed(path, "closed"),
+            _ => {}
+        }
+
+        for target in old.options.iter().filter(|t| !new.options.contains(t)) {
+            self.push(SchemaChange::UnionOptionRemoved {
+                path: path.to_owned(),
+                target: target.clone(),
+            });
+        }
+
+        for target in new.options.iter().filter(|t| !old.options.contains(t)) {
+            self.push(SchemaChange::UnionOptionAdded {
+                path: path.to_owned(),
+                target: target.clone(),
+                closed,
+            });
+        }
+    }
+
+    fn query(&mut self, path: &str, old: &Query, new: &Query) {
+        self.parameters(path, old.parameters.as_ref(), new.parameters.as_ref());
+        self.body(
+            &format!("{path}.output"),
+            old.output.as_ref(),
+            new.output.as_ref(),
+        );
+    }
+
+    fn procedure(&mut self, path: &str, old: &Procedure, new: &Procedure) {
+        self.parameters(path, old.parameters.as_ref(), new.parameters.as_ref());
+        self.body(
+            &format!("{path}.input"),
+            old.input.as_ref(),
+            new.input.as_ref(),
+        );
+        self.body(
+            &format!("{path}.output"),
+            old.output.as_ref(),
+            new.output.as_ref(),
+        );
+    }
+
+    fn parameters(&mut self, path: &str, old: Option<&QuerySchema>, new: Option<&QuerySchema>) {
+        let empty = Parameters {
+            metadata: Default::default(),
+            properties: Default::default(),
+            required: Vec::new(),
+        };
+        let (old, new) = match (old, new) {
+            (None, None) => return,
+            (Some(QuerySchema::Parameters(old)), None) => (old, &empty),
+            (None, Some(QuerySchema::Parameters(new))) => (&empty, new),
+            (Some(QuerySchema::Parameters(old)), Some(QuerySchema::Parameters(new))) => (old, new),
+        };
+
+        for (name, old_param) in &old.properties {
+            let path = format!("{path}?{name}");
+
+            let Some(new_param) = new.properties.get(name) else {
+                self.push(SchemaChange::PropertyRemoved { path });
+                continue;
+            };
+
+            let required = new.required.contains(name);
+            if old.required.contains(name) != required {
+                self.push(SchemaChange::RequirementChanged {
+                    path: path.clone(),
+                    required,
+                });
+            }
+
+            self.parameter(&path, old_param, new_param);
+        }
+
+        for name in new.properties.keys() {
+            if !old.properties.contains_key(name) {
+                self.push(SchemaChange::PropertyAdded {
+                    path: format!("{path}?{name}"),
+                    required: new.required.contains(name),
+                });
+            }
+        }
+    }
+
+    fn parameter(&mut self, path: &str, old: &ParameterValue, new: &ParameterValue) {
+        use ParameterValue as P;
+
+        match (old, new) {
+            (P::Boolean(old), P::Boolean(new)) => self.boolean(path, old, new),
+            (P::Integer(old), P::Integer(new)) => self.integer(path, old, new),
+            (P::String(old), P::String(new)) => self.string(path, old, new),
+            (P::Array(old), P::Array(new)) => self.parameter_array(path, old, new),
+            (P::Unknown(_), P::Unknown(_)) => {}
+            _ => self.type_changed(path),
+        }
+    }
+
+    fn parameter_array(&mut self, path: &str, old: &ParameterArray, new: &ParameterArray) {
+        use ParameterArrayItem as P;
+
+        self.lower(path, "minLength", old.min_length, new.min_length);
+        self.upper(path, "maxLength", old.max_length, new.max_length);
+
+        let path = format!("{path}[]");
+        match (&old.items, &new.items) {
+            (P::Boolean(old), P::Boolean(new)) => self.boolean(&path, old, new),
+            (P::Integer(old), P::Integer(new)) => self.integer(&path, old, new),
+            (P::String(old), P::String(new)) => self.string(&path, old, new),
+            (P::Unknown(_), P::Unknown(_)) => {}
+            _ => self.type_changed(&path),
+        }
+    }
+
+    fn body(&mut self, path: &str, old: Option<&Body>, new: Option<&Body>) {
+        let (old, new) = match (old, new) {
+            (None, None) => return,
+            (Some(old), Some(new)) => (old, new),
+            _ => return self.type_changed(path),
+        };
+
+        if old.encoding != new.encoding {
+            self.push(SchemaChange::EncodingChanged {
+                path: path.to_owned(),
+                from: old.encoding.clone(),
+                to: new.encoding.clone(),
+            });
+        }
+
+        use BodySchema as B;
+        match (old.schema.as_ref(), new.schema.as_ref()) {
+            (None, None) => {}
+            (Some(B::Object(old)), Some(B::Object(new))) => self.object(path, old, new),
+            (Some(B::Ref(old)), Some(B::Ref(new))) => self.reference(path, old, new),
+            (Some(B::Union(old)), Some(B::Union(new))) => self.union(path, old, new),
+            _ => self.type_changed(path),
+        }
+    }
+
+    /// Compare a lower bound, like `minimum` or `minLength`.
+    fn lower<T: PartialOrd>(
+        &mut self,
+        path: &str,
+        name: &'static str,
+        old: Option<T>,
+        new: Option<T>,
+    ) {
+        match (old, new) {
+            (None, Some(_)) => self.narrowed(path, name),
+            (Some(_), None) => self.relaxed(path, name),
+            (Some(old), Some(new)) if new > old => self.narrowed(path, name),
+            (Some(old), Some(new)) if new < old => self.relaxed(path, name),
+            _ => {}
+        }
+    }
+
+    /// Compare an upper bound, like `maximum` or `maxLength`.
+    fn upper<T: PartialOrd>(
+        &mut self,
+        path: &str,
+        name: &'static str,
+        old: Option<T>,
+        new: Option<T>,
+    ) {
+        match (old, new) {
+            (None, Some(_)) => self.narrowed(path, name),
+            (Some(_), None) => self.relaxed(path, name),
+            (Some(old), Some(new)) if new < old => self.narrowed(path, name),
+            (Some(old), Some(new)) if new > old => self.relaxed(path, name),
+            _ => {}
+        }
+    }
+
+    /// Compare a constraint admitting exactly one value, like `const`.
+    fn fixed<T: PartialEq>(
+        &mut self,
+        path: &str,
+        name: &'static str,
+        old: Option<&T>,
+        new: Option<&T>,
+    ) {
+        match (old, new) {
+            (None, Some(_)) => self.narrowed(path, name),
+            (Some(_), None) => self.relaxed(path, name),
+            (Some(old), Some(new)) if old != new => self.narrowed(path, name),
+            _ => {}
+        }
+    }
+
+    /// Compare a constraint admitting a set of values, like `enum`.
+    fn values<T: PartialEq>(
+        &mut self,
+        path: &str,
+        name: &'static str,
+        old: Option<&[T]>,
+        new: Option<&[T]>,
+    ) {
+        match (old, new) {
+            (None, Some(_)) => self.narrowed(path, name),
+            (Some(_), None) => self.relaxed(path, name),
+            (Some(old), Some(new)) => {
+                if old.iter().any(|value| !new.contains(value)) {
+                    self.narrowed(path, name);
+                } else if new.iter().any(|value| !old.contains(value)) {
+                    self.relaxed(path, name);
+                }
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{from_value, json};
+
+    use super::{diff, diff_documents, SchemaChange};
+    use crate::schema::{Document, RecordKey, Schema};
+
+    fn document(defs: serde_json::Value) -> Document {
+        from_value(json!({
+            "lexicon": 1,
+            "id": "com.example.thing",
+            "defs": defs,
+        }))
+        .expect("test document")
+    }
+
+    fn record(key: &str, properties: serde_json::Value, required: &[&str]) -> Document {
+        document(json!({
+            "main": {
+                "type": "record",
+                "key": key,
+                "record": {"type": "object", "properties": properties, "required": required},
+            },
+        }))
+    }
+
+    #[test]
+    fn test_diff_unchanged() {
+        let old = record("tid", json!({"text": {"type": "string"}}), &["text"]);
+        assert_eq!(diff_documents(&old, &old.clone()), vec![]);
+    }
+
+    #[test]
+    fn test_diff_tokens() {
+        let old = document(json!({
+            "less": {"type": "token", "description": "Show less."},
+            "more": {"type": "token"},
+        }));
+        assert_eq!(diff_documents(&old, &old.clone()), vec![]);
+
+        let new = document(json!({
+            "less": {"type": "token", "description": "Show less of this."},
+            "more": {"type": "string"},
+        }));
+        assert_eq!(
+            diff_documents(&old, &new),
+            vec![SchemaChange::TypeChanged {
+                path: "com.example.thing#more".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_unsupported() {
+        let old = document(json!({
+            "main": {"type": "subscription", "message": {"schema": {"type": "union", "refs": []}}},
+        }));
+        assert_eq!(diff_documents(&old, &old.clone()), vec![]);
+
+        let new = document(json!({
+            "main": {"type": "subscription", "message": {"schema": {"type": "union", "refs": ["#a"]}}},
+        }));
+        let changes = diff_documents(&old, &new);
+        assert_eq!(
+            changes,
+            vec![SchemaChange::UnsupportedChanged {
+                path: "com.example.thing#main".to_owned()
+            }]
+        );
+        assert!(!changes[0].is_breaking());
+
+        let new = document(json!({"main": {"type": "firehose"}}));
+        assert_eq!(
+            diff_documents(&old, &new),
+            vec![SchemaChange::TypeChanged {
+                path: "com.example.thing#main".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_properties() {
+        let old = record(
+            "tid",
+            json!({"text": {"type": "string"}, "tags": {"type": "array", "items": {"type": "string"}}}),
+            &["text"],
+        );
+        let new = record(
+            "tid",
+            json!({"text": {"type": "string"}, "lang": {"type": "string"}, "title": {"type": "string"}}),
+            &["text", "title"],
+        );
+
+        let changes = diff_documents(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                SchemaChange::PropertyRemoved {
+                    path: "com.example.thing#main.tags".to_owned()
+                },
+                SchemaChange::PropertyAdded {
+                    path: "com.example.thing#main.lang".to_owned(),
+                    required: false,
+                },
+                SchemaChange::PropertyAdded {
+                    path: "com.example.thing#main.title".to_owned(),
+                    required: true,
+                },
+            ]
+        );
+
+        let breaking: Vec<_> = changes.iter().map(SchemaChange::is_breaking).collect();
+        assert_eq!(breaking, vec![true, false, true]);
+    }
+
+    #[test]
+    fn test_diff_constraints() {
+        let old = record(
+            "tid",
+            json!({
+                "text": {"type": "string", "maxLength": 300},
+                "count": {"type": "integer", "minimum": 0, "maximum": 10},
+            }),
+            &[],
+        );
+        let new = record(
+            "tid",
+            json!({
+                "text": {"type": "string", "maxLength": 3000},
+                "count": {"type": "integer", "minimum": 1, "maximum": 10},
+            }),
+            &[],
+        );
+
+        let changes = diff_documents(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                SchemaChange::Relaxed {
+                    path: "com.example.thing#main.text".to_owned(),
+                    constraint: "maxLength",
+                },
+                SchemaChange::Narrowed {
+                    path: "com.example.thing#main.count".to_owned(),
+                    constraint: "minimum",
+                },
+            ]
+        );
+        assert!(!changes[0].is_breaking());
+        assert!(changes[1].is_breaking());
+    }
+
+    #[test]
+    fn test_diff_types() {
+        let old = record("tid", json!({"count": {"type": "integer"}}), &[]);
+        let new = record("any", json!({"count": {"type": "string"}}), &[]);
+
+        let changes = diff_documents(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                SchemaChange::RecordKeyChanged {
+                    path: "com.example.thing#main".to_owned(),
+                    from: RecordKey::Tid,
+                    to: RecordKey::Any,
+                },
+                SchemaChange::TypeChanged {
+                    path: "com.example.thing#main.count".to_owned()
+                },
+            ]
+        );
+        assert!(changes.iter().all(SchemaChange::is_breaking));
+    }
+
+    #[test]
+    fn test_diff_schemas() {
+        let thing = record("tid", json!({}), &[]);
+        let mut other = thing.clone();
+        other.id = "com.example.other".parse().unwrap();
+
+        let old: Schema = [(thing.id.clone(), thing.clone())].into_iter().collect();
+        let new: Schema = [(other.id.clone(), other.clone())].into_iter().collect();
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                SchemaChange::DocumentRemoved(thing.id.clone()),
+                SchemaChange::DocumentAdded(other.id.clone()),
+            ]
+        );
+    }
+}
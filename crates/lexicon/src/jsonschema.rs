@@ -0,0 +1,366 @@
+//! Conversion of Lexicon documents to [JSON Schema][spec].
+//!
+//! [spec]: https://json-schema.org/draft/2020-12/json-schema-core
+
+use serde_json::{json, Map as JsonMap, Value};
+
+use crate::schema::{
+    Array, Blob, Boolean, Bytes, Document, Integer, Metadata, Node, Nsid, Object, RecordDefinition,
+    Ref, RefTarget, String, StringFormat, Union,
+};
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+impl Document {
+    /// Convert the data definitions in this document to a [JSON Schema][spec]
+    /// (draft 2020-12).
+    ///
+    /// Each record, object, array, and primitive definition becomes an entry
+    /// in `$defs`, and the schema's `$id` is the document's `lex:` URI, so
+    /// refs to other documents (like `lex:app.bsky.feed.defs#/$defs/postView`)
+    /// can be resolved by a tool which has their schemas too. If `main` is a
+    /// data definition, the schema as a whole refers to it.
+    ///
+    /// The conversion is not lossless. Queries, procedures, tokens, and
+    /// definitions of unsupported types are left out. String lengths are in
+    /// UTF-8 bytes in Lexicon but in characters in JSON Schema, so
+    /// `maxLength` and `minLength` are derived from `maxLength` and
+    /// `minGraphemes`, which accept every string the lexicon does but also
+    /// some that it doesn't.
+    /// Formats without a JSON Schema equivalent are described with a
+    /// `pattern` where one is simple enough, and otherwise only by their
+    /// Lexicon `format` name.
+    ///
+    /// ```
+    /// use atprose_lexicon::schema::Document;
+    ///
+    /// let document: Document = serde_json::from_str(r#"{
+    ///     "lexicon": 1,
+    ///     "id": "com.example.defs",
+    ///     "defs": {"count": {"type": "integer", "minimum": 0}}
+    /// }"#).unwrap();
+    ///
+    /// let schema = document.to_json_schema();
+    /// assert_eq!("lex:com.example.defs", schema["$id"]);
+    /// assert_eq!(0, schema["$defs"]["count"]["minimum"]);
+    /// ```
+    ///
+    /// [spec]: https://json-schema.org/draft/2020-12/json-schema-core
+    #[cfg_attr(docsrs, doc(cfg(feature = "jsonschema")))]
+    pub fn to_json_schema(&self) -> Value {
+        let defs: JsonMap<_, _> = self
+            .defs
+            .iter()
+            .filter_map(|(name, def)| Some((name.clone(), node(def.into(), &self.id)?)))
+            .collect();
+
+        let mut schema = json!({
+            "$schema": DIALECT,
+            "$id": format!("lex:{}", self.id),
+        });
+        describe(&mut schema, &self.metadata);
+        if defs.contains_key("main") {
+            schema["$ref"] = json!("#/$defs/main");
+        }
+        schema["$defs"] = Value::Object(defs);
+
+        schema
+    }
+}
+
+/// The JSON Schema for a lexicon type, if it is a data type.
+fn node(node: Node<'_>, base: &Nsid) -> Option<Value> {
+    Some(match node {
+        Node::Record(record) => {
+            let RecordDefinition::Object(value) = &record.def;
+            let mut schema = object(value, base);
+            describe(&mut schema, &record.metadata);
+            schema
+        }
+        Node::Array(value) => array(value, base),
+        Node::Object(value) => object(value, base),
+        Node::Blob(value) => blob(value),
+        Node::Boolean(value) => boolean(value),
+        Node::Bytes(value) => bytes(value),
+        Node::Integer(value) => integer(value),
+        Node::Link(value) => link(&value.metadata),
+        Node::String(value) => string(value),
+        Node::Unknown(value) => unknown(&value.metadata),
+        Node::Ref(value) => reference(value, base),
+        Node::Union(value) => union(value, base),
+        Node::Query(_)
+        | Node::Procedure(_)
+        | Node::Parameters(_)
+        | Node::Body(_)
+        | Node::ParameterArray(_)
+        | Node::Token(_)
+        | Node::Unsupported { .. } => return None,
+    })
+}
+
+/// The JSON Schema for an object property or array item, which is always a
+/// data type.
+fn data_type<'a>(value: impl Into<Node<'a>>, base: &Nsid) -> Value {
+    node(value.into(), base).expect("properties and array items are data types")
+}
+
+fn object(value: &Object, base: &Nsid) -> Value {
+    let properties: JsonMap<_, _> = value
+        .properties
+        .iter()
+        .map(|(name, prop)| {
+            let schema = data_type(prop, base);
+            let schema = if value.nullable.contains(name) {
+                json!({"anyOf": [schema, {"type": "null"}]})
+            } else {
+                schema
+            };
+
+            (name.clone(), schema)
+        })
+        .collect();
+
+    let mut schema = json!({"type": "object", "properties": properties});
+    if !value.required.is_empty() {
+        schema["required"] = json!(value.required);
+    }
+    describe(&mut schema, &value.metadata);
+    schema
+}
+
+fn array(value: &Array, base: &Nsid) -> Value {
+    let mut schema = json!({"type": "array", "items": data_type(&value.items, base)});
+    set(&mut schema, "minItems", value.min_length);
+    set(&mut schema, "maxItems", value.max_length);
+    describe(&mut schema, &value.metadata);
+    schema
+}
+
+fn boolean(value: &Boolean) -> Value {
+    let mut schema = json!({"type": "boolean"});
+    set(&mut schema, "const", value.value);
+    set(&mut schema, "default", value.default);
+    describe(&mut schema, &value.metadata);
+    schema
+}
+
+fn integer(value: &Integer) -> Value {
+    let mut schema = json!({"type": "integer"});
+    set(&mut schema, "const", value.value);
+    set(&mut schema, "enum", value.values.as_ref());
+    set(&mut schema, "minimum", value.minimum);
+    set(&mut schema, "maximum", value.maximum);
+    set(&mut schema, "default", value.default);
+    describe(&mut schema, &value.metadata);
+    schema
+}
+
+fn string(value: &String) -> Value {
+    let mut schema = json!({"type": "string"});
+
+    if let Some(format) = &value.format {
+        schema["format"] = json!(match format {
+            StringFormat::Datetime => "date-time".to_string(),
+            StringFormat::Uri => "uri".to_string(),
+            format => format.to_string(),
+        });
+        set(&mut schema, "pattern", pattern(format));
+    }
+
+    set(&mut schema, "const", value.value.as_ref());
+    set(&mut schema, "enum", value.values.as_ref());
+    set(&mut schema, "examples", value.known_values.as_ref());
+    // Every grapheme is at least one character, and every character at least
+    // one byte.
+    set(&mut schema, "minLength", value.min_graphemes);
+    set(&mut schema, "maxLength", value.max_length);
+    set(&mut schema, "default", value.default.as_ref());
+    describe(&mut schema, &value.metadata);
+    schema
+}
+
+/// A regular expression for strings in `format`, if it has a simple one.
+fn pattern(format: &StringFormat) -> Option<&'static str> {
+    match format {
+        StringFormat::AtUri => Some("^at://"),
+        StringFormat::Did => Some("^did:[a-z]+:[a-zA-Z0-9._:%-]*[a-zA-Z0-9._-]$"),
+        StringFormat::Handle => Some(
+            "^([a-zA-Z0-9]([a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?\\.)+[a-zA-Z]([a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?$",
+        ),
+        StringFormat::Nsid => Some(
+            "^[a-zA-Z]([a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?(\\.[a-zA-Z0-9]([a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?)+\\.[a-zA-Z][a-zA-Z0-9]{0,62}$",
+        ),
+        StringFormat::RecordKey => Some("^[a-zA-Z0-9._:~-]{1,512}$"),
+        StringFormat::Tid => Some("^[234567abcdefghij][234567abcdefghijklmnopqrstuvwxyz]{12}$"),
+        _ => None,
+    }
+}
+
+fn blob(value: &Blob) -> Value {
+    let mut size = json!({"type": "integer", "minimum": 0});
+    set(&mut size, "maximum", value.max_size);
+
+    let mut schema = json!({
+        "type": "object",
+        "properties": {
+            "$type": {"const": "blob"},
+            "ref": link(&Metadata::default()),
+            "mimeType": {"type": "string"},
+            "size": size,
+        },
+        "required": ["$type", "ref", "mimeType", "size"],
+    });
+    describe(&mut schema, &value.metadata);
+    schema
+}
+
+fn bytes(value: &Bytes) -> Value {
+    let mut schema = json!({
+        "type": "object",
+        "properties": {"$bytes": {"type": "string", "contentEncoding": "base64"}},
+        "required": ["$bytes"],
+    });
+    describe(&mut schema, &value.metadata);
+    schema
+}
+
+fn link(metadata: &Metadata) -> Value {
+    let mut schema = json!({
+        "type": "object",
+        "properties": {"$link": {"type": "string"}},
+        "required": ["$link"],
+    });
+    describe(&mut schema, metadata);
+    schema
+}
+
+fn unknown(metadata: &Metadata) -> Value {
+    let mut schema = json!({"type": "object"});
+    describe(&mut schema, metadata);
+    schema
+}
+
+fn reference(value: &Ref, base: &Nsid) -> Value {
+    let mut schema = json!({"$ref": ref_uri(&value.target, base)});
+    describe(&mut schema, &value.metadata);
+    schema
+}
+
+/// A union is any of its options, or (if it's open) any object with a
+/// `$type`.
+fn union(value: &Union, base: &Nsid) -> Value {
+    let mut options: Vec<_> = value
+        .options
+        .iter()
+        .map(|target| json!({"$ref": ref_uri(target, base)}))
+        .collect();
+    if value.closed != Some(true) {
+        options.push(json!({"type": "object", "required": ["$type"]}));
+    }
+
+    let mut schema = json!({"anyOf": options});
+    describe(&mut schema, &value.metadata);
+    schema
+}
+
+/// The JSON Schema reference for `target`, relative to the schema of `base`.
+fn ref_uri(target: &RefTarget, base: &Nsid) -> std::string::String {
+    let name = target.name().unwrap_or("main");
+
+    match target.ns() {
+        Some(ns) if ns != base => format!("lex:{ns}#/$defs/{name}"),
+        _ => format!("#/$defs/{name}"),
+    }
+}
+
+fn describe(schema: &mut Value, metadata: &Metadata) {
+    set(schema, "description", metadata.description.as_ref());
+}
+
+fn set(schema: &mut Value, key: &str, value: Option<impl serde::Serialize>) {
+    if let Some(value) = value {
+        schema[key] = json!(value);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::schema::Document;
+
+    #[test]
+    fn test_to_json_schema() {
+        let document: Document = serde_json::from_str(
+            r##"{
+                "lexicon": 1,
+                "id": "com.example.post",
+                "defs": {
+                    "main": {
+                        "type": "record",
+                        "key": "tid",
+                        "description": "A post.",
+                        "record": {
+                            "type": "object",
+                            "required": ["text", "createdAt"],
+                            "nullable": ["reply"],
+                            "properties": {
+                                "text": {"type": "string", "maxLength": 3000, "maxGraphemes": 300},
+                                "createdAt": {"type": "string", "format": "datetime"},
+                                "langs": {"type": "array", "maxLength": 3, "items": {"type": "string", "format": "language"}},
+                                "reply": {"type": "ref", "ref": "#replyRef"},
+                                "embed": {"type": "union", "refs": ["com.example.embed#main", "#replyRef"]},
+                                "likes": {"type": "integer", "minimum": 0}
+                            }
+                        }
+                    },
+                    "replyRef": {
+                        "type": "object",
+                        "properties": {"parent": {"type": "string", "format": "at-uri"}}
+                    },
+                    "getPost": {"type": "query"}
+                }
+            }"##,
+        )
+        .unwrap();
+
+        let schema = document.to_json_schema();
+        assert_eq!(
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$id": "lex:com.example.post",
+                "$ref": "#/$defs/main",
+                "$defs": {
+                    "main": {
+                        "type": "object",
+                        "description": "A post.",
+                        "required": ["text", "createdAt"],
+                        "properties": {
+                            "text": {"type": "string", "maxLength": 3000},
+                            "createdAt": {"type": "string", "format": "date-time"},
+                            "langs": {
+                                "type": "array",
+                                "maxItems": 3,
+                                "items": {"type": "string", "format": "language"}
+                            },
+                            "reply": {"anyOf": [{"$ref": "#/$defs/replyRef"}, {"type": "null"}]},
+                            "embed": {"anyOf": [
+                                {"$ref": "lex:com.example.embed#/$defs/main"},
+                                {"$ref": "#/$defs/replyRef"},
+                                {"type": "object", "required": ["$type"]}
+                            ]},
+                            "likes": {"type": "integer", "minimum": 0}
+                        }
+                    },
+                    "replyRef": {
+                        "type": "object",
+                        "properties": {
+                            "parent": {"type": "string", "format": "at-uri", "pattern": "^at://"}
+                        }
+                    }
+                }
+            }),
+            schema
+        );
+    }
+}
@@ -4,9 +4,31 @@ pub mod schema;
 
 pub use indexmap::{IndexMap as Map, IndexSet as Set};
 
+mod analysis;
+pub use analysis::{string_enums, tokens, StringEnum};
+
+mod diff;
+pub use diff::{diff, diff_documents, SchemaChange};
+
+mod validate;
+pub use validate::{BlobError, LengthError, StringError, StringValidator, ValidationError};
+
+#[cfg(feature = "jsonschema")]
+mod jsonschema;
+
 #[cfg(feature = "load")]
 mod load;
 
+#[cfg(feature = "snapshot")]
+mod snapshot;
+
+#[cfg(feature = "snapshot")]
+#[cfg_attr(docsrs, doc(cfg(feature = "snapshot")))]
+pub use snapshot::SnapshotError;
+
 #[cfg(feature = "load")]
 #[cfg_attr(docsrs, doc(cfg(feature = "load")))]
-pub use load::{load, load_document};
+pub use load::{
+    load, load_array, load_document, load_document_strict, load_jsonl, save, LoadError, Loader,
+    Location,
+};
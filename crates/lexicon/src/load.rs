@@ -1,36 +1,597 @@
-use std::{fs, io, path::Path};
+use std::{
+    fmt, fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
 
 use globwalk::{GlobWalker, GlobWalkerBuilder};
 use serde_json::from_reader;
 
-use super::schema::{self, Schema};
+use super::schema::{self, Nsid, Schema};
 
-pub fn load(base: impl AsRef<Path>) -> io::Result<Schema> {
-    let mut schema = Schema::new();
+/// Load every `.json` document under `base` into a schema.
+///
+/// See [`Loader`] to configure which files are loaded.
+pub fn load(base: impl AsRef<Path>) -> Result<Schema, LoadError> {
+    Loader::new(base).load()
+}
+
+/// Loads lexicon documents from files matching a glob pattern.
+///
+/// ```no_run
+/// use atprose_lexicon::Loader;
+///
+/// # fn main() -> Result<(), atprose_lexicon::LoadError> {
+/// let schema = Loader::new("lexicons")
+///     .pattern("app/bsky/**/*.json")
+///     .follow_links(true)
+///     .load()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Loader {
+    base: PathBuf,
+    patterns: Vec<String>,
+    follow_links: bool,
+    strict: bool,
+    check_paths: bool,
+}
+
+impl Loader {
+    /// Create a loader for the `*.json` files in `base` and its
+    /// subdirectories.
+    pub fn new(base: impl AsRef<Path>) -> Self {
+        Self {
+            base: base.as_ref().to_owned(),
+            patterns: Vec::new(),
+            follow_links: false,
+            strict: false,
+            check_paths: false,
+        }
+    }
+
+    /// Load files matching the glob `pattern`, relative to the base
+    /// directory, instead of `*.json`.
+    ///
+    /// This can be called more than once to load files matching any of the
+    /// patterns. Patterns use [gitignore][] syntax, so a pattern starting with
+    /// `!` excludes files.
+    ///
+    /// [gitignore]: https://git-scm.com/docs/gitignore#_pattern_format
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.patterns.push(pattern.into());
+        self
+    }
+
+    /// Whether to follow symbolic links when searching for files (by default,
+    /// they are not).
+    pub fn follow_links(mut self, follow_links: bool) -> Self {
+        self.follow_links = follow_links;
+        self
+    }
+
+    /// Whether to reject documents with fields this crate doesn't recognize
+    /// (by default, they are ignored).
+    ///
+    /// See [`Document::from_str_strict`][schema::Document::from_str_strict].
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Whether to require each document to be in the file its NSID names,
+    /// relative to the base directory (by default, any file may hold any
+    /// document).
+    ///
+    /// This is the layout [`save`] writes: `app.bsky.feed.post` must be in
+    /// `app/bsky/feed/post.json`. A document found anywhere else is reported
+    /// as [`LoadError::Misplaced`].
+    pub fn check_paths(mut self, check_paths: bool) -> Self {
+        self.check_paths = check_paths;
+        self
+    }
+
+    pub fn load(&self) -> Result<Schema, LoadError> {
+        let mut schema = Schema::new();
+
+        for file in self.walk()? {
+            let file = file?;
+            if !file.file_type().is_file() {
+                continue;
+            }
+
+            let document = if self.strict {
+                load_document_strict(file.path())?
+            } else {
+                load_document(file.path())?
+            };
 
-    for file in glob(base, "*.json") {
-        let file = match file {
-            Ok(file) => file,
-            Err(error) => return Err(io::Error::other(error)),
+            if self.check_paths {
+                let expected = document_path(&self.base, &document.id);
+                if file.path() != expected {
+                    return Err(LoadError::Misplaced {
+                        id: document.id,
+                        path: file.into_path(),
+                        expected,
+                    });
+                }
+            }
+
+            insert(&mut schema, document)?;
+        }
+
+        Ok(schema)
+    }
+
+    fn walk(&self) -> Result<GlobWalker, LoadError> {
+        let walker = if self.patterns.is_empty() {
+            GlobWalkerBuilder::new(&self.base, "*.json")
+        } else {
+            GlobWalkerBuilder::from_patterns(&self.base, &self.patterns)
         };
 
-        let document = load_document(file.path())?;
-        schema.insert(document.id.clone(), document);
+        Ok(walker.follow_links(self.follow_links).build()?)
     }
+}
 
-    Ok(schema)
+pub fn load_document(path: impl AsRef<Path>) -> Result<schema::Document, LoadError> {
+    let path = path.as_ref();
+    let file = fs::File::open(path)?;
+    let reader = io::BufReader::new(file);
+
+    from_reader(reader).map_err(|error| LoadError::Parse {
+        location: Location::File(path.to_owned()),
+        error,
+    })
 }
 
-pub fn load_document(path: impl AsRef<Path>) -> io::Result<schema::Document> {
+/// Load a lexicon document, rejecting any field this crate doesn't
+/// recognize.
+///
+/// See [`Document::from_str_strict`][schema::Document::from_str_strict].
+pub fn load_document_strict(path: impl AsRef<Path>) -> Result<schema::Document, LoadError> {
+    let path = path.as_ref();
     let file = fs::File::open(path)?;
     let reader = io::BufReader::new(file);
 
-    let document = from_reader(reader).map_err(io::Error::other)?;
-    Ok(document)
+    from_reader(reader)
+        .and_then(schema::Document::from_value_strict)
+        .map_err(|error| LoadError::Parse {
+            location: Location::File(path.to_owned()),
+            error,
+        })
+}
+
+/// Load a schema from [JSON lines][jsonl]: one document per line.
+///
+/// Blank lines are skipped.
+///
+/// [jsonl]: https://jsonlines.org/
+pub fn load_jsonl(reader: impl BufRead) -> Result<Schema, LoadError> {
+    let mut schema = Schema::new();
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let document = serde_json::from_str(&line).map_err(|error| LoadError::Parse {
+            location: Location::Line(i + 1),
+            error,
+        })?;
+        insert(&mut schema, document)?;
+    }
+
+    Ok(schema)
+}
+
+/// Load a schema from a JSON array of documents.
+///
+/// An error parsing a document reports its (zero-based) index in the array.
+/// Input which isn't a JSON array at all is reported as a
+/// [parse error][LoadError::Parse] in the whole [input][Location::Input].
+pub fn load_array(reader: impl io::Read) -> Result<Schema, LoadError> {
+    let values: Vec<serde_json::Value> = from_reader(reader).map_err(|error| {
+        if error.is_io() {
+            LoadError::Io(error.into())
+        } else {
+            LoadError::Parse {
+                location: Location::Input,
+                error,
+            }
+        }
+    })?;
+    let mut schema = Schema::new();
+
+    for (i, value) in values.into_iter().enumerate() {
+        let document = serde_json::from_value(value).map_err(|error| LoadError::Parse {
+            location: Location::Index(i),
+            error,
+        })?;
+        insert(&mut schema, document)?;
+    }
+
+    Ok(schema)
+}
+
+/// Write each document in `schema` to its own pretty-printed JSON file under
+/// `base`, creating directories as needed.
+///
+/// Each document is stored at a path made from the segments of its NSID, so
+/// `app.bsky.feed.post` is written to `base/app/bsky/feed/post.json`. This is
+/// the layout [`load`] expects, and output is stable: definitions and
+/// properties are written in the order they appear in the schema.
+pub fn save(schema: &Schema, base: impl AsRef<Path>) -> Result<(), LoadError> {
+    let base = base.as_ref();
+
+    for (id, document) in schema {
+        let path = document_path(base, id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut writer = io::BufWriter::new(fs::File::create(&path)?);
+        serde_json::to_writer_pretty(&mut writer, document).map_err(io::Error::from)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+/// The path of the file under `base` which should hold the document `id`.
+fn document_path(base: &Path, id: &Nsid) -> PathBuf {
+    let mut path = base.to_owned();
+    path.extend(id.segments());
+    path.set_extension("json");
+    path
+}
+
+/// Add `document` to `schema`, unless a document with the same NSID was
+/// already loaded.
+fn insert(schema: &mut Schema, document: schema::Document) -> Result<(), LoadError> {
+    if schema.contains_key(&document.id) {
+        return Err(LoadError::Duplicate(document.id));
+    }
+
+    schema.insert(document.id.clone(), document);
+    Ok(())
+}
+
+/// An error loading a [`Schema`].
+#[derive(thiserror::Error, Debug)]
+pub enum LoadError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("invalid glob pattern: {0}")]
+    Glob(#[from] globwalk::GlobError),
+    #[error("failed to search for lexicon files: {0}")]
+    Walk(#[from] globwalk::WalkError),
+    #[error("invalid lexicon document in {location}: {error}")]
+    Parse {
+        location: Location,
+        #[source]
+        error: serde_json::Error,
+    },
+    #[error("duplicate lexicon document {0}")]
+    Duplicate(Nsid),
+    #[error("lexicon document {id} is in {}, not {}", path.display(), expected.display())]
+    Misplaced {
+        id: Nsid,
+        path: PathBuf,
+        /// The file under the base directory named by the document's NSID.
+        expected: PathBuf,
+    },
 }
 
-fn glob(base: impl AsRef<Path>, pattern: impl AsRef<str>) -> GlobWalker {
-    GlobWalkerBuilder::new(base, pattern)
-        .build()
-        .expect("globwalk")
+/// Where a lexicon document being [loaded][LoadError] came from.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Location {
+    File(PathBuf),
+    Line(usize),
+    /// An index in a JSON array, from [`load_array`].
+    Index(usize),
+    /// The whole input, which isn't a JSON array, from [`load_array`].
+    Input,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::File(path) => write!(f, "{}", path.display()),
+            Location::Line(line) => write!(f, "line {line}"),
+            Location::Index(index) => write!(f, "array index {index}"),
+            Location::Input => write!(f, "input"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{load, load_array, load_jsonl, save, LoadError, Loader, Location};
+    use crate::schema::{Definition, Nsid};
+    use crate::{diff, diff_documents, SchemaChange, Set};
+
+    #[test]
+    fn test_load_jsonl() {
+        let input = concat!(
+            r#"{"lexicon": 1, "id": "com.example.foo", "defs": {}}"#,
+            "\n\n",
+            r#"{"lexicon": 1, "id": "com.example.bar", "defs": {}}"#,
+            "\n",
+        );
+
+        let schema = load_jsonl(input.as_bytes()).expect("load JSON lines");
+        let ids: Vec<_> = schema.keys().map(ToString::to_string).collect();
+        assert_eq!(vec!["com.example.foo", "com.example.bar"], ids);
+
+        let input = concat!(
+            r#"{"lexicon": 1, "id": "com.example.foo", "defs": {}}"#,
+            "\n",
+            r#"{"lexicon": 1, "id": "com.example.foo", "defs": {}}"#,
+        );
+        match load_jsonl(input.as_bytes()) {
+            Err(LoadError::Duplicate(id)) => assert_eq!(Nsid::new("com.example", "foo"), id),
+            result => panic!("expected duplicate error, got {result:?}"),
+        }
+
+        let input = concat!(
+            r#"{"lexicon": 1, "id": "com.example.foo", "defs": {}}"#,
+            "\n",
+            r#"{"lexicon": 1, "defs": {}}"#,
+        );
+        match load_jsonl(input.as_bytes()) {
+            Err(LoadError::Parse { location, .. }) => assert_eq!(Location::Line(2), location),
+            result => panic!("expected parse error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_load_array() {
+        let input = r#"[
+            {"lexicon": 1, "id": "com.example.foo", "defs": {}},
+            {"lexicon": 1, "id": "com.example.bar", "defs": {}}
+        ]"#;
+        let schema = load_array(input.as_bytes()).expect("load array");
+        let ids: Vec<_> = schema.keys().map(ToString::to_string).collect();
+        assert_eq!(vec!["com.example.foo", "com.example.bar"], ids);
+
+        let input = r#"[
+            {"lexicon": 1, "id": "com.example.foo", "defs": {}},
+            {"lexicon": 1, "id": "com.example.foo", "defs": {}}
+        ]"#;
+        match load_array(input.as_bytes()) {
+            Err(LoadError::Duplicate(id)) => assert_eq!(Nsid::new("com.example", "foo"), id),
+            result => panic!("expected duplicate error, got {result:?}"),
+        }
+
+        let input = r#"[
+            {"lexicon": 1, "id": "com.example.foo", "defs": {}},
+            {"lexicon": 1, "defs": {}}
+        ]"#;
+        let err = load_array(input.as_bytes()).expect_err("parse error");
+        assert!(
+            matches!(
+                &err,
+                LoadError::Parse {
+                    location: Location::Index(1),
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+        assert!(err.to_string().contains("array index 1"), "{err}");
+
+        for input in [
+            r#"{"lexicon": 1, "id": "com.example.foo", "defs": {}}"#,
+            "[{",
+        ] {
+            match load_array(input.as_bytes()) {
+                Err(LoadError::Parse {
+                    location: Location::Input,
+                    ..
+                }) => {}
+                result => panic!("expected parse error, got {result:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_save() {
+        let input = concat!(
+            r#"{"lexicon": 1, "id": "com.example.foo", "defs": {"main": {"type": "boolean"}}}"#,
+            "\n",
+            r#"{"lexicon": 1, "id": "com.example.bar.baz", "defs": {}}"#,
+        );
+        let schema = load_jsonl(input.as_bytes()).expect("load JSON lines");
+
+        let base = std::env::temp_dir().join(format!("atprose-save-{}", std::process::id()));
+        save(&schema, &base).expect("save schema");
+
+        assert!(base.join("com/example/foo.json").is_file());
+        assert!(base.join("com/example/bar/baz.json").is_file());
+
+        let loaded = load(&base).expect("load saved schema");
+        let bar = Loader::new(&base)
+            .pattern("com/example/bar/*.json")
+            .load()
+            .expect("load saved schema with pattern");
+        std::fs::remove_dir_all(&base).expect("remove saved schema");
+
+        assert_eq!(schema, loaded);
+        let ids: Vec<_> = bar.keys().map(ToString::to_string).collect();
+        assert_eq!(vec!["com.example.bar.baz"], ids);
+    }
+
+    /// Definitions in the corpus of types this crate doesn't model, which are
+    /// expected to load as [`Definition::Unsupported`].
+    const UNSUPPORTED: &[(&str, &str)] = &[
+        ("com.atproto.label.subscribeLabels", "subscription"),
+        ("com.atproto.sync.subscribeRepos", "subscription"),
+    ];
+
+    /// Every lexicon under `test/lexicons` (synced from upstream by
+    /// `test/sync-lexicons.sh`) deserializes, serializes back to the same
+    /// JSON it was read from, and uses only the types this crate models or
+    /// lists in [`UNSUPPORTED`].
+    #[test]
+    fn test_corpus() {
+        use crate::schema::{Document, TypeId};
+
+        let base = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test/lexicons");
+
+        let mut failures = Vec::new();
+        for file in Loader::new(&base).walk().expect("walk corpus") {
+            let file = file.expect("walk corpus");
+            if !file.file_type().is_file() {
+                continue;
+            }
+
+            let path = file.into_path();
+            let json = std::fs::read_to_string(&path).expect("read lexicon");
+            let expected: serde_json::Value = serde_json::from_str(&json).expect("parse JSON");
+
+            match serde_json::from_str::<Document>(&json) {
+                Ok(document) => {
+                    let actual = serde_json::to_value(&document).expect("serialize document");
+                    if expected != actual {
+                        failures.push(format!("{}: does not round-trip", path.display()));
+                    }
+
+                    for (name, def) in &document.defs {
+                        let Definition::Unsupported { type_name, .. } = def else {
+                            continue;
+                        };
+                        let id = TypeId::of(&document.id, name).to_string();
+                        if !UNSUPPORTED.contains(&(id.as_str(), type_name.as_str())) {
+                            failures.push(format!("{id}: unsupported type {type_name:?}"));
+                        }
+                    }
+                }
+                Err(error) => failures.push(format!("{}: {error}", path.display())),
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+
+        let schema = load(&base).unwrap_or_else(|error| panic!("{error}"));
+        assert!(!schema.is_empty());
+
+        let strict = Loader::new(&base).strict(true).check_paths(true).load();
+        assert_eq!(schema, strict.unwrap_or_else(|error| panic!("{error}")));
+
+        #[cfg(feature = "snapshot")]
+        {
+            use crate::schema::{Schema, SchemaExt};
+
+            let snapshot = schema.to_bytes().expect("write snapshot");
+            assert_eq!(
+                schema,
+                Schema::from_bytes(&snapshot).expect("read snapshot")
+            );
+        }
+
+        // Refs to documents outside the synced namespaces (or outside a
+        // partial sync) can't be resolved.
+        for document in schema.values() {
+            let errors = document.validate(&schema).err().unwrap_or_default();
+            for error in errors {
+                let target = error.message.strip_prefix("unresolved ref ");
+                let ns = target.and_then(|target| target.split('#').next());
+                assert!(
+                    ns.is_some_and(|ns| !schema.contains_key(&ns.parse::<Nsid>().unwrap())),
+                    "{error}"
+                );
+            }
+        }
+
+        let kinds: Set<_> = schema
+            .values()
+            .flat_map(|document| document.defs.values().map(Definition::kind))
+            .collect();
+        for kind in [
+            "record",
+            "query",
+            "procedure",
+            "object",
+            "token",
+            "subscription",
+        ] {
+            assert!(kinds.contains(kind), "no {kind} definitions in the corpus");
+        }
+
+        for document in schema.values() {
+            assert_eq!(
+                Vec::<SchemaChange>::new(),
+                diff_documents(document, document),
+                "{}",
+                document.id
+            );
+        }
+        assert_eq!(Vec::<SchemaChange>::new(), diff(&schema, &schema));
+    }
+
+    #[test]
+    fn test_loader_errors() {
+        match Loader::new(".").pattern("[").load() {
+            Err(LoadError::Glob(_)) => {}
+            result => panic!("expected glob error, got {result:?}"),
+        }
+
+        let base = std::env::temp_dir().join(format!("atprose-strict-{}", std::process::id()));
+        std::fs::create_dir_all(&base).expect("create directory");
+        std::fs::write(
+            base.join("foo.json"),
+            r#"{"lexicon": 1, "id": "com.example.foo", "defs": {"main": {"type": "string", "maxLenght": 3}}}"#,
+        )
+        .expect("write lexicon");
+
+        let lenient = Loader::new(&base).load();
+        let strict = Loader::new(&base).strict(true).load();
+        std::fs::remove_dir_all(&base).expect("remove directory");
+
+        assert_eq!(1, lenient.expect("load lexicons").len());
+        match strict {
+            Err(LoadError::Parse { location, error }) => {
+                assert_eq!(Location::File(base.join("foo.json")), location);
+                assert_eq!("unknown field `maxLenght` at defs.main", error.to_string());
+            }
+            result => panic!("expected parse error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_check_paths() {
+        let base = std::env::temp_dir().join(format!("atprose-paths-{}", std::process::id()));
+        std::fs::create_dir_all(base.join("com/example")).expect("create directory");
+        std::fs::write(
+            base.join("com/example/foo.json"),
+            r#"{"lexicon": 1, "id": "com.example.foo", "defs": {}}"#,
+        )
+        .expect("write lexicon");
+
+        let placed = Loader::new(&base).check_paths(true).load();
+
+        std::fs::write(
+            base.join("com/example/bar.json"),
+            r#"{"lexicon": 1, "id": "com.example.baz", "defs": {}}"#,
+        )
+        .expect("write lexicon");
+
+        let unchecked = Loader::new(&base).load();
+        let misplaced = Loader::new(&base).check_paths(true).load();
+        std::fs::remove_dir_all(&base).expect("remove directory");
+
+        assert_eq!(1, placed.expect("load lexicons").len());
+        assert_eq!(2, unchecked.expect("load lexicons").len());
+        match misplaced {
+            Err(LoadError::Misplaced { id, path, expected }) => {
+                assert_eq!(id, "com.example.baz");
+                assert_eq!(base.join("com/example/bar.json"), path);
+                assert_eq!(base.join("com/example/baz.json"), expected);
+            }
+            result => panic!("expected misplaced document, got {result:?}"),
+        }
+    }
 }
@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
 
 use super::meta::Metadata;
+use crate::validate::LengthError;
 
 /// A [`null`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#null
-#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Default, Clone, Debug)]
 pub struct Null {
     #[serde(flatten)]
     pub metadata: Metadata,
@@ -22,7 +23,7 @@ impl std::ops::Deref for Null {
 /// A [`boolean`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#boolean
-#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Default, Clone, Debug)]
 pub struct Boolean {
     #[serde(flatten)]
     pub metadata: Metadata,
@@ -45,7 +46,7 @@ impl std::ops::Deref for Boolean {
 /// An [`integer`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#integer
-#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Default, Clone, Debug)]
 pub struct Integer {
     #[serde(flatten)]
     pub metadata: Metadata,
@@ -66,6 +67,67 @@ pub struct Integer {
     pub values: Option<Vec<i64>>,
 }
 
+impl Integer {
+    /// Check that this integer type is consistent with itself: its `enum` has
+    /// no duplicate values, its `const` value (if any) is within `minimum` and
+    /// `maximum`, and `minimum` is not greater than `maximum`.
+    ///
+    /// Returns a message for every problem found.
+    pub fn validate_self(&self) -> Result<(), Vec<std::string::String>> {
+        let mut errors = Vec::new();
+
+        if let Some(values) = &self.values {
+            let mut seen = crate::Set::new();
+            let mut duplicates = crate::Set::new();
+
+            for value in values {
+                if !seen.insert(value) && duplicates.insert(value) {
+                    errors.push(format!("duplicate enum value {value}"));
+                }
+            }
+        }
+
+        if let Some(value) = self.value {
+            match (self.minimum, self.maximum) {
+                (Some(minimum), _) if value < minimum => {
+                    errors.push(format!(
+                        "const value {value} is less than the minimum {minimum}"
+                    ));
+                }
+                (_, Some(maximum)) if value > maximum => {
+                    errors.push(format!(
+                        "const value {value} is greater than the maximum {maximum}"
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        if let (Some(minimum), Some(maximum)) = (self.minimum, self.maximum) {
+            if minimum > maximum {
+                errors.push(format!(
+                    "minimum {minimum} is greater than maximum {maximum}"
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Sort the `enum` values and remove any duplicates, for deterministic
+    /// output.
+    pub fn canonicalize(&mut self) {
+        if let Some(values) = &mut self.values {
+            values.sort_unstable();
+            values.dedup();
+        }
+    }
+}
+
 impl std::ops::Deref for Integer {
     type Target = Metadata;
 
@@ -76,8 +138,15 @@ impl std::ops::Deref for Integer {
 
 /// A [`string`][spec] type.
 ///
+/// String lengths are measured two ways, and neither is a count of `char`s:
+/// `min_length` and `max_length` count UTF-8 bytes (as [`str::len`] does),
+/// while `min_graphemes` and `max_graphemes` count grapheme clusters. Use
+/// [`check_length`][Self::check_length] and `validate_graphemes` (with the
+/// `unicode` feature), or a [`validator`][Self::validator], rather than
+/// measuring values directly.
+///
 /// [spec]: https://atproto.com/specs/lexicon#string
-#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Default, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct String {
     #[serde(flatten)]
@@ -92,15 +161,19 @@ pub struct String {
     #[serde(rename = "const", skip_serializing_if = "Option::is_none")]
     pub value: Option<std::string::String>,
 
+    /// The minimum length of the string, in UTF-8 bytes.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub min_length: Option<usize>,
 
+    /// The maximum length of the string, in UTF-8 bytes.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_length: Option<usize>,
 
+    /// The minimum length of the string, in grapheme clusters.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub min_graphemes: Option<usize>,
 
+    /// The maximum length of the string, in grapheme clusters.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_graphemes: Option<usize>,
 
@@ -111,6 +184,39 @@ pub struct String {
     pub values: Option<Vec<std::string::String>>,
 }
 
+impl String {
+    /// Check that `value` is within this string's `min_length` and
+    /// `max_length`, measuring it in UTF-8 bytes.
+    ///
+    /// ```
+    /// use atprose_lexicon::{schema::String, LengthError};
+    ///
+    /// let schema = String { max_length: Some(5), ..Default::default() };
+    /// assert_eq!(Ok(()), schema.check_length("café"));
+    /// assert_eq!(
+    ///     Err(LengthError::TooLong { max_length: 5, length: 6 }),
+    ///     schema.check_length("cafés")
+    /// );
+    /// ```
+    pub fn check_length(&self, value: &str) -> Result<(), LengthError> {
+        let length = value.len();
+
+        if let Some(min_length) = self.min_length {
+            if length < min_length {
+                return Err(LengthError::TooShort { min_length, length });
+            }
+        }
+
+        if let Some(max_length) = self.max_length {
+            if length > max_length {
+                return Err(LengthError::TooLong { max_length, length });
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl std::ops::Deref for String {
     type Target = Metadata;
 
@@ -122,7 +228,7 @@ impl std::ops::Deref for String {
 /// A [`String`] [format][spec].
 ///
 /// [spec]: https://atproto.com/specs/lexicon#string-formats
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub enum StringFormat {
     /// Either a [DID][] or a [handle][].
@@ -136,6 +242,11 @@ pub enum StringFormat {
     /// [scheme]: https://atproto.com/specs/at-uri-scheme
     AtUri,
 
+    /// A [CID][] in string form.
+    ///
+    /// [CID]: https://atproto.com/specs/data-model#link-and-cid-formats
+    Cid,
+
     /// A [date-time][] string.
     ///
     /// [date-time]: https://atproto.com/specs/lexicon#datetime
@@ -156,6 +267,16 @@ pub enum StringFormat {
     /// [NSID]: https://atproto.com/specs/nsid
     Nsid,
 
+    /// A [record key][].
+    ///
+    /// [record key]: https://atproto.com/specs/record-key
+    RecordKey,
+
+    /// A [TID][] (timestamp identifier).
+    ///
+    /// [TID]: https://atproto.com/specs/tid
+    Tid,
+
     /// An IETF [language tag].
     ///
     /// [language tag]: https://atproto.com/specs/lexicon#language
@@ -165,10 +286,28 @@ pub enum StringFormat {
     Uri,
 }
 
+impl std::fmt::Display for StringFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            StringFormat::AtIdentifier => "at-identifier",
+            StringFormat::AtUri => "at-uri",
+            StringFormat::Cid => "cid",
+            StringFormat::Datetime => "datetime",
+            StringFormat::Did => "did",
+            StringFormat::Handle => "handle",
+            StringFormat::Nsid => "nsid",
+            StringFormat::RecordKey => "record-key",
+            StringFormat::Tid => "tid",
+            StringFormat::Language => "language",
+            StringFormat::Uri => "uri",
+        })
+    }
+}
+
 /// A [`blob`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#blob
-#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Default, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Blob {
     #[serde(flatten)]
@@ -192,7 +331,7 @@ impl std::ops::Deref for Blob {
 /// A [`bytes`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#bytes
-#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Default, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Bytes {
     #[serde(flatten)]
@@ -216,7 +355,7 @@ impl std::ops::Deref for Bytes {
 /// A [`cid-link`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#cid-link
-#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Default, Clone, Debug)]
 pub struct Link {
     #[serde(flatten)]
     pub metadata: Metadata,
@@ -229,3 +368,47 @@ impl std::ops::Deref for Link {
         &self.metadata
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Integer;
+
+    #[test]
+    fn test_integer_validate_self() {
+        let integer = Integer {
+            value: Some(5),
+            minimum: Some(1),
+            maximum: Some(10),
+            values: Some(vec![5, 1, 3]),
+            ..Default::default()
+        };
+        assert_eq!(Ok(()), integer.validate_self());
+
+        let integer = Integer {
+            value: Some(12),
+            minimum: Some(10),
+            maximum: Some(1),
+            values: Some(vec![3, 1, 3, 2, 1, 3]),
+            ..Default::default()
+        };
+        assert_eq!(
+            Err(vec![
+                "duplicate enum value 3".to_owned(),
+                "duplicate enum value 1".to_owned(),
+                "const value 12 is greater than the maximum 1".to_owned(),
+                "minimum 10 is greater than maximum 1".to_owned(),
+            ]),
+            integer.validate_self()
+        );
+    }
+
+    #[test]
+    fn test_integer_canonicalize() {
+        let mut integer = Integer {
+            values: Some(vec![3, 1, 2, 3]),
+            ..Default::default()
+        };
+        integer.canonicalize();
+        assert_eq!(Some(vec![1, 2, 3]), integer.values);
+    }
+}
@@ -4,14 +4,14 @@ use serde::{Deserialize, Serialize};
 
 use super::{
     concrete::{Blob, Boolean, Bytes, Integer, Link, String},
-    meta::{Metadata, Ref, Union, Unknown},
+    meta::{Described, Metadata, Ref, Union, Unknown},
 };
 use crate::Map;
 
 /// A [`record`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#record
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Record {
     #[serde(flatten)]
@@ -44,7 +44,7 @@ impl std::ops::Deref for Record {
 /// The [key format][rkey] of a [`Record`].
 ///
 /// [rkey]: https://atproto.com/specs/record-key
-#[derive(PartialEq, Eq, Default, Clone, Debug)]
+#[derive(Hash, PartialEq, Eq, Default, Clone, Debug)]
 pub enum RecordKey {
     /// A [timestamp identifier][tid].
     ///
@@ -110,16 +110,24 @@ impl Serialize for RecordKey {
     }
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum RecordDefinition {
     Object(Object),
 }
 
+impl Described for RecordDefinition {
+    fn description(&self) -> Option<&str> {
+        match self {
+            RecordDefinition::Object(value) => value.description(),
+        }
+    }
+}
+
 /// An [`array`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#array
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Array {
     #[serde(flatten)]
@@ -143,13 +151,14 @@ impl std::ops::Deref for Array {
 }
 
 /// The type of an [array][Array]'s items.
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum ArrayItem {
     Blob(Blob),
     Boolean(Boolean),
     Bytes(Bytes),
     Integer(Integer),
+    #[serde(rename = "cid-link")]
     Link(Link),
     String(String),
     Unknown(Unknown),
@@ -158,6 +167,39 @@ pub enum ArrayItem {
     Union(Union),
 }
 
+impl Described for ArrayItem {
+    fn description(&self) -> Option<&str> {
+        match self {
+            ArrayItem::Blob(value) => value.description(),
+            ArrayItem::Boolean(value) => value.description(),
+            ArrayItem::Bytes(value) => value.description(),
+            ArrayItem::Integer(value) => value.description(),
+            ArrayItem::Link(value) => value.description(),
+            ArrayItem::String(value) => value.description(),
+            ArrayItem::Unknown(value) => value.description(),
+            ArrayItem::Ref(value) => value.description(),
+            ArrayItem::Union(value) => value.description(),
+        }
+    }
+}
+
+impl ArrayItem {
+    /// The name of this item's type, as used in its `type` field.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ArrayItem::Blob(_) => "blob",
+            ArrayItem::Boolean(_) => "boolean",
+            ArrayItem::Bytes(_) => "bytes",
+            ArrayItem::Integer(_) => "integer",
+            ArrayItem::Link(_) => "cid-link",
+            ArrayItem::String(_) => "string",
+            ArrayItem::Unknown(_) => "unknown",
+            ArrayItem::Ref(_) => "ref",
+            ArrayItem::Union(_) => "union",
+        }
+    }
+}
+
 /// An [`object`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#object
@@ -176,6 +218,48 @@ pub struct Object {
     pub nullable: Vec<std::string::String>,
 }
 
+impl Object {
+    /// Start building an [`Object`], checking at [`build`][ObjectBuilder::build]
+    /// that its `required` and `nullable` lists name real properties.
+    pub fn builder() -> ObjectBuilder {
+        ObjectBuilder::default()
+    }
+
+    /// Check that every name in `required` and `nullable` is one of this
+    /// object's `properties`, as the spec requires.
+    ///
+    /// Returns a message for every problem found.
+    pub fn validate_self(&self) -> Result<(), Vec<std::string::String>> {
+        let errors: Vec<_> = self.undefined().map(|err| err.to_string()).collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn undefined(&self) -> impl Iterator<Item = InvalidObject> + '_ {
+        let undefined = |name: &&std::string::String| !self.properties.contains_key(*name);
+
+        let required = self.required.iter().filter(undefined);
+        let nullable = self.nullable.iter().filter(undefined);
+
+        required
+            .map(|name| InvalidObject::Required(name.clone()))
+            .chain(nullable.map(|name| InvalidObject::Nullable(name.clone())))
+    }
+}
+
+impl std::hash::Hash for Object {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.metadata.hash(state);
+        super::hash_map(&self.properties, state);
+        self.required.hash(state);
+        self.nullable.hash(state);
+    }
+}
+
 impl std::ops::Deref for Object {
     type Target = Metadata;
 
@@ -184,14 +268,74 @@ impl std::ops::Deref for Object {
     }
 }
 
+/// A builder for an [`Object`]; see [`Object::builder`].
+#[derive(Clone, Default, Debug)]
+pub struct ObjectBuilder {
+    object: Object,
+}
+
+impl ObjectBuilder {
+    pub fn description(mut self, description: impl Into<std::string::String>) -> Self {
+        self.object.metadata.description = Some(description.into());
+        self
+    }
+
+    /// Add a property, replacing any previous property with the same name.
+    pub fn property(mut self, name: impl Into<std::string::String>, property: Property) -> Self {
+        self.object.properties.insert(name.into(), property);
+        self
+    }
+
+    /// Mark the property `name` as required.
+    pub fn required(mut self, name: impl Into<std::string::String>) -> Self {
+        let name = name.into();
+        if !self.object.required.contains(&name) {
+            self.object.required.push(name);
+        }
+        self
+    }
+
+    /// Mark the property `name` as nullable.
+    pub fn nullable(mut self, name: impl Into<std::string::String>) -> Self {
+        let name = name.into();
+        if !self.object.nullable.contains(&name) {
+            self.object.nullable.push(name);
+        }
+        self
+    }
+
+    /// Finish building the [`Object`].
+    ///
+    /// Fails if a name given to [`required`][Self::required] or
+    /// [`nullable`][Self::nullable] was never added as a
+    /// [`property`][Self::property].
+    pub fn build(self) -> Result<Object, InvalidObject> {
+        if let Some(err) = self.object.undefined().next() {
+            return Err(err);
+        }
+
+        Ok(self.object)
+    }
+}
+
+/// A problem with the property lists of an [`Object`].
+#[derive(thiserror::Error, PartialEq, Eq, Clone, Debug)]
+pub enum InvalidObject {
+    #[error("required property {0:?} is not defined")]
+    Required(std::string::String),
+    #[error("nullable property {0:?} is not defined")]
+    Nullable(std::string::String),
+}
+
 /// A property of an [object][Object].
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum Property {
     Blob(Blob),
     Boolean(Boolean),
     Bytes(Bytes),
     Integer(Integer),
+    #[serde(rename = "cid-link")]
     Link(Link),
     String(String),
     Unknown(Unknown),
@@ -201,3 +345,160 @@ pub enum Property {
     Ref(Ref),
     Union(Union),
 }
+
+impl Described for Property {
+    fn description(&self) -> Option<&str> {
+        match self {
+            Property::Blob(value) => value.description(),
+            Property::Boolean(value) => value.description(),
+            Property::Bytes(value) => value.description(),
+            Property::Integer(value) => value.description(),
+            Property::Link(value) => value.description(),
+            Property::String(value) => value.description(),
+            Property::Unknown(value) => value.description(),
+            Property::Array(value) => value.description(),
+            Property::Ref(value) => value.description(),
+            Property::Union(value) => value.description(),
+        }
+    }
+}
+
+impl Property {
+    /// The name of this property's type, as used in its `type` field.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Property::Blob(_) => "blob",
+            Property::Boolean(_) => "boolean",
+            Property::Bytes(_) => "bytes",
+            Property::Integer(_) => "integer",
+            Property::Link(_) => "cid-link",
+            Property::String(_) => "string",
+            Property::Unknown(_) => "unknown",
+            Property::Array(_) => "array",
+            Property::Ref(_) => "ref",
+            Property::Union(_) => "union",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::hash::{BuildHasher, RandomState};
+
+    use super::{InvalidObject, Object, Property, RecordKey};
+    use crate::schema::{Boolean, Integer};
+
+    #[test]
+    fn test_kind() {
+        let properties = [
+            r#"{"type": "boolean"}"#,
+            r#"{"type": "cid-link"}"#,
+            r#"{"type": "array", "items": {"type": "integer"}}"#,
+            r##"{"type": "ref", "ref": "#main"}"##,
+        ];
+
+        for json in properties {
+            let property: Property = serde_json::from_str(json).expect(json);
+            let value: serde_json::Value = serde_json::to_value(&property).unwrap();
+            assert_eq!(value["type"], property.kind(), "{json}");
+
+            if let Property::Array(array) = property {
+                let value = serde_json::to_value(&array.items).unwrap();
+                assert_eq!(value["type"], array.items.kind());
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_key() {
+        let cases = [
+            (RecordKey::Tid, "tid"),
+            (RecordKey::Any, "any"),
+            (RecordKey::Literal("self".to_owned()), "literal:self"),
+            (RecordKey::Literal("any".to_owned()), "literal:any"),
+            (RecordKey::Literal("tid".to_owned()), "literal:tid"),
+            (RecordKey::Literal("a:b".to_owned()), "literal:a:b"),
+            (
+                RecordKey::Literal("literal:x".to_owned()),
+                "literal:literal:x",
+            ),
+        ];
+
+        for (key, expected) in cases {
+            assert_eq!(expected, key.to_string());
+            assert_eq!(Ok(key.clone()), expected.parse::<RecordKey>(), "{expected}");
+
+            let json = serde_json::to_string(&key).unwrap();
+            assert_eq!(key, serde_json::from_str(&json).unwrap(), "{json}");
+        }
+
+        assert_eq!(Err(()), "self".parse::<RecordKey>());
+        assert_eq!(Err(()), "Literal:self".parse::<RecordKey>());
+    }
+
+    #[test]
+    fn test_object_hash() {
+        let a = Object {
+            properties: [
+                ("a".into(), Property::Boolean(Boolean::default())),
+                ("b".into(), Property::Integer(Integer::default())),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let b = Object {
+            properties: a.properties.clone().into_iter().rev().collect(),
+            ..Default::default()
+        };
+        assert_eq!(a, b);
+
+        let state = RandomState::new();
+        assert_eq!(state.hash_one(&a), state.hash_one(&b));
+    }
+
+    #[test]
+    fn test_object_builder() {
+        let object = Object::builder()
+            .description("a test object")
+            .property("a", Property::Boolean(Boolean::default()))
+            .property("b", Property::Integer(Integer::default()))
+            .required("a")
+            .nullable("b")
+            .build()
+            .expect("build object");
+        assert_eq!(Some("a test object"), object.description.as_deref());
+        assert_eq!(2, object.properties.len());
+        assert_eq!(vec!["a".to_owned()], object.required);
+        assert_eq!(vec!["b".to_owned()], object.nullable);
+
+        let err = Object::builder()
+            .property("a", Property::Boolean(Boolean::default()))
+            .required("c")
+            .build();
+        assert_eq!(Err(InvalidObject::Required("c".to_owned())), err);
+
+        let err = Object::builder().nullable("a").build();
+        assert_eq!(Err(InvalidObject::Nullable("a".to_owned())), err);
+    }
+
+    #[test]
+    fn test_validate_self() {
+        let mut object = Object::builder()
+            .property("a", Property::Boolean(Boolean::default()))
+            .required("a")
+            .build()
+            .expect("build object");
+        assert_eq!(Ok(()), object.validate_self());
+
+        object.required.push("b".to_owned());
+        object.nullable.push("c".to_owned());
+        assert_eq!(
+            Err(vec![
+                r#"required property "b" is not defined"#.to_owned(),
+                r#"nullable property "c" is not defined"#.to_owned(),
+            ]),
+            object.validate_self()
+        );
+    }
+}
@@ -5,8 +5,9 @@ use atprose_types::{Nsid, TypeId};
 use super::{
     concrete::{Blob, Boolean, Bytes, Integer, Link, String},
     container::{Array, Object, Record},
-    meta::{Metadata, Ref, Union, Unknown},
+    meta::{Described, Metadata, Ref, RefTarget, Token, Union, Unknown},
     rpc::{Procedure, Query},
+    walk::Node,
 };
 use crate::Map;
 
@@ -30,6 +31,16 @@ pub struct Document {
     pub defs: Map<std::string::String, Definition>,
 }
 
+impl std::hash::Hash for Document {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.version.hash(state);
+        self.id.hash(state);
+        self.revision.hash(state);
+        self.metadata.hash(state);
+        super::hash_map(&self.defs, state);
+    }
+}
+
 impl Document {
     pub fn new(id: Nsid) -> Self {
         Self {
@@ -41,6 +52,145 @@ impl Document {
         }
     }
 
+    /// Parse a document from JSON, rejecting any field this crate doesn't
+    /// recognize.
+    ///
+    /// Ordinary parsing ignores unknown fields, so a misspelled constraint
+    /// (like `maximun`) is silently dropped. In strict mode it is an error
+    /// naming the field and where it was found. Definitions of
+    /// [unsupported][Definition::Unsupported] types are kept as raw JSON, and
+    /// their fields aren't checked.
+    ///
+    /// ```
+    /// use atprose_lexicon::schema::Document;
+    ///
+    /// let json = r#"{
+    ///     "lexicon": 1,
+    ///     "id": "com.example.defs",
+    ///     "defs": {"count": {"type": "integer", "maximun": 10}}
+    /// }"#;
+    ///
+    /// assert!(serde_json::from_str::<Document>(json).is_ok());
+    /// let error = Document::from_str_strict(json).unwrap_err();
+    /// assert_eq!("unknown field `maximun` at defs.count", error.to_string());
+    /// ```
+    pub fn from_str_strict(s: &str) -> serde_json::Result<Self> {
+        Self::from_value_strict(serde_json::from_str(s)?)
+    }
+
+    /// Convert a document from a JSON value, rejecting any field this crate
+    /// doesn't recognize, like [`from_str_strict`][Self::from_str_strict].
+    pub fn from_value_strict(value: serde_json::Value) -> serde_json::Result<Self> {
+        let document: Self = serde_json::from_value(value.clone())?;
+
+        // Every field this crate knows is serialized again, so a field which
+        // doesn't survive the round trip was ignored.
+        let output = serde_json::to_value(&document)?;
+        match unknown_field(&value, &output, &mut Vec::new()) {
+            Some(error) => Err(serde::de::Error::custom(error)),
+            None => Ok(document),
+        }
+    }
+
+    /// This document's `revision`, treating a document without one as
+    /// revision 0.
+    pub fn revision_or_zero(&self) -> u32 {
+        self.revision.unwrap_or_default()
+    }
+
+    /// Check the internal consistency of every definition in this document.
+    ///
+    /// Currently this [validates][Object::validate_self] every object's
+    /// `required` and `nullable` lists, including record, input and output
+    /// objects, and the `required` list of query parameters. Each message is
+    /// prefixed with the location of the problem (like `nsid#def.input`).
+    pub fn validate_structure(&self) -> Result<(), Vec<std::string::String>> {
+        let mut errors = Vec::new();
+        let mut check = |path: &str, result: Result<(), Vec<std::string::String>>| {
+            if let Err(messages) = result {
+                errors.extend(
+                    messages
+                        .into_iter()
+                        .map(|message| format!("{path}: {message}")),
+                );
+            }
+        };
+
+        self.walk(|path, node| match node {
+            Node::Object(object) => check(path, object.validate_self()),
+            Node::Parameters(parameters) => check(path, parameters.validate_self()),
+            _ => {}
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The path of the [XRPC][xrpc] endpoint for this document's `main`
+    /// definition, if it is a query or procedure.
+    ///
+    /// [xrpc]: https://atproto.com/specs/xrpc
+    pub fn xrpc_path(&self) -> Option<std::string::String> {
+        self.defs
+            .get("main")
+            .and_then(Definition::http_method)
+            .map(|_| super::xrpc_path(&self.id))
+    }
+
+    /// A SHA-256 hash of this document's content, for caching work (like
+    /// generated code) derived from it.
+    ///
+    /// The hash is taken over the document's compact JSON serialization, with
+    /// two changes: `revision` is left out, since bumping it doesn't change
+    /// what the document defines, and runs of whitespace in every
+    /// `description` are collapsed to a single space. Descriptions otherwise
+    /// count, since they end up in generated documentation. Definitions and
+    /// properties are hashed in document order, so reordering them changes
+    /// the fingerprint.
+    #[cfg(feature = "hash")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "hash")))]
+    pub fn fingerprint(&self) -> [u8; 32] {
+        use sha2::{Digest, Sha256};
+
+        let mut value = serde_json::to_value(self).expect("document serializes to JSON");
+        if let Some(document) = value.as_object_mut() {
+            document.shift_remove("revision");
+        }
+        normalize_descriptions(&mut value);
+
+        Sha256::digest(value.to_string()).into()
+    }
+
+    /// Whether this document is the same as `other`, ignoring every
+    /// [description][Described::description] within them.
+    ///
+    /// Description changes are documentation-only, so documents which are
+    /// semantically equal accept and produce the same data.
+    pub fn semantically_eq(&self, other: &Document) -> bool {
+        without_descriptions(self) == without_descriptions(other)
+    }
+
+    /// Every [ref][RefTarget] in this document, in the order they appear.
+    ///
+    /// This includes the targets of `ref` and `union` types at any depth:
+    /// top-level definitions, object properties, array items, and the input
+    /// and output bodies of queries and procedures. Local refs (like
+    /// `#postView`) are yielded as written; use [`RefTarget::resolve`] to
+    /// qualify them with this document's NSID.
+    pub fn refs(&self) -> impl Iterator<Item = &RefTarget> + '_ {
+        let mut refs = Vec::new();
+        self.walk(|_, node| match node {
+            Node::Ref(value) => refs.push(&value.target),
+            Node::Union(union) => refs.extend(&union.options),
+            _ => {}
+        });
+
+        refs.into_iter()
+    }
+
     pub fn types(&self) -> impl Iterator<Item = (TypeId, &Definition)> + '_ {
         self.defs
             .iter()
@@ -62,6 +212,93 @@ impl Document {
     }
 }
 
+/// Describe the first field of `input` which is missing from `output`.
+///
+/// A field given as `null` or `[]` may have been dropped because it was
+/// empty, so it isn't reported.
+fn unknown_field(
+    input: &serde_json::Value,
+    output: &serde_json::Value,
+    path: &mut Vec<std::string::String>,
+) -> Option<std::string::String> {
+    use serde_json::Value;
+
+    match (input, output) {
+        (Value::Object(input), Value::Object(output)) => {
+            for (key, value) in input {
+                let found = match output.get(key) {
+                    Some(output) => {
+                        path.push(key.clone());
+                        let found = unknown_field(value, output, path);
+                        path.pop();
+                        found
+                    }
+                    None if value.is_null() || value.as_array().is_some_and(Vec::is_empty) => None,
+                    None if path.is_empty() => Some(format!("unknown field `{key}`")),
+                    None => Some(format!("unknown field `{key}` at {}", path.join("."))),
+                };
+
+                if found.is_some() {
+                    return found;
+                }
+            }
+            None
+        }
+        (Value::Array(input), Value::Array(output)) => input
+            .iter()
+            .zip(output)
+            .enumerate()
+            .find_map(|(i, (input, output))| {
+                path.push(i.to_string());
+                let found = unknown_field(input, output, path);
+                path.pop();
+                found
+            }),
+        _ => None,
+    }
+}
+
+/// Serialize `value` to JSON, leaving out every `description`.
+fn without_descriptions(value: &impl Serialize) -> serde_json::Value {
+    fn strip(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(object) => {
+                if object.get("description").is_some_and(|d| d.is_string()) {
+                    object.shift_remove("description");
+                }
+                object.values_mut().for_each(strip);
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+
+    let mut value = serde_json::to_value(value).expect("schema serializes to JSON");
+    strip(&mut value);
+    value
+}
+
+/// Collapse the whitespace in every `description` string within `value`.
+#[cfg(feature = "hash")]
+fn normalize_descriptions(value: &mut serde_json::Value) {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match value {
+                    Value::String(description) if key == "description" => {
+                        *description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+                    }
+                    value => normalize_descriptions(value),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(normalize_descriptions),
+        _ => {}
+    }
+}
+
 impl std::ops::Deref for Document {
     type Target = Metadata;
 
@@ -71,7 +308,7 @@ impl std::ops::Deref for Document {
 }
 
 /// Lexicon language version used in a [`Document`].
-#[derive(Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
 pub struct Version(pub u32);
 
 impl Default for Version {
@@ -81,8 +318,11 @@ impl Default for Version {
 }
 
 /// A top-level definition in a Lexicon [`Document`].
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
-#[serde(tag = "type", rename_all = "kebab-case")]
+///
+/// A definition with a `type` this crate doesn't model is deserialized as
+/// [`Unsupported`][Definition::Unsupported], and serialized back unchanged.
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
+#[serde(remote = "Self", tag = "type", rename_all = "kebab-case")]
 pub enum Definition {
     Record(Record),
     Query(Query),
@@ -95,19 +335,152 @@ pub enum Definition {
     Boolean(Boolean),
     Bytes(Bytes),
     Integer(Integer),
+    #[serde(rename = "cid-link")]
     Link(Link),
     String(String),
     Unknown(Unknown),
 
     Ref(Ref),
     Union(Union),
+
+    Token(Token),
+
+    /// A definition of a type not known to this crate, kept as raw JSON.
+    #[serde(skip)]
+    Unsupported {
+        type_name: std::string::String,
+        raw: serde_json::Value,
+    },
+}
+
+impl Definition {
+    const KINDS: &'static [&'static str] = &[
+        "record",
+        "query",
+        "procedure",
+        "array",
+        "object",
+        "blob",
+        "boolean",
+        "bytes",
+        "integer",
+        "cid-link",
+        "string",
+        "unknown",
+        "ref",
+        "union",
+        "token",
+    ];
+}
+
+impl<'de> Deserialize<'de> for Definition {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let raw = serde_json::Value::deserialize(deserializer)?;
+        let type_name = match raw.get("type") {
+            Some(serde_json::Value::String(type_name)) => type_name,
+            Some(_) => return Err(D::Error::custom("definition type must be a string")),
+            None => return Err(D::Error::missing_field("type")),
+        };
+
+        if Self::KINDS.contains(&type_name.as_str()) {
+            Definition::deserialize(raw).map_err(D::Error::custom)
+        } else {
+            Ok(Definition::Unsupported {
+                type_name: type_name.clone(),
+                raw,
+            })
+        }
+    }
+}
+
+impl Serialize for Definition {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Definition::Unsupported { raw, .. } => raw.serialize(serializer),
+            definition => Definition::serialize(definition, serializer),
+        }
+    }
+}
+
+impl Described for Definition {
+    fn description(&self) -> Option<&str> {
+        match self {
+            Definition::Record(value) => value.description(),
+            Definition::Query(value) => value.description(),
+            Definition::Procedure(value) => value.description(),
+            Definition::Array(value) => value.description(),
+            Definition::Object(value) => value.description(),
+            Definition::Blob(value) => value.description(),
+            Definition::Boolean(value) => value.description(),
+            Definition::Bytes(value) => value.description(),
+            Definition::Integer(value) => value.description(),
+            Definition::Link(value) => value.description(),
+            Definition::String(value) => value.description(),
+            Definition::Unknown(value) => value.description(),
+            Definition::Ref(value) => value.description(),
+            Definition::Union(value) => value.description(),
+            Definition::Token(value) => value.description(),
+            Definition::Unsupported { raw, .. } => {
+                raw.get("description").and_then(serde_json::Value::as_str)
+            }
+        }
+    }
+}
+
+impl Definition {
+    /// Whether this definition is the same as `other`, ignoring every
+    /// [description][Described::description] within them.
+    pub fn semantically_eq(&self, other: &Definition) -> bool {
+        without_descriptions(self) == without_descriptions(other)
+    }
+
+    /// The name of this definition's type, as used in its `type` field.
+    pub fn kind(&self) -> &str {
+        match self {
+            Definition::Record(_) => "record",
+            Definition::Query(_) => "query",
+            Definition::Procedure(_) => "procedure",
+            Definition::Array(_) => "array",
+            Definition::Object(_) => "object",
+            Definition::Blob(_) => "blob",
+            Definition::Boolean(_) => "boolean",
+            Definition::Bytes(_) => "bytes",
+            Definition::Integer(_) => "integer",
+            Definition::Link(_) => "cid-link",
+            Definition::String(_) => "string",
+            Definition::Unknown(_) => "unknown",
+            Definition::Ref(_) => "ref",
+            Definition::Union(_) => "union",
+            Definition::Token(_) => "token",
+            Definition::Unsupported { type_name, .. } => type_name,
+        }
+    }
+
+    /// The HTTP method used to call this definition over XRPC, if it is a
+    /// query or procedure.
+    pub fn http_method(&self) -> Option<&'static str> {
+        match self {
+            Definition::Query(query) => Some(query.http_method()),
+            Definition::Procedure(procedure) => Some(procedure.http_method()),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use serde_json::from_str;
 
-    use super::Document;
+    use super::{Definition, Document, RefTarget};
+    use crate::schema::{Described, Property, RecordDefinition};
 
     static POST: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test/post.json"));
     static POST_DEBUG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test/post.out"));
@@ -119,4 +492,238 @@ mod test {
         let expected = format!("{:#?}\n", &document);
         assert_eq!(expected.as_str(), POST_DEBUG);
     }
+
+    #[test]
+    fn test_from_str_strict() {
+        let document = Document::from_str_strict(POST).expect("strict parse");
+        assert_eq!(from_str::<Document>(POST).unwrap(), document);
+
+        let cases = [
+            (
+                r#"{"lexicon": 1, "id": "a.b.c", "defs": {}, "revison": 2}"#,
+                "unknown field `revison`",
+            ),
+            (
+                r#"{"lexicon": 1, "id": "a.b.c", "defs": {"main": {"type": "record", "key": "tid",
+                    "record": {"type": "object", "properties": {
+                        "count": {"type": "integer", "maximun": 10}
+                    }}}}}"#,
+                "unknown field `maximun` at defs.main.record.properties.count",
+            ),
+            (
+                r#"{"lexicon": 1, "id": "a.b.c", "defs": {"main": {"type": "query",
+                    "errors": [{"name": "NotFound"}, {"name": "Gone", "descripton": "gone"}]}}}"#,
+                "unknown field `descripton` at defs.main.errors.1",
+            ),
+        ];
+        for (json, expected) in cases {
+            assert!(from_str::<Document>(json).is_ok(), "{json}");
+            let error = Document::from_str_strict(json).expect_err(json);
+            assert_eq!(expected, error.to_string());
+        }
+
+        let json = r#"{"lexicon": 1, "id": "a.b.c", "description": null, "defs": {
+            "main": {"type": "object", "required": [], "properties": {}},
+            "other": {"type": "future-type", "anything": true}
+        }}"#;
+        assert!(Document::from_str_strict(json).is_ok());
+    }
+
+    #[test]
+    fn test_validate_structure() {
+        let mut document: Document = from_str(POST).expect("failed to deserialize test/post.json");
+        assert_eq!(Ok(()), document.validate_structure());
+
+        let Some(Definition::Record(record)) = document.defs.get_mut("main") else {
+            panic!("expected a main record definition");
+        };
+        let RecordDefinition::Object(object) = &mut record.def;
+        object.required.push("missing".to_owned());
+
+        assert_eq!(
+            Err(vec![
+                r#"dev.atprose.test.post#main: required property "missing" is not defined"#
+                    .to_owned()
+            ]),
+            document.validate_structure()
+        );
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let json = r##"{
+            "lexicon": 1,
+            "id": "com.example.stream",
+            "defs": {
+                "main": {
+                    "type": "subscription",
+                    "description": "A stream of events.",
+                    "message": {"schema": {"type": "union", "refs": ["#event"]}}
+                },
+                "count": {"type": "integer"}
+            }
+        }"##;
+        let document: Document = from_str(json).expect("deserialize document");
+
+        let main = &document.defs["main"];
+        assert_eq!("subscription", main.kind());
+        assert_eq!(Some("A stream of events."), main.description());
+        assert!(matches!(
+            main,
+            Definition::Unsupported { type_name, .. } if type_name == "subscription"
+        ));
+        assert!(matches!(document.defs["count"], Definition::Integer(_)));
+
+        let expected: serde_json::Value = from_str(json).unwrap();
+        assert_eq!(expected, serde_json::to_value(&document).unwrap());
+
+        for json in [
+            r#"{"type": "integer", "minimum": "one"}"#,
+            r#"{"type": 1}"#,
+            r#"{"description": "untyped"}"#,
+        ] {
+            assert!(from_str::<Definition>(json).is_err(), "{json}");
+        }
+    }
+
+    #[cfg(feature = "hash")]
+    #[test]
+    fn test_fingerprint() {
+        let mut document: Document = from_str(POST).expect("failed to deserialize test/post.json");
+        document.metadata.description = Some("Lexicons for testing.".to_owned());
+
+        let fingerprint = document.fingerprint();
+        assert_eq!(fingerprint, document.clone().fingerprint());
+
+        let mut revised = document.clone();
+        revised.revision = Some(revised.revision.unwrap_or_default() + 1);
+        assert_eq!(fingerprint, revised.fingerprint());
+
+        let mut reformatted = document.clone();
+        reformatted.metadata.description = Some("\n  Lexicons  for\n  testing.\n".to_owned());
+        assert_eq!(fingerprint, reformatted.fingerprint());
+
+        let mut described = document.clone();
+        described.metadata.description = Some("Something else.".to_owned());
+        assert_ne!(fingerprint, described.fingerprint());
+
+        let mut extended = document.clone();
+        extended.defs.insert(
+            "count".to_owned(),
+            from_str(r#"{"type": "integer"}"#).unwrap(),
+        );
+        assert_ne!(fingerprint, extended.fingerprint());
+    }
+
+    #[test]
+    fn test_xrpc() {
+        let document: Document = from_str(
+            r#"{"lexicon": 1, "id": "com.example.getThing", "defs": {
+                "main": {"type": "query", "output": {"encoding": "application/json"}}
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Some("/xrpc/com.example.getThing"),
+            document.xrpc_path().as_deref()
+        );
+        assert_eq!(Some("GET"), document.defs["main"].http_method());
+
+        let document: Document = from_str(
+            r#"{"lexicon": 1, "id": "com.example.putThing", "defs": {
+                "main": {"type": "procedure"}
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(Some("POST"), document.defs["main"].http_method());
+
+        let post: Document = from_str(POST).unwrap();
+        assert_eq!(None, post.xrpc_path());
+        assert_eq!(None, post.defs["main"].http_method());
+    }
+
+    #[test]
+    fn test_refs() {
+        let document: Document = from_str(
+            r##"{"lexicon": 1, "id": "com.example.defs", "defs": {
+                "main": {
+                    "type": "procedure",
+                    "input": {"encoding": "application/json", "schema": {
+                        "type": "object",
+                        "properties": {
+                            "subject": {"type": "ref", "ref": "com.atproto.repo.strongRef"},
+                            "count": {"type": "integer"}
+                        }
+                    }},
+                    "output": {"encoding": "application/json", "schema": {
+                        "type": "ref", "ref": "#view"
+                    }}
+                },
+                "view": {
+                    "type": "object",
+                    "properties": {
+                        "embeds": {"type": "array", "items": {
+                            "type": "union", "refs": ["#image", "com.example.video"]
+                        }},
+                        "author": {"type": "ref", "ref": "#author"}
+                    }
+                },
+                "list": {"type": "array", "items": {"type": "ref", "ref": "#view"}},
+                "name": {"type": "string"}
+            }}"##,
+        )
+        .expect("deserialize document");
+
+        let refs: Vec<_> = document.refs().map(RefTarget::to_string).collect();
+        assert_eq!(
+            vec![
+                "com.atproto.repo.strongRef",
+                "#view",
+                "#image",
+                "com.example.video",
+                "#author",
+                "#view",
+            ],
+            refs
+        );
+
+        let post: Document = from_str(POST).expect("failed to deserialize test/post.json");
+        let refs: Vec<_> = post.refs().map(RefTarget::to_string).collect();
+        assert_eq!(vec!["#body"], refs);
+    }
+
+    #[test]
+    fn test_semantically_eq() {
+        let document: Document = from_str(POST).expect("failed to deserialize test/post.json");
+        assert!(document.semantically_eq(&document.clone()));
+
+        let mut described = document.clone();
+        described.metadata.description = Some("Test lexicons.".to_owned());
+        let Some(Definition::Record(record)) = described.defs.get_mut("main") else {
+            panic!("expected a main record definition");
+        };
+        record.metadata.description = None;
+        let RecordDefinition::Object(object) = &mut record.def;
+        for property in object.properties.values_mut() {
+            let Property::String(string) = property else {
+                continue;
+            };
+            string.metadata.description = Some("Changed.".to_owned());
+        }
+
+        assert_ne!(document, described);
+        assert!(document.semantically_eq(&described));
+        assert!(document.defs["main"].semantically_eq(&described.defs["main"]));
+
+        let mut changed = described.clone();
+        changed.defs.shift_remove("main");
+        assert!(!document.semantically_eq(&changed));
+
+        let integer: Definition = from_str(r#"{"type": "integer", "minimum": 1}"#).unwrap();
+        let described: Definition =
+            from_str(r#"{"type": "integer", "minimum": 1, "description": "A count."}"#).unwrap();
+        let other: Definition = from_str(r#"{"type": "integer", "minimum": 2}"#).unwrap();
+        assert!(integer.semantically_eq(&described));
+        assert!(!integer.semantically_eq(&other));
+    }
 }
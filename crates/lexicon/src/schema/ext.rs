@@ -0,0 +1,375 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::{Document, Nsid, Schema};
+
+/// Methods for working with a [`Schema`] as a whole.
+///
+/// [`Schema`] is a plain map of documents, so these are provided by an
+/// extension trait; import it to use them.
+pub trait SchemaExt {
+    /// Merge the documents from `other` into this schema.
+    ///
+    /// Documents with a new NSID are inserted, and documents identical to one
+    /// already present are ignored. A document whose NSID is already present
+    /// with different contents is left out and reported as a
+    /// [`MergeConflict`]; every non-conflicting document is still merged.
+    fn merge(&mut self, other: Schema) -> Result<(), Vec<MergeConflict>>;
+
+    /// Build a schema from documents, keyed by their [`id`][Document::id].
+    ///
+    /// Like [`merge`][Self::merge], a document identical to an earlier one
+    /// with the same NSID is ignored, and a different document with the same
+    /// NSID is reported as a [`MergeConflict`].
+    ///
+    /// To instead let later documents replace earlier ones, use
+    /// [`collect`][Iterator::collect] or [`extend`][Extend::extend].
+    fn from_documents(
+        documents: impl IntoIterator<Item = Document>,
+    ) -> Result<Self, Vec<MergeConflict>>
+    where
+        Self: Sized;
+
+    /// Merge the documents from `other` into this schema, replacing documents
+    /// with newer [revisions][Document::revision] of them.
+    ///
+    /// A document missing a revision counts as revision 0. An incoming
+    /// document with a higher revision than the one already present replaces
+    /// it, and one with the same revision is handled like
+    /// [`merge`][Self::merge]. An incoming document with a lower revision is
+    /// left out and reported as an [`UpgradeError::Regression`]. Every other
+    /// document is still merged.
+    fn upgrade(&mut self, other: Schema) -> Result<(), Vec<UpgradeError>>;
+
+    /// Iterate over the documents in order of their NSIDs, rather than the
+    /// order they were added.
+    fn sorted_documents(&self) -> impl Iterator<Item = (&Nsid, &Document)>;
+
+    /// Write a compact binary snapshot of this schema, which
+    /// [`from_bytes`][Self::from_bytes] reads back much faster than parsing
+    /// the original JSON documents.
+    ///
+    /// Snapshots are only meant to be read by the same version of this crate
+    /// which wrote them.
+    #[cfg(feature = "snapshot")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "snapshot")))]
+    fn to_bytes(&self) -> Result<Vec<u8>, crate::SnapshotError>;
+
+    /// Read a schema from a snapshot written by [`to_bytes`][Self::to_bytes].
+    ///
+    /// ```
+    /// use atprose_lexicon::schema::{Document, Schema, SchemaExt};
+    ///
+    /// let document: Document = serde_json::from_str(r#"{
+    ///     "lexicon": 1,
+    ///     "id": "com.example.defs",
+    ///     "defs": {"status": {"type": "string", "knownValues": ["active", "away"]}}
+    /// }"#).unwrap();
+    /// let schema: Schema = [document].into_iter().collect();
+    ///
+    /// let snapshot = schema.to_bytes().unwrap();
+    /// assert_eq!(schema, Schema::from_bytes(&snapshot).unwrap());
+    /// ```
+    #[cfg(feature = "snapshot")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "snapshot")))]
+    fn from_bytes(bytes: &[u8]) -> Result<Self, crate::SnapshotError>
+    where
+        Self: Sized;
+}
+
+impl SchemaExt for Schema {
+    fn from_documents(
+        documents: impl IntoIterator<Item = Document>,
+    ) -> Result<Self, Vec<MergeConflict>> {
+        let mut schema = Schema::new();
+        let mut conflicts = Vec::new();
+
+        for document in documents {
+            match schema.get(&document.id) {
+                None => {
+                    schema.insert(document.id.clone(), document);
+                }
+                Some(existing) if *existing == document => {}
+                Some(_) => conflicts.push(MergeConflict {
+                    id: document.id.clone(),
+                    document,
+                }),
+            }
+        }
+
+        if conflicts.is_empty() {
+            Ok(schema)
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    fn merge(&mut self, other: Schema) -> Result<(), Vec<MergeConflict>> {
+        let mut conflicts = Vec::new();
+
+        for (id, document) in other {
+            match self.get(&id) {
+                None => {
+                    self.insert(id, document);
+                }
+                Some(existing) if *existing == document => {}
+                Some(_) => conflicts.push(MergeConflict { id, document }),
+            }
+        }
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    fn upgrade(&mut self, other: Schema) -> Result<(), Vec<UpgradeError>> {
+        let mut errors = Vec::new();
+
+        for (id, document) in other {
+            let Some(existing) = self.get(&id) else {
+                self.insert(id, document);
+                continue;
+            };
+
+            let (revision, incoming) = (existing.revision_or_zero(), document.revision_or_zero());
+            match incoming.cmp(&revision) {
+                Ordering::Greater => {
+                    self.insert(id, document);
+                }
+                Ordering::Equal if *existing == document => {}
+                Ordering::Equal => {
+                    errors.push(UpgradeError::Conflict(MergeConflict { id, document }));
+                }
+                Ordering::Less => errors.push(UpgradeError::Regression {
+                    id,
+                    revision,
+                    document,
+                }),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn sorted_documents(&self) -> impl Iterator<Item = (&Nsid, &Document)> {
+        let mut documents: Vec<_> = self.iter().collect();
+        documents.sort_unstable_by_key(|&(id, _)| id);
+        documents.into_iter()
+    }
+
+    #[cfg(feature = "snapshot")]
+    fn to_bytes(&self) -> Result<Vec<u8>, crate::SnapshotError> {
+        crate::snapshot::encode(self)
+    }
+
+    #[cfg(feature = "snapshot")]
+    fn from_bytes(bytes: &[u8]) -> Result<Self, crate::SnapshotError> {
+        crate::snapshot::decode(bytes)
+    }
+}
+
+impl FromIterator<Document> for Schema {
+    fn from_iter<T: IntoIterator<Item = Document>>(iter: T) -> Self {
+        let mut schema = Schema::new();
+        schema.extend(iter);
+        schema
+    }
+}
+
+/// Insert documents keyed by their [`id`][Document::id], replacing any
+/// existing document with the same NSID.
+impl Extend<Document> for Schema {
+    fn extend<T: IntoIterator<Item = Document>>(&mut self, iter: T) {
+        for document in iter {
+            self.insert(document.id.clone(), document);
+        }
+    }
+}
+
+/// A document which could not be [merged][SchemaExt::merge] into a schema,
+/// because the schema already has a different document with the same NSID.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct MergeConflict {
+    pub id: Nsid,
+    /// The incoming document which was not merged.
+    pub document: Document,
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "conflicting documents for {}", self.id)
+    }
+}
+
+impl std::error::Error for MergeConflict {}
+
+/// A document which [`SchemaExt::upgrade`] did not merge.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum UpgradeError {
+    /// The incoming document has the same revision as the one already in the
+    /// schema, but different contents.
+    Conflict(MergeConflict),
+    /// The incoming document has a lower revision than the one already in
+    /// the schema.
+    Regression {
+        id: Nsid,
+        /// The revision of the document already in the schema.
+        revision: u32,
+        /// The incoming document which was not merged.
+        document: Document,
+    },
+}
+
+impl fmt::Display for UpgradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpgradeError::Conflict(conflict) => conflict.fmt(f),
+            UpgradeError::Regression {
+                id,
+                revision,
+                document,
+            } => write!(
+                f,
+                "revision {} of {id} is older than revision {revision}",
+                document.revision_or_zero()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UpgradeError {}
+
+#[cfg(test)]
+mod test {
+    use super::{MergeConflict, SchemaExt, UpgradeError};
+    use crate::schema::{Document, Nsid, Schema};
+
+    fn schema(documents: impl IntoIterator<Item = Document>) -> Schema {
+        documents.into_iter().collect()
+    }
+
+    #[test]
+    fn test_merge() {
+        let foo = Document::new(Nsid::new("com.example", "foo"));
+        let bar = Document::new(Nsid::new("com.example", "bar"));
+
+        let mut base = schema([foo.clone()]);
+        base.merge(schema([foo.clone(), bar.clone()]))
+            .expect("merge identical documents");
+        assert_eq!(base, schema([foo.clone(), bar.clone()]));
+
+        let mut changed = foo.clone();
+        changed.revision = Some(2);
+        let baz = Document::new(Nsid::new("com.example", "baz"));
+
+        let conflicts = base
+            .merge(schema([changed.clone(), baz.clone()]))
+            .expect_err("merge conflicting documents");
+        assert_eq!(
+            conflicts,
+            vec![MergeConflict {
+                id: foo.id.clone(),
+                document: changed,
+            }]
+        );
+        assert_eq!(base, schema([foo, bar, baz]));
+    }
+
+    #[test]
+    fn test_from_documents() {
+        let foo = Document::new(Nsid::new("com.example", "foo"));
+        let bar = Document::new(Nsid::new("com.example", "bar"));
+
+        let schema =
+            Schema::from_documents([foo.clone(), bar.clone(), foo.clone()]).expect("build schema");
+        let ids: Vec<_> = schema.keys().cloned().collect();
+        assert_eq!(vec![foo.id.clone(), bar.id.clone()], ids);
+
+        let mut changed = foo.clone();
+        changed.revision = Some(2);
+        assert_eq!(
+            Err(vec![MergeConflict {
+                id: foo.id.clone(),
+                document: changed.clone(),
+            }]),
+            Schema::from_documents([foo.clone(), bar, changed.clone()])
+        );
+
+        let mut schema = schema;
+        schema.extend([changed.clone()]);
+        assert_eq!(Some(&changed), schema.get(&foo.id));
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let revision = |id: &str, revision: Option<u32>| {
+            let mut document = Document::new(id.parse().unwrap());
+            document.revision = revision;
+            document
+        };
+
+        let foo = revision("com.example.foo", None);
+        let bar = revision("com.example.bar", Some(2));
+        let baz = revision("com.example.baz", Some(1));
+        let mut base = schema([foo.clone(), bar.clone(), baz.clone()]);
+
+        let newer_foo = revision("com.example.foo", Some(1));
+        let older_bar = revision("com.example.bar", Some(1));
+        let mut changed_baz = baz.clone();
+        changed_baz.metadata.description = Some("changed".to_owned());
+        let qux = revision("com.example.qux", None);
+
+        let errors = base
+            .upgrade(schema([
+                newer_foo.clone(),
+                older_bar.clone(),
+                changed_baz.clone(),
+                qux.clone(),
+            ]))
+            .expect_err("upgrade with regressions");
+        assert_eq!(
+            vec![
+                UpgradeError::Regression {
+                    id: bar.id.clone(),
+                    revision: 2,
+                    document: older_bar,
+                },
+                UpgradeError::Conflict(MergeConflict {
+                    id: baz.id.clone(),
+                    document: changed_baz,
+                }),
+            ],
+            errors
+        );
+        assert_eq!(schema([newer_foo, bar, baz, qux]), base);
+        assert_eq!(0, foo.revision_or_zero());
+    }
+
+    #[test]
+    fn test_sorted_documents() {
+        let ids = [
+            Nsid::new("com.example", "foo"),
+            Nsid::new("app.bsky.feed", "post"),
+            Nsid::new("com.example", "bar"),
+        ];
+        let schema = schema(ids.iter().cloned().map(Document::new));
+
+        let sorted: Vec<_> = schema
+            .sorted_documents()
+            .map(|(id, document)| {
+                assert_eq!(id, &document.id);
+                id.to_string()
+            })
+            .collect();
+        assert_eq!(
+            vec!["app.bsky.feed.post", "com.example.bar", "com.example.foo"],
+            sorted
+        );
+    }
+}
@@ -5,16 +5,34 @@ use serde::{Deserialize, Serialize};
 use super::{Nsid, TypeId};
 
 /// The metadata defined for every Lexicon type.
-#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Default, Clone, Debug)]
 pub struct Metadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 }
 
+/// Access to the [description][Metadata::description] of any schema type,
+/// including the enums of possible types.
+pub trait Described {
+    fn description(&self) -> Option<&str>;
+}
+
+impl Described for Metadata {
+    fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+}
+
+impl<T: std::ops::Deref<Target = Metadata>> Described for T {
+    fn description(&self) -> Option<&str> {
+        self.deref().description()
+    }
+}
+
 /// A [`ref`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#ref
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 pub struct Ref {
     #[serde(flatten)]
     pub metadata: Metadata,
@@ -34,7 +52,7 @@ impl std::ops::Deref for Ref {
 /// A [`union`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#union
-#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Default, Clone, Debug)]
 pub struct Union {
     #[serde(flatten)]
     pub metadata: Metadata,
@@ -46,6 +64,60 @@ pub struct Union {
     pub closed: Option<bool>,
 }
 
+impl Union {
+    /// Check that this union lists at least one ref, and no ref more than
+    /// once.
+    ///
+    /// Returns a message for every problem found.
+    pub fn validate_self(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.options.is_empty() {
+            errors.push("union has no refs".to_owned());
+        }
+
+        let mut seen = crate::Set::new();
+        let mut duplicates = crate::Set::new();
+
+        for option in &self.options {
+            if !seen.insert(option) && duplicates.insert(option) {
+                errors.push(format!("duplicate union ref {option}"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The refs of this union without duplicates, in the order they first
+    /// appear.
+    pub fn unique_options(&self) -> Vec<&RefTarget> {
+        let options: crate::Set<_> = self.options.iter().collect();
+        options.into_iter().collect()
+    }
+
+    /// Find which of this union's types `value` is, by its `$type` field.
+    ///
+    /// Refs in the union are resolved relative to the document `base`. A
+    /// `$type` that isn't one of the union's refs is only accepted if the
+    /// union is open. Returns `None` if `value` isn't an object, has no
+    /// `$type`, or its `$type` isn't allowed.
+    pub fn match_value(&self, value: &serde_json::Value, base: &Nsid) -> Option<TypeId> {
+        let type_id = value.get("$type")?.as_str()?;
+        let type_id = type_id.parse::<RefTarget>().ok()?.resolve(base);
+
+        let listed = self
+            .options
+            .iter()
+            .any(|option| option.resolve(base) == type_id);
+
+        (listed || self.closed != Some(true)).then_some(type_id)
+    }
+}
+
 impl std::ops::Deref for Union {
     type Target = Metadata;
 
@@ -76,7 +148,7 @@ impl RefTarget {
     }
 
     pub fn name(&self) -> Option<&str> {
-        self.name.as_ref().map(String::as_str)
+        self.name.as_deref()
     }
 
     pub fn resolve(&self, base: &Nsid) -> TypeId {
@@ -116,14 +188,30 @@ impl Display for RefTarget {
     }
 }
 
+/// Refs are strings, like `"app.bsky.feed.defs#postView"`. For compatibility
+/// with some older lexicons, a ref may also be written as an object with a
+/// single `ref` key, like `{"ref": "app.bsky.feed.defs#postView"}`; it is
+/// always serialized as a string.
 impl<'de> Deserialize<'de> for RefTarget {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let s = std::string::String::deserialize(deserializer)?;
+        #[derive(Deserialize)]
+        #[serde(untagged, deny_unknown_fields)]
+        enum Repr {
+            String(std::string::String),
+            Object {
+                #[serde(rename = "ref")]
+                target: std::string::String,
+            },
+        }
+
+        let s = match Repr::deserialize(deserializer)? {
+            Repr::String(s) | Repr::Object { target: s } => s,
+        };
         s.parse()
-            .map_err(|_| serde::de::Error::custom("unknown record key"))
+            .map_err(|_| serde::de::Error::custom(format!("invalid ref {s:?}")))
     }
 }
 
@@ -139,7 +227,7 @@ impl Serialize for RefTarget {
 /// A [`token`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#token
-#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Default, Clone, Debug)]
 pub struct Token {
     #[serde(flatten)]
     pub metadata: Metadata,
@@ -156,7 +244,7 @@ impl std::ops::Deref for Token {
 /// An [`unknown`][spec] type.
 ///
 /// [spec]: https://atproto.com/specs/lexicon#unknown
-#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Default, Clone, Debug)]
 pub struct Unknown {
     #[serde(flatten)]
     pub metadata: Metadata,
@@ -172,8 +260,9 @@ impl std::ops::Deref for Unknown {
 
 #[cfg(test)]
 mod test {
-    use super::RefTarget;
-    use crate::schema::Nsid;
+    use super::{Described, RefTarget, Union};
+    use crate::schema::{Definition, Nsid, Property};
+    use serde_json::json;
 
     #[test]
     fn test_ref_target_from_str() {
@@ -189,4 +278,129 @@ mod test {
             RefTarget::new(Some(Nsid::new("app.bsky.feed", "defs")), "generatorView")
         );
     }
+
+    #[test]
+    fn test_ref_target_deserialize() {
+        let expected = RefTarget::new(Some(Nsid::new("app.bsky.feed", "defs")), "postView");
+
+        let union: Union = serde_json::from_str(
+            r#"{"refs": ["app.bsky.feed.defs#postView", {"ref": "app.bsky.feed.defs#postView"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(vec![expected.clone(), expected.clone()], union.options);
+        assert_eq!(
+            json!({"refs": ["app.bsky.feed.defs#postView", "app.bsky.feed.defs#postView"]}),
+            serde_json::to_value(&union).unwrap()
+        );
+
+        for invalid in [
+            json!({"ref": "app.bsky.feed.defs#postView", "type": "ref"}),
+            json!({}),
+            json!(1),
+            json!("not a ref"),
+        ] {
+            assert!(
+                serde_json::from_value::<RefTarget>(invalid.clone()).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_union_validate_self() {
+        let union: Union = serde_json::from_str(
+            r##"{"refs": ["#a", "com.example.foo#b", "#a", "#c", "#a", "com.example.foo#b"]}"##,
+        )
+        .unwrap();
+
+        let unique: Vec<_> = union
+            .unique_options()
+            .into_iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(vec!["#a", "com.example.foo#b", "#c"], unique);
+        assert_eq!(
+            Err(vec![
+                "duplicate union ref #a".to_owned(),
+                "duplicate union ref com.example.foo#b".to_owned(),
+            ]),
+            union.validate_self()
+        );
+
+        let union = Union {
+            options: union.unique_options().into_iter().cloned().collect(),
+            ..Default::default()
+        };
+        assert_eq!(Ok(()), union.validate_self());
+
+        assert_eq!(
+            Err(vec!["union has no refs".to_owned()]),
+            Union::default().validate_self()
+        );
+    }
+
+    #[test]
+    fn test_union_match_value() {
+        let mut union: Union =
+            serde_json::from_str(r##"{"refs": ["#a", "com.example.foo", "com.example.foo#b"]}"##)
+                .unwrap();
+        let base = Nsid::new("com.example", "defs");
+
+        let matched = |union: &Union, value: serde_json::Value| {
+            union
+                .match_value(&value, &base)
+                .map(|type_id| type_id.to_string())
+        };
+
+        assert_eq!(
+            Some("com.example.defs#a"),
+            matched(&union, json!({"$type": "com.example.defs#a"})).as_deref()
+        );
+        assert_eq!(
+            Some("com.example.foo"),
+            matched(&union, json!({"$type": "com.example.foo#main"})).as_deref()
+        );
+        assert_eq!(
+            Some("com.example.foo#b"),
+            matched(&union, json!({"$type": "com.example.foo#b", "x": 1})).as_deref()
+        );
+        assert_eq!(
+            Some("com.example.bar"),
+            matched(&union, json!({"$type": "com.example.bar"})).as_deref()
+        );
+
+        assert_eq!(None, matched(&union, json!({"x": 1})));
+        assert_eq!(None, matched(&union, json!({"$type": 1})));
+        assert_eq!(None, matched(&union, json!({"$type": "not a type"})));
+        assert_eq!(None, matched(&union, json!("com.example.foo")));
+
+        union.closed = Some(true);
+        assert_eq!(None, matched(&union, json!({"$type": "com.example.bar"})));
+        assert_eq!(
+            Some("com.example.foo#b"),
+            matched(&union, json!({"$type": "com.example.foo#b"})).as_deref()
+        );
+    }
+
+    #[test]
+    fn test_described() {
+        let definition: Definition = serde_json::from_str(
+            r#"{"type": "object", "description": "An object.", "properties": {
+                "a": {"type": "string", "description": "A string."},
+                "b": {"type": "integer"}
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(Some("An object."), definition.description());
+
+        let Definition::Object(object) = definition else {
+            panic!("expected object");
+        };
+        let descriptions: Vec<_> = object
+            .properties
+            .values()
+            .map(Property::description)
+            .collect();
+        assert_eq!(vec![Some("A string."), None], descriptions);
+    }
 }
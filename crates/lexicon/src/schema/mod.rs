@@ -1,17 +1,53 @@
 mod concrete;
 mod container;
 mod document;
+mod ext;
 mod meta;
 mod rpc;
+mod set;
+mod structure;
+mod walk;
 
 pub use self::{
-    concrete::{Blob, Boolean, Bytes, Integer, Link, String, StringFormat},
-    container::{Array, ArrayItem, Object, Record, RecordDefinition, RecordKey},
+    concrete::{Blob, Boolean, Bytes, Integer, Link, Null, String, StringFormat},
+    container::{
+        Array, ArrayItem, InvalidObject, Object, ObjectBuilder, Property, Record, RecordDefinition,
+        RecordKey,
+    },
     document::{Definition, Document, Version},
-    meta::{Metadata, Ref, Token, Union, Unknown},
+    ext::{MergeConflict, SchemaExt, UpgradeError},
+    meta::{Described, Metadata, Ref, RefTarget, Token, Union, Unknown},
+    rpc::{
+        xrpc_path, Body, BodySchema, Notice, ParameterArray, ParameterArrayItem, ParameterValue,
+        Parameters, Procedure, Query, QueryError, QuerySchema, QueryValue,
+    },
+    set::LexiconSet,
+    structure::StructuralError,
+    walk::Node,
 };
+/// The schema uses the identifier types of `atprose_types` itself, so values
+/// pass between the two crates without conversion.
 pub use atprose_types::{Nsid, TypeId};
 
 use crate::Map;
 
 pub type Schema = Map<Nsid, Document>;
+
+/// Hash the entries of `map` in key order.
+///
+/// [`Map`] equality ignores insertion order, so a [`Hash`][std::hash::Hash]
+/// implementation consistent with it must too.
+fn hash_map<K, V, H>(map: &Map<K, V>, state: &mut H)
+where
+    K: std::hash::Hash + Ord,
+    V: std::hash::Hash,
+    H: std::hash::Hasher,
+{
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by_key(|&(key, _)| key);
+
+    state.write_usize(entries.len());
+    for entry in entries {
+        std::hash::Hash::hash(&entry, state);
+    }
+}
@@ -3,11 +3,27 @@ use serde::{Deserialize, Serialize};
 use super::{
     concrete::{Boolean, Integer, String},
     container::Object,
-    meta::{Metadata, Ref, Union, Unknown},
+    meta::{Described, Metadata, Ref, Union, Unknown},
+    Nsid,
 };
 use crate::Map;
 
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+/// The path of the [XRPC][xrpc] endpoint for the query or procedure `nsid`,
+/// relative to the server's origin.
+///
+/// ```
+/// use atprose_lexicon::schema::{xrpc_path, Nsid};
+///
+/// let id = Nsid::new("app.bsky.feed", "getTimeline");
+/// assert_eq!("/xrpc/app.bsky.feed.getTimeline", xrpc_path(&id));
+/// ```
+///
+/// [xrpc]: https://atproto.com/specs/xrpc
+pub fn xrpc_path(nsid: &Nsid) -> std::string::String {
+    format!("/xrpc/{nsid}")
+}
+
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Query {
     #[serde(flatten)]
@@ -23,6 +39,13 @@ pub struct Query {
     pub errors: Option<Vec<Notice>>,
 }
 
+impl Query {
+    /// The HTTP method used to call a query: `GET`.
+    pub fn http_method(&self) -> &'static str {
+        "GET"
+    }
+}
+
 impl std::ops::Deref for Query {
     type Target = Metadata;
 
@@ -31,7 +54,7 @@ impl std::ops::Deref for Query {
     }
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Procedure {
     #[serde(flatten)]
@@ -50,6 +73,13 @@ pub struct Procedure {
     pub errors: Option<Vec<Notice>>,
 }
 
+impl Procedure {
+    /// The HTTP method used to call a procedure: `POST`.
+    pub fn http_method(&self) -> &'static str {
+        "POST"
+    }
+}
+
 impl std::ops::Deref for Procedure {
     type Target = Metadata;
 
@@ -58,7 +88,7 @@ impl std::ops::Deref for Procedure {
     }
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Body {
     #[serde(flatten)]
@@ -70,6 +100,64 @@ pub struct Body {
     pub schema: Option<BodySchema>,
 }
 
+impl Body {
+    /// Whether the body is JSON (`application/json`).
+    pub fn is_json(&self) -> bool {
+        essence(&self.encoding).eq_ignore_ascii_case("application/json")
+    }
+
+    /// Check that `encoding` is a syntactically valid MIME type (like
+    /// `image/png`), or a wildcard like `*/*` or `image/*`.
+    ///
+    /// Returns a message for every problem found.
+    pub fn validate_self(&self) -> Result<(), Vec<std::string::String>> {
+        if is_mime_type(&self.encoding) {
+            Ok(())
+        } else {
+            Err(vec![format!("invalid encoding {:?}", self.encoding)])
+        }
+    }
+}
+
+/// The `type/subtype` of a MIME type, without any parameters.
+fn essence(mime: &str) -> &str {
+    mime.split_once(';')
+        .map_or(mime, |(essence, _)| essence)
+        .trim()
+}
+
+/// Whether `mime` is a [MIME type][rfc]: a `type/subtype` pair, optionally
+/// followed by `; name=value` parameters.
+///
+/// [rfc]: https://www.rfc-editor.org/rfc/rfc2045#section-5.1
+fn is_mime_type(mime: &str) -> bool {
+    fn is_token(value: &str) -> bool {
+        !value.is_empty()
+            && value
+                .bytes()
+                .all(|c| c.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?=".contains(&c))
+    }
+
+    let mut parts = mime.split(';');
+    let Some((kind, subtype)) = parts.next().unwrap_or_default().split_once('/') else {
+        return false;
+    };
+
+    is_token(kind)
+        && is_token(subtype)
+        && (kind != "*" || subtype == "*")
+        && parts.all(|param| {
+            param
+                .trim_start()
+                .split_once('=')
+                .is_some_and(|(name, value)| {
+                    is_token(name)
+                        && (is_token(value)
+                            || (value.len() >= 2 && value.starts_with('"') && value.ends_with('"')))
+                })
+        })
+}
+
 impl std::ops::Deref for Body {
     type Target = Metadata;
 
@@ -78,7 +166,7 @@ impl std::ops::Deref for Body {
     }
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum BodySchema {
     Ref(Ref),
@@ -86,13 +174,31 @@ pub enum BodySchema {
     Object(Object),
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+impl Described for BodySchema {
+    fn description(&self) -> Option<&str> {
+        match self {
+            BodySchema::Ref(value) => value.description(),
+            BodySchema::Union(value) => value.description(),
+            BodySchema::Object(value) => value.description(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(tag = "type")]
 pub enum QuerySchema {
     #[serde(rename = "params")]
     Parameters(Parameters),
 }
 
+impl Described for QuerySchema {
+    fn description(&self) -> Option<&str> {
+        match self {
+            QuerySchema::Parameters(value) => value.description(),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Parameters {
@@ -101,10 +207,138 @@ pub struct Parameters {
 
     pub properties: Map<std::string::String, ParameterValue>,
 
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub required: Vec<std::string::String>,
 }
 
+impl Parameters {
+    /// Check that every name in `required` is one of these `properties`.
+    ///
+    /// Returns a message for every problem found.
+    pub fn validate_self(&self) -> Result<(), Vec<std::string::String>> {
+        let errors: Vec<_> = self
+            .required
+            .iter()
+            .filter(|name| !self.properties.contains_key(*name))
+            .map(|name| format!("required parameter {name:?} is not defined"))
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Encode `values` as a URL query string (without the leading `?`).
+    ///
+    /// Parameters are written in the order of `properties`. An
+    /// [array parameter][ParameterArray] repeats its key once per item, as
+    /// XRPC expects; booleans are written as `true` or `false`.
+    ///
+    /// ```
+    /// use atprose_lexicon::{schema::Parameters, Map};
+    /// use atprose_lexicon::schema::QueryValue;
+    ///
+    /// let params: Parameters = serde_json::from_str(r#"{
+    ///     "properties": {
+    ///         "actors": {"type": "array", "items": {"type": "string"}},
+    ///         "limit": {"type": "integer"}
+    ///     },
+    ///     "required": ["actors"]
+    /// }"#).unwrap();
+    ///
+    /// let values = Map::from([
+    ///     ("limit".to_string(), QueryValue::from(10)),
+    ///     ("actors".to_string(), QueryValue::from(vec!["alice", "bob & co"])),
+    /// ]);
+    /// assert_eq!(
+    ///     Ok("actors=alice&actors=bob%20%26%20co&limit=10".to_string()),
+    ///     params.encode_query(&values),
+    /// );
+    /// ```
+    pub fn encode_query(
+        &self,
+        values: &Map<std::string::String, QueryValue>,
+    ) -> Result<std::string::String, QueryError> {
+        if let Some(name) = values
+            .keys()
+            .find(|name| !self.properties.contains_key(*name))
+        {
+            return Err(QueryError::Unknown(name.clone()));
+        }
+        if let Some(name) = self
+            .required
+            .iter()
+            .find(|name| !values.contains_key(*name))
+        {
+            return Err(QueryError::Missing(name.clone()));
+        }
+
+        let mut query = std::string::String::new();
+        for (name, schema) in &self.properties {
+            let Some(value) = values.get(name) else {
+                continue;
+            };
+
+            match (schema, value) {
+                (ParameterValue::Array(array), QueryValue::Array(items)) => {
+                    for item in items {
+                        if !array.items.accepts(item) {
+                            return Err(QueryError::Type(name.clone()));
+                        }
+                        append_query(&mut query, name, item);
+                    }
+                }
+                (schema, value) if schema.accepts(value) => append_query(&mut query, name, value),
+                _ => return Err(QueryError::Type(name.clone())),
+            }
+        }
+
+        Ok(query)
+    }
+}
+
+fn append_query(query: &mut std::string::String, name: &str, value: &QueryValue) {
+    use std::fmt::Write;
+
+    if !query.is_empty() {
+        query.push('&');
+    }
+
+    encode_component(query, name);
+    query.push('=');
+    match value {
+        QueryValue::Boolean(value) => write!(query, "{value}").unwrap(),
+        QueryValue::Integer(value) => write!(query, "{value}").unwrap(),
+        QueryValue::String(value) => encode_component(query, value),
+        QueryValue::Array(_) => unreachable!("arrays are expanded by the caller"),
+    }
+}
+
+/// Percent-encode `value` into `query`, leaving only RFC 3986 unreserved
+/// characters as-is.
+fn encode_component(query: &mut std::string::String, value: &str) {
+    use std::fmt::Write;
+
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                query.push(char::from(byte))
+            }
+            _ => write!(query, "%{byte:02X}").unwrap(),
+        }
+    }
+}
+
+impl std::hash::Hash for Parameters {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.metadata.hash(state);
+        super::hash_map(&self.properties, state);
+        self.required.hash(state);
+    }
+}
+
 impl std::ops::Deref for Parameters {
     type Target = Metadata;
 
@@ -113,7 +347,7 @@ impl std::ops::Deref for Parameters {
     }
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum ParameterValue {
     Boolean(Boolean),
@@ -123,7 +357,36 @@ pub enum ParameterValue {
     Array(ParameterArray),
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+impl ParameterValue {
+    /// Whether `value` is a scalar of this type. Arrays are checked item by
+    /// item by [`Parameters::encode_query`].
+    fn accepts(&self, value: &QueryValue) -> bool {
+        matches!(
+            (self, value),
+            (ParameterValue::Boolean(_), QueryValue::Boolean(_))
+                | (ParameterValue::Integer(_), QueryValue::Integer(_))
+                | (ParameterValue::String(_), QueryValue::String(_))
+                | (
+                    ParameterValue::Unknown(_),
+                    QueryValue::Boolean(_) | QueryValue::Integer(_) | QueryValue::String(_)
+                )
+        )
+    }
+}
+
+impl Described for ParameterValue {
+    fn description(&self) -> Option<&str> {
+        match self {
+            ParameterValue::Boolean(value) => value.description(),
+            ParameterValue::Integer(value) => value.description(),
+            ParameterValue::String(value) => value.description(),
+            ParameterValue::Unknown(value) => value.description(),
+            ParameterValue::Array(value) => value.description(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ParameterArray {
     #[serde(flatten)]
@@ -138,7 +401,15 @@ pub struct ParameterArray {
     pub max_length: Option<usize>,
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+impl std::ops::Deref for ParameterArray {
+    type Target = Metadata;
+
+    fn deref(&self) -> &Self::Target {
+        &self.metadata
+    }
+}
+
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum ParameterArrayItem {
     Boolean(Boolean),
@@ -147,7 +418,83 @@ pub enum ParameterArrayItem {
     Unknown(Unknown),
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+impl ParameterArrayItem {
+    fn accepts(&self, value: &QueryValue) -> bool {
+        matches!(
+            (self, value),
+            (ParameterArrayItem::Boolean(_), QueryValue::Boolean(_))
+                | (ParameterArrayItem::Integer(_), QueryValue::Integer(_))
+                | (ParameterArrayItem::String(_), QueryValue::String(_))
+                | (
+                    ParameterArrayItem::Unknown(_),
+                    QueryValue::Boolean(_) | QueryValue::Integer(_) | QueryValue::String(_)
+                )
+        )
+    }
+}
+
+impl Described for ParameterArrayItem {
+    fn description(&self) -> Option<&str> {
+        match self {
+            ParameterArrayItem::Boolean(value) => value.description(),
+            ParameterArrayItem::Integer(value) => value.description(),
+            ParameterArrayItem::String(value) => value.description(),
+            ParameterArrayItem::Unknown(value) => value.description(),
+        }
+    }
+}
+
+/// The value of a query parameter, for [`Parameters::encode_query`].
+#[derive(Hash, PartialEq, Eq, Clone, Debug)]
+pub enum QueryValue {
+    Boolean(bool),
+    Integer(i64),
+    String(std::string::String),
+    Array(Vec<QueryValue>),
+}
+
+impl From<bool> for QueryValue {
+    fn from(value: bool) -> Self {
+        QueryValue::Boolean(value)
+    }
+}
+
+impl From<i64> for QueryValue {
+    fn from(value: i64) -> Self {
+        QueryValue::Integer(value)
+    }
+}
+
+impl From<&str> for QueryValue {
+    fn from(value: &str) -> Self {
+        QueryValue::String(value.to_string())
+    }
+}
+
+impl From<std::string::String> for QueryValue {
+    fn from(value: std::string::String) -> Self {
+        QueryValue::String(value)
+    }
+}
+
+impl<T: Into<QueryValue>> From<Vec<T>> for QueryValue {
+    fn from(values: Vec<T>) -> Self {
+        QueryValue::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Query parameter values which don't match their [`Parameters`].
+#[derive(thiserror::Error, PartialEq, Eq, Clone, Debug)]
+pub enum QueryError {
+    #[error("missing required parameter {0:?}")]
+    Missing(std::string::String),
+    #[error("unknown parameter {0:?}")]
+    Unknown(std::string::String),
+    #[error("parameter {0:?} has the wrong type")]
+    Type(std::string::String),
+}
+
+#[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Notice {
     pub name: std::string::String,
@@ -155,3 +502,162 @@ pub struct Notice {
     #[serde(flatten)]
     pub metadata: Metadata,
 }
+
+impl std::ops::Deref for Notice {
+    type Target = Metadata;
+
+    fn deref(&self) -> &Self::Target {
+        &self.metadata
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        Body, ParameterArrayItem, ParameterValue, Parameters, QueryError, QuerySchema, QueryValue,
+    };
+    use crate::Map;
+
+    #[test]
+    fn test_parameter_arrays() {
+        let json = serde_json::json!({
+            "type": "params",
+            "properties": {
+                "ids": {"type": "array", "items": {"type": "integer", "minimum": 1}, "maxLength": 25},
+                "flags": {"type": "array", "items": {"type": "boolean"}},
+                "names": {"type": "array", "items": {"type": "string"}},
+                "extra": {"type": "array", "items": {"type": "unknown"}}
+            }
+        });
+
+        let QuerySchema::Parameters(params) = serde_json::from_value(json.clone()).unwrap();
+        assert!(params.required.is_empty());
+
+        let kinds: Vec<_> = params
+            .properties
+            .values()
+            .map(|value| match value {
+                ParameterValue::Array(array) => match &array.items {
+                    ParameterArrayItem::Integer(integer) => {
+                        assert_eq!(Some(1), integer.minimum);
+                        assert_eq!(Some(25), array.max_length);
+                        "integer"
+                    }
+                    ParameterArrayItem::Boolean(_) => "boolean",
+                    ParameterArrayItem::String(_) => "string",
+                    ParameterArrayItem::Unknown(_) => "unknown",
+                },
+                value => panic!("expected array, got {value:?}"),
+            })
+            .collect();
+        assert_eq!(vec!["integer", "boolean", "string", "unknown"], kinds);
+
+        let schema = QuerySchema::Parameters(params);
+        assert_eq!(json, serde_json::to_value(&schema).unwrap());
+
+        let json = serde_json::json!({
+            "type": "params",
+            "properties": {"ids": {"type": "array", "items": {"type": "ref", "ref": "#id"}}}
+        });
+        assert!(serde_json::from_value::<QuerySchema>(json).is_err());
+    }
+
+    #[test]
+    fn test_encode_query() {
+        let params: Parameters = serde_json::from_str(
+            r#"{
+                "properties": {
+                    "uri": {"type": "string"},
+                    "depth": {"type": "integer"},
+                    "reverse": {"type": "boolean"},
+                    "tags": {"type": "array", "items": {"type": "string"}}
+                },
+                "required": ["uri"]
+            }"#,
+        )
+        .unwrap();
+
+        let values = |entries: &[(&str, QueryValue)]| -> Map<String, QueryValue> {
+            entries
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect()
+        };
+
+        assert_eq!(
+            Ok("uri=at%3A%2F%2Falice.test&depth=-1&reverse=true&tags=a&tags=b".to_string()),
+            params.encode_query(&values(&[
+                ("reverse", true.into()),
+                ("tags", vec!["a", "b"].into()),
+                ("uri", "at://alice.test".into()),
+                ("depth", (-1).into()),
+            ]))
+        );
+        assert_eq!(
+            Ok("uri=x".to_string()),
+            params.encode_query(&values(&[
+                ("uri", "x".into()),
+                ("tags", QueryValue::Array(vec![]))
+            ]))
+        );
+
+        assert_eq!(
+            Err(QueryError::Missing("uri".to_string())),
+            params.encode_query(&values(&[("depth", 1.into())]))
+        );
+        assert_eq!(
+            Err(QueryError::Unknown("cursor".to_string())),
+            params.encode_query(&values(&[("uri", "x".into()), ("cursor", "c".into())]))
+        );
+        assert_eq!(
+            Err(QueryError::Type("depth".to_string())),
+            params.encode_query(&values(&[("uri", "x".into()), ("depth", "1".into())]))
+        );
+        assert_eq!(
+            Err(QueryError::Type("tags".to_string())),
+            params.encode_query(&values(&[("uri", "x".into()), ("tags", vec![1i64].into())]))
+        );
+        assert_eq!(
+            Err(QueryError::Type("uri".to_string())),
+            params.encode_query(&values(&[("uri", vec!["x"].into())]))
+        );
+    }
+
+    #[test]
+    fn test_body_encoding() {
+        let body = |encoding: &str| Body {
+            metadata: Default::default(),
+            encoding: encoding.to_string(),
+            schema: None,
+        };
+
+        for encoding in [
+            "application/json",
+            "*/*",
+            "image/png",
+            "application/vnd.ipld.car",
+            "image/*",
+            "text/plain; charset=utf-8",
+            "text/plain;charset=\"utf-8\"",
+        ] {
+            assert_eq!(Ok(()), body(encoding).validate_self(), "{encoding}");
+        }
+        for encoding in [
+            "",
+            "json",
+            "application/",
+            "/json",
+            "*/json",
+            "a b/c",
+            "text/plain; charset",
+            "a/b/c",
+        ] {
+            assert!(body(encoding).validate_self().is_err(), "{encoding}");
+        }
+
+        assert!(body("application/json").is_json());
+        assert!(body("Application/JSON; charset=utf-8").is_json());
+        assert!(!body("*/*").is_json());
+        assert!(!body("application/jsonl").is_json());
+    }
+}
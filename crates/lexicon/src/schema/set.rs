@@ -0,0 +1,135 @@
+use super::{Definition, Document, Nsid, RefTarget, Schema, TypeId};
+use crate::Map;
+
+/// Every definition in a set of lexicon documents, indexed by [`TypeId`].
+///
+/// A [`Schema`] holds whole documents; this looks up the individual
+/// definitions within them, like `app.bsky.feed.defs#postView`.
+///
+/// ```
+/// use atprose_lexicon::schema::{Document, LexiconSet, Nsid, RefTarget};
+///
+/// let document: Document = serde_json::from_str(r#"{
+///     "lexicon": 1,
+///     "id": "com.example.defs",
+///     "defs": {"name": {"type": "string"}}
+/// }"#).unwrap();
+///
+/// let set: LexiconSet = [document].into_iter().collect();
+/// assert!(set.get(&"com.example.defs#name".parse().unwrap()).is_some());
+///
+/// let base = Nsid::new("com.example", "defs");
+/// let target: RefTarget = "#name".parse().unwrap();
+/// assert_eq!(Some("string"), set.resolve(&target, &base).map(|def| def.kind()));
+/// ```
+#[derive(PartialEq, Eq, Default, Clone, Debug)]
+pub struct LexiconSet {
+    types: Map<TypeId, Definition>,
+}
+
+impl LexiconSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add every definition in `document`, replacing any definitions with the
+    /// same type IDs.
+    pub fn insert(&mut self, document: Document) {
+        self.types.extend(document.into_types());
+    }
+
+    pub fn get(&self, id: &TypeId) -> Option<&Definition> {
+        self.types.get(id)
+    }
+
+    pub fn contains(&self, id: &TypeId) -> bool {
+        self.types.contains_key(id)
+    }
+
+    /// Find the definition a [ref][RefTarget] points to, resolving a local ref
+    /// (like `#postView`) relative to the document `base`.
+    pub fn resolve(&self, target: &RefTarget, base: &Nsid) -> Option<&Definition> {
+        self.get(&target.resolve(base))
+    }
+
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Iterate over every definition, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (&TypeId, &Definition)> {
+        self.types.iter()
+    }
+}
+
+impl From<Schema> for LexiconSet {
+    fn from(schema: Schema) -> Self {
+        schema.into_values().collect()
+    }
+}
+
+impl FromIterator<Document> for LexiconSet {
+    fn from_iter<T: IntoIterator<Item = Document>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Document> for LexiconSet {
+    fn extend<T: IntoIterator<Item = Document>>(&mut self, iter: T) {
+        for document in iter {
+            self.insert(document);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LexiconSet;
+    use crate::schema::{Definition, Document, Nsid, RefTarget, Schema, TypeId};
+
+    fn document(json: &str) -> Document {
+        serde_json::from_str(json).expect("parse document")
+    }
+
+    #[test]
+    fn test_lexicon_set() {
+        let post = document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test/post.json"
+        )));
+        let defs = document(
+            r##"{"lexicon": 1, "id": "dev.atprose.test.defs", "defs": {
+                "count": {"type": "integer"},
+                "viewer": {"type": "ref", "ref": "#count"}
+            }}"##,
+        );
+
+        let schema: Schema = [post.clone(), defs].into_iter().collect();
+        let set = LexiconSet::from(schema);
+        assert_eq!(post.defs.len() + 2, set.len());
+
+        let main = TypeId::new(post.id.clone(), None);
+        assert!(matches!(set.get(&main), Some(Definition::Record(_))));
+        assert!(set.contains(&"dev.atprose.test.post#main".parse().unwrap()));
+
+        let base = Nsid::new("dev.atprose.test", "defs");
+        let Some(Definition::Ref(viewer)) = set.get(&TypeId::of(&base, "viewer")) else {
+            panic!("expected ref");
+        };
+        assert!(matches!(
+            set.resolve(&viewer.target, &base),
+            Some(Definition::Integer(_))
+        ));
+
+        let target: RefTarget = "dev.atprose.test.defs#count".parse().unwrap();
+        assert!(set.resolve(&target, &post.id).is_some());
+        let target: RefTarget = "#count".parse().unwrap();
+        assert!(set.resolve(&target, &post.id).is_none());
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+
+use super::{Document, Node, RefTarget, Schema};
+
+impl Document {
+    /// Run every structural check on this document, resolving refs to other
+    /// documents in `schema`.
+    ///
+    /// This checks that:
+    ///
+    /// - every object's `required` and `nullable` names are
+    ///   [defined][Object::validate_self],
+    /// - every union has [refs, without duplicates][Union::validate_self],
+    /// - every integer's constraints are [consistent][Integer::validate_self],
+    /// - every query or procedure's `required` parameters are
+    ///   [defined][super::Parameters::validate_self], and its input and output
+    ///   encodings are [valid][Body::validate_self], and
+    /// - every ref (including union refs) points to a definition in this
+    ///   document or in `schema`.
+    ///
+    /// Every problem found is returned, with its location in the same syntax
+    /// as [`SchemaChange`][crate::SchemaChange] paths.
+    ///
+    /// ```
+    /// use atprose_lexicon::schema::{Document, Schema};
+    ///
+    /// let document: Document = serde_json::from_str(r##"{
+    ///     "lexicon": 1,
+    ///     "id": "com.example.thing",
+    ///     "defs": {"main": {"type": "object", "required": ["name"], "properties": {
+    ///         "owner": {"type": "ref", "ref": "#user"}
+    ///     }}}
+    /// }"##).unwrap();
+    ///
+    /// let errors = document.validate(&Schema::new()).unwrap_err();
+    /// assert_eq!(
+    ///     vec![
+    ///         "com.example.thing#main: required property \"name\" is not defined",
+    ///         "com.example.thing#main.owner: unresolved ref #user",
+    ///     ],
+    ///     errors.iter().map(ToString::to_string).collect::<Vec<_>>()
+    /// );
+    /// ```
+    pub fn validate(&self, schema: &Schema) -> Result<(), Vec<StructuralError>> {
+        let mut checker = Checker {
+            document: self,
+            schema,
+            errors: Vec::new(),
+        };
+
+        self.walk(|path, node| match node {
+            Node::Object(object) => checker.check(path, object.validate_self()),
+            Node::Parameters(parameters) => checker.check(path, parameters.validate_self()),
+            Node::Body(body) => checker.check(path, body.validate_self()),
+            Node::Integer(integer) => checker.check(path, integer.validate_self()),
+            Node::Ref(value) => checker.reference(path, &value.target),
+            Node::Union(union) => {
+                checker.check(path, union.validate_self());
+                for target in union.unique_options() {
+                    checker.reference(path, target);
+                }
+            }
+            _ => {}
+        });
+
+        if checker.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(checker.errors)
+        }
+    }
+}
+
+/// A structural problem in a lexicon document, found by
+/// [`Document::validate`].
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct StructuralError {
+    /// Where the problem is, like `com.example.thing#main.owner`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for StructuralError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for StructuralError {}
+
+struct Checker<'a> {
+    document: &'a Document,
+    schema: &'a Schema,
+    errors: Vec<StructuralError>,
+}
+
+impl Checker<'_> {
+    fn check(&mut self, path: &str, result: Result<(), Vec<String>>) {
+        if let Err(messages) = result {
+            self.errors
+                .extend(messages.into_iter().map(|message| StructuralError {
+                    path: path.to_owned(),
+                    message,
+                }));
+        }
+    }
+
+    fn reference(&mut self, path: &str, target: &RefTarget) {
+        let id = target.resolve(&self.document.id);
+        let document = if id.ns == self.document.id {
+            Some(self.document)
+        } else {
+            self.schema.get(&id.ns)
+        };
+
+        let name = id.name.as_deref().unwrap_or("main");
+        if !document.is_some_and(|document| document.defs.contains_key(name)) {
+            self.errors.push(StructuralError {
+                path: path.to_owned(),
+                message: format!("unresolved ref {target}"),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::schema::{Document, Schema};
+
+    fn document(json: &str) -> Document {
+        serde_json::from_str(json).expect("parse document")
+    }
+
+    #[test]
+    fn test_validate() {
+        let defs = document(
+            r#"{"lexicon": 1, "id": "com.example.defs", "defs": {
+                "user": {"type": "object", "properties": {}}
+            }}"#,
+        );
+        let schema: Schema = [defs].into_iter().collect();
+
+        let valid = document(
+            r##"{"lexicon": 1, "id": "com.example.ok", "defs": {
+                "main": {"type": "object", "required": ["owner"], "properties": {
+                    "owner": {"type": "ref", "ref": "com.example.defs#user"},
+                    "other": {"type": "ref", "ref": "#other"}
+                }},
+                "other": {"type": "integer", "minimum": 0}
+            }}"##,
+        );
+        assert_eq!(Ok(()), valid.validate(&schema));
+
+        let invalid = document(
+            r##"{"lexicon": 1, "id": "com.example.bad", "defs": {
+                "main": {"type": "record", "key": "tid", "record": {
+                    "type": "object",
+                    "nullable": ["gone"],
+                    "properties": {
+                        "count": {"type": "integer", "minimum": 5, "maximum": 1},
+                        "tags": {"type": "array", "items": {"type": "union", "refs": []}}
+                    }
+                }},
+                "get": {
+                    "type": "query",
+                    "parameters": {"type": "params", "required": ["q"], "properties": {}},
+                    "output": {"encoding": "json", "schema": {"type": "ref", "ref": "com.example.defs#nobody"}}
+                },
+                "either": {"type": "union", "refs": ["#main", "com.example.missing", "#main"]}
+            }}"##,
+        );
+
+        let errors: Vec<_> = invalid
+            .validate(&schema)
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            vec![
+                "com.example.bad#main: nullable property \"gone\" is not defined",
+                "com.example.bad#main.count: minimum 5 is greater than maximum 1",
+                "com.example.bad#main.tags[]: union has no refs",
+                "com.example.bad#get: required parameter \"q\" is not defined",
+                "com.example.bad#get.output: invalid encoding \"json\"",
+                "com.example.bad#get.output: unresolved ref com.example.defs#nobody",
+                "com.example.bad#either: duplicate union ref #main",
+                "com.example.bad#either: unresolved ref com.example.missing",
+            ],
+            errors
+        );
+    }
+}
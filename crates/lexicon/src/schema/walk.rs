@@ -0,0 +1,300 @@
+use super::{
+    Array, ArrayItem, Blob, Body, BodySchema, Boolean, Bytes, Definition, Document, Integer, Link,
+    Object, ParameterArray, ParameterArrayItem, ParameterValue, Parameters, Procedure, Property,
+    Query, QuerySchema, Record, RecordDefinition, Ref, String, Token, Union, Unknown,
+};
+
+/// Any type within a lexicon [`Document`], as visited by
+/// [`Document::walk`].
+///
+/// Definitions, object properties, array items, query parameters, and XRPC
+/// bodies each have their own enum of the types allowed there; every one of
+/// them converts to a `Node`, so a pass over a document can handle each type
+/// in one place.
+#[derive(Clone, Copy, Debug)]
+pub enum Node<'a> {
+    Record(&'a Record),
+    Query(&'a Query),
+    Procedure(&'a Procedure),
+    Parameters(&'a Parameters),
+    Body(&'a Body),
+
+    Array(&'a Array),
+    ParameterArray(&'a ParameterArray),
+    Object(&'a Object),
+
+    Blob(&'a Blob),
+    Boolean(&'a Boolean),
+    Bytes(&'a Bytes),
+    Integer(&'a Integer),
+    Link(&'a Link),
+    String(&'a String),
+    Unknown(&'a Unknown),
+
+    Ref(&'a Ref),
+    Union(&'a Union),
+
+    Token(&'a Token),
+
+    Unsupported {
+        type_name: &'a str,
+        raw: &'a serde_json::Value,
+    },
+}
+
+impl Document {
+    /// Call `visit` with every type in this document, in the order they
+    /// appear.
+    ///
+    /// Each type is visited before the types within it, and with its path,
+    /// in the same syntax as [`SchemaChange`][crate::SchemaChange] paths. A
+    /// record's object, a query's parameters, and an XRPC body's schema have
+    /// the same path as the type they belong to.
+    ///
+    /// ```
+    /// use atprose_lexicon::schema::{Document, Node};
+    ///
+    /// let document: Document = serde_json::from_str(r#"{
+    ///     "lexicon": 1,
+    ///     "id": "com.example.defs",
+    ///     "defs": {"view": {"type": "object", "properties": {
+    ///         "count": {"type": "integer"},
+    ///         "tags": {"type": "array", "items": {"type": "integer"}}
+    ///     }}}
+    /// }"#).unwrap();
+    ///
+    /// let mut integers = Vec::new();
+    /// document.walk(|path, node| {
+    ///     if let Node::Integer(_) = node {
+    ///         integers.push(path.to_owned());
+    ///     }
+    /// });
+    /// assert_eq!(vec!["com.example.defs#view.count", "com.example.defs#view.tags[]"], integers);
+    /// ```
+    pub fn walk<'a>(&'a self, mut visit: impl FnMut(&str, Node<'a>)) {
+        for (name, def) in &self.defs {
+            walk(&format!("{}#{name}", self.id), def.into(), &mut visit);
+        }
+    }
+}
+
+fn walk<'a, F>(path: &str, node: Node<'a>, visit: &mut F)
+where
+    F: FnMut(&str, Node<'a>),
+{
+    visit(path, node);
+
+    match node {
+        Node::Record(record) => {
+            let RecordDefinition::Object(object) = &record.def;
+            walk(path, Node::Object(object), visit);
+        }
+        Node::Query(query) => {
+            if let Some(QuerySchema::Parameters(parameters)) = &query.parameters {
+                walk(path, Node::Parameters(parameters), visit);
+            }
+            if let Some(output) = &query.output {
+                walk(&format!("{path}.output"), Node::Body(output), visit);
+            }
+        }
+        Node::Procedure(procedure) => {
+            if let Some(QuerySchema::Parameters(parameters)) = &procedure.parameters {
+                walk(path, Node::Parameters(parameters), visit);
+            }
+            if let Some(input) = &procedure.input {
+                walk(&format!("{path}.input"), Node::Body(input), visit);
+            }
+            if let Some(output) = &procedure.output {
+                walk(&format!("{path}.output"), Node::Body(output), visit);
+            }
+        }
+        Node::Parameters(parameters) => {
+            for (name, parameter) in &parameters.properties {
+                walk(&format!("{path}?{name}"), parameter.into(), visit);
+            }
+        }
+        Node::Body(body) => {
+            if let Some(schema) = &body.schema {
+                walk(path, schema.into(), visit);
+            }
+        }
+        Node::Array(array) => walk(&format!("{path}[]"), (&array.items).into(), visit),
+        Node::ParameterArray(array) => walk(&format!("{path}[]"), (&array.items).into(), visit),
+        Node::Object(object) => {
+            for (name, property) in &object.properties {
+                walk(&format!("{path}.{name}"), property.into(), visit);
+            }
+        }
+        Node::Blob(_)
+        | Node::Boolean(_)
+        | Node::Bytes(_)
+        | Node::Integer(_)
+        | Node::Link(_)
+        | Node::String(_)
+        | Node::Unknown(_)
+        | Node::Ref(_)
+        | Node::Union(_)
+        | Node::Token(_)
+        | Node::Unsupported { .. } => {}
+    }
+}
+
+impl<'a> From<&'a Definition> for Node<'a> {
+    fn from(value: &'a Definition) -> Self {
+        match value {
+            Definition::Record(value) => Node::Record(value),
+            Definition::Query(value) => Node::Query(value),
+            Definition::Procedure(value) => Node::Procedure(value),
+            Definition::Array(value) => Node::Array(value),
+            Definition::Object(value) => Node::Object(value),
+            Definition::Blob(value) => Node::Blob(value),
+            Definition::Boolean(value) => Node::Boolean(value),
+            Definition::Bytes(value) => Node::Bytes(value),
+            Definition::Integer(value) => Node::Integer(value),
+            Definition::Link(value) => Node::Link(value),
+            Definition::String(value) => Node::String(value),
+            Definition::Unknown(value) => Node::Unknown(value),
+            Definition::Ref(value) => Node::Ref(value),
+            Definition::Union(value) => Node::Union(value),
+            Definition::Token(value) => Node::Token(value),
+            Definition::Unsupported { type_name, raw } => Node::Unsupported { type_name, raw },
+        }
+    }
+}
+
+impl<'a> From<&'a Property> for Node<'a> {
+    fn from(value: &'a Property) -> Self {
+        match value {
+            Property::Blob(value) => Node::Blob(value),
+            Property::Boolean(value) => Node::Boolean(value),
+            Property::Bytes(value) => Node::Bytes(value),
+            Property::Integer(value) => Node::Integer(value),
+            Property::Link(value) => Node::Link(value),
+            Property::String(value) => Node::String(value),
+            Property::Unknown(value) => Node::Unknown(value),
+            Property::Array(value) => Node::Array(value),
+            Property::Ref(value) => Node::Ref(value),
+            Property::Union(value) => Node::Union(value),
+        }
+    }
+}
+
+impl<'a> From<&'a ArrayItem> for Node<'a> {
+    fn from(value: &'a ArrayItem) -> Self {
+        match value {
+            ArrayItem::Blob(value) => Node::Blob(value),
+            ArrayItem::Boolean(value) => Node::Boolean(value),
+            ArrayItem::Bytes(value) => Node::Bytes(value),
+            ArrayItem::Integer(value) => Node::Integer(value),
+            ArrayItem::Link(value) => Node::Link(value),
+            ArrayItem::String(value) => Node::String(value),
+            ArrayItem::Unknown(value) => Node::Unknown(value),
+            ArrayItem::Ref(value) => Node::Ref(value),
+            ArrayItem::Union(value) => Node::Union(value),
+        }
+    }
+}
+
+impl<'a> From<&'a ParameterValue> for Node<'a> {
+    fn from(value: &'a ParameterValue) -> Self {
+        match value {
+            ParameterValue::Boolean(value) => Node::Boolean(value),
+            ParameterValue::Integer(value) => Node::Integer(value),
+            ParameterValue::String(value) => Node::String(value),
+            ParameterValue::Unknown(value) => Node::Unknown(value),
+            ParameterValue::Array(value) => Node::ParameterArray(value),
+        }
+    }
+}
+
+impl<'a> From<&'a ParameterArrayItem> for Node<'a> {
+    fn from(value: &'a ParameterArrayItem) -> Self {
+        match value {
+            ParameterArrayItem::Boolean(value) => Node::Boolean(value),
+            ParameterArrayItem::Integer(value) => Node::Integer(value),
+            ParameterArrayItem::String(value) => Node::String(value),
+            ParameterArrayItem::Unknown(value) => Node::Unknown(value),
+        }
+    }
+}
+
+impl<'a> From<&'a BodySchema> for Node<'a> {
+    fn from(value: &'a BodySchema) -> Self {
+        match value {
+            BodySchema::Ref(value) => Node::Ref(value),
+            BodySchema::Union(value) => Node::Union(value),
+            BodySchema::Object(value) => Node::Object(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Node;
+    use crate::schema::Document;
+
+    #[test]
+    fn test_walk() {
+        let document: Document = serde_json::from_str(
+            r##"{"lexicon": 1, "id": "com.example.thing", "defs": {
+                "main": {
+                    "type": "procedure",
+                    "parameters": {"type": "params", "properties": {
+                        "tags": {"type": "array", "items": {"type": "string"}}
+                    }},
+                    "input": {"encoding": "application/json", "schema": {
+                        "type": "object",
+                        "properties": {"subject": {"type": "ref", "ref": "#view"}}
+                    }}
+                },
+                "view": {"type": "record", "key": "tid", "record": {
+                    "type": "object",
+                    "properties": {"embeds": {"type": "array", "items": {
+                        "type": "union", "refs": ["#view"]
+                    }}}
+                }},
+                "less": {"type": "token"},
+                "stream": {"type": "subscription"}
+            }}"##,
+        )
+        .unwrap();
+
+        let mut visited = Vec::new();
+        document.walk(|path, node| {
+            let kind = match node {
+                Node::Procedure(_) => "procedure",
+                Node::Parameters(_) => "params",
+                Node::ParameterArray(_) | Node::Array(_) => "array",
+                Node::String(_) => "string",
+                Node::Body(_) => "body",
+                Node::Object(_) => "object",
+                Node::Ref(_) => "ref",
+                Node::Record(_) => "record",
+                Node::Union(_) => "union",
+                Node::Token(_) => "token",
+                Node::Unsupported { type_name, .. } => type_name,
+                node => panic!("unexpected {node:?}"),
+            };
+            visited.push(format!("{path} {kind}"));
+        });
+
+        assert_eq!(
+            vec![
+                "com.example.thing#main procedure",
+                "com.example.thing#main params",
+                "com.example.thing#main?tags array",
+                "com.example.thing#main?tags[] string",
+                "com.example.thing#main.input body",
+                "com.example.thing#main.input object",
+                "com.example.thing#main.input.subject ref",
+                "com.example.thing#view record",
+                "com.example.thing#view object",
+                "com.example.thing#view.embeds array",
+                "com.example.thing#view.embeds[] union",
+                "com.example.thing#less token",
+                "com.example.thing#stream subscription",
+            ],
+            visited
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Compact binary snapshots of a [`Schema`], for loading it quickly.
+//!
+//! Snapshots are [MessagePack], written through the schema types' `serde`
+//! implementations. A format which isn't self-describing (like `bincode`)
+//! can't be used: the schema types are internally tagged by their `type`
+//! field, and carry [unknown][crate::schema::Unknown] JSON values.
+//!
+//! [MessagePack]: https://msgpack.org
+
+use crate::schema::Schema;
+
+/// An error writing or reading a [snapshot][crate::schema::SchemaExt::to_bytes]
+/// of a [`Schema`].
+#[derive(thiserror::Error, Debug)]
+pub enum SnapshotError {
+    #[error("failed to write schema snapshot: {0}")]
+    Encode(#[from] rmp_serde::encode::Error),
+    #[error("invalid schema snapshot: {0}")]
+    Decode(#[from] rmp_serde::decode::Error),
+}
+
+pub(crate) fn encode(schema: &Schema) -> Result<Vec<u8>, SnapshotError> {
+    // Fields are written by name, since the schema types skip serializing
+    // empty optional fields.
+    Ok(rmp_serde::to_vec_named(schema)?)
+}
+
+pub(crate) fn decode(bytes: &[u8]) -> Result<Schema, SnapshotError> {
+    Ok(rmp_serde::from_slice(bytes)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::SnapshotError;
+    use crate::schema::{Document, Schema, SchemaExt};
+
+    #[test]
+    fn test_snapshot() {
+        let document: Document = serde_json::from_str(
+            r##"{
+                "lexicon": 1,
+                "id": "com.example.thing",
+                "revision": 2,
+                "defs": {
+                    "main": {
+                        "type": "record",
+                        "key": "tid",
+                        "record": {
+                            "type": "object",
+                            "required": ["name"],
+                            "properties": {
+                                "name": {"type": "string", "maxGraphemes": 64},
+                                "count": {"type": "integer", "minimum": 0},
+                                "owner": {"type": "union", "refs": ["#user"]}
+                            }
+                        }
+                    },
+                    "user": {"type": "object", "properties": {}},
+                    "extra": {"type": "custom", "anything": [1, "two", {"three": null}]}
+                }
+            }"##,
+        )
+        .unwrap();
+        let schema: Schema = [document].into_iter().collect();
+
+        let bytes = schema.to_bytes().unwrap();
+        assert_eq!(schema, Schema::from_bytes(&bytes).unwrap());
+
+        assert!(matches!(
+            Schema::from_bytes(&bytes[..bytes.len() / 2]),
+            Err(SnapshotError::Decode(_))
+        ));
+    }
+}
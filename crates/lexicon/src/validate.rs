@@ -0,0 +1,663 @@
+//! Validation of values against Lexicon schema types.
+
+use atprose_types::{
+    validate_datetime, validate_language, AtUri, BlobRef, Cid, DatetimePolicy, Handle, Nsid, Rkey,
+    Tid,
+};
+
+use crate::schema::{Blob, Integer, String, StringFormat};
+
+impl Integer {
+    /// Check that `value` satisfies this integer's constraints.
+    ///
+    /// The value must equal the `const` value and be one of the `enum` values,
+    /// if either is given, and must fall within `minimum` and `maximum`
+    /// (inclusive).
+    pub fn validate_value(&self, value: i64) -> Result<(), ValidationError> {
+        if let Some(expected) = self.value {
+            if value != expected {
+                return Err(ValidationError::Const { expected, value });
+            }
+        }
+
+        if let Some(values) = &self.values {
+            if !values.contains(&value) {
+                return Err(ValidationError::Enum { value });
+            }
+        }
+
+        if let Some(minimum) = self.minimum {
+            if value < minimum {
+                return Err(ValidationError::Minimum { minimum, value });
+            }
+        }
+
+        if let Some(maximum) = self.maximum {
+            if value > maximum {
+                return Err(ValidationError::Maximum { maximum, value });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl String {
+    /// Check that `value` is within this string's `min_length` and
+    /// `max_length`, which count UTF-8 bytes, like
+    /// [`check_length`][Self::check_length].
+    ///
+    /// This does not check `min_graphemes` or `max_graphemes`; see
+    /// [`validate_graphemes`][Self::validate_graphemes].
+    pub fn validate_length(&self, value: &str) -> Result<(), ValidationError> {
+        Ok(self.check_length(value)?)
+    }
+
+    /// Check that `value` is within this string's `min_graphemes` and
+    /// `max_graphemes`, which count extended grapheme clusters: what a reader
+    /// would see as a single character, like `é` written with a combining
+    /// accent, or an emoji built from several code points.
+    #[cfg(feature = "unicode")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode")))]
+    pub fn validate_graphemes(&self, value: &str) -> Result<(), ValidationError> {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        if self.min_graphemes.is_none() && self.max_graphemes.is_none() {
+            return Ok(());
+        }
+
+        let graphemes = value.graphemes(true).count();
+
+        if let Some(min_graphemes) = self.min_graphemes {
+            if graphemes < min_graphemes {
+                return Err(ValidationError::MinGraphemes {
+                    min_graphemes,
+                    graphemes,
+                });
+            }
+        }
+
+        if let Some(max_graphemes) = self.max_graphemes {
+            if graphemes > max_graphemes {
+                return Err(ValidationError::MaxGraphemes {
+                    max_graphemes,
+                    graphemes,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl String {
+    /// Create a [`StringValidator`] checking values against every constraint
+    /// of this string type at once.
+    pub fn validator(&self) -> StringValidator<'_> {
+        StringValidator { schema: self }
+    }
+}
+
+/// Checks string values against the constraints of a [`String`] type, from
+/// [`String::validator`].
+///
+/// ```
+/// use atprose_lexicon::{schema::String, StringError};
+///
+/// let schema: String = serde_json::from_str(
+///     r#"{"type": "string", "format": "handle", "maxLength": 20}"#,
+/// ).unwrap();
+/// let validator = schema.validator();
+///
+/// assert_eq!(Ok(()), validator.check("alice.bsky.social"));
+/// assert!(matches!(
+///     validator.check("alice").unwrap_err().as_slice(),
+///     [StringError::Format { .. }]
+/// ));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct StringValidator<'a> {
+    schema: &'a String,
+}
+
+impl StringValidator<'_> {
+    /// Check `value` against the string's `const`, `enum`, `format`, and
+    /// length constraints, returning every constraint it fails.
+    ///
+    /// Grapheme limits are only checked with the `unicode` feature enabled.
+    /// `knownValues` are only a suggestion, so other values aren't rejected;
+    /// use [`is_known`][Self::is_known] to warn about them instead.
+    pub fn check(&self, value: &str) -> Result<(), Vec<StringError>> {
+        let schema = self.schema;
+        let mut errors = Vec::new();
+
+        if let Some(expected) = &schema.value {
+            if value != expected {
+                errors.push(StringError::Const {
+                    expected: expected.clone(),
+                    value: value.to_owned(),
+                });
+            }
+        }
+
+        if let Some(values) = &schema.values {
+            if !values.iter().any(|allowed| allowed == value) {
+                errors.push(StringError::Enum(value.to_owned()));
+            }
+        }
+
+        if let Some(format) = &schema.format {
+            if !format.matches(value) {
+                errors.push(StringError::Format {
+                    format: format.clone(),
+                    value: value.to_owned(),
+                });
+            }
+        }
+
+        if let Err(err) = schema.validate_length(value) {
+            errors.push(StringError::Length(err));
+        }
+
+        #[cfg(feature = "unicode")]
+        if let Err(err) = schema.validate_graphemes(value) {
+            errors.push(StringError::Length(err));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Whether `value` is one of the string's `knownValues`, or the string has
+    /// none.
+    pub fn is_known(&self, value: &str) -> bool {
+        self.schema
+            .known_values
+            .as_ref()
+            .is_none_or(|known| known.iter().any(|known| known == value))
+    }
+}
+
+impl StringFormat {
+    /// Whether `value` is a valid string of this format.
+    ///
+    /// Datetimes must have a timezone, as the Lexicon spec requires (see
+    /// [`DatetimePolicy::Strict`]). DIDs (including those in at-identifiers)
+    /// are checked against the generic DID syntax, so any method is accepted,
+    /// not only those supported by [`Did`][atprose_types::Did]. A generic URI
+    /// only needs a scheme, a non-empty remainder, and no whitespace.
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            StringFormat::AtIdentifier => is_did(value) || Handle::<&str>::parse(value).is_ok(),
+            StringFormat::AtUri => value.parse::<AtUri>().is_ok(),
+            StringFormat::Cid => Cid::try_from(value).is_ok(),
+            StringFormat::Datetime => validate_datetime(value, DatetimePolicy::Strict).is_ok(),
+            StringFormat::Did => is_did(value),
+            StringFormat::Handle => Handle::<&str>::parse(value).is_ok(),
+            StringFormat::Nsid => Nsid::is_valid(value),
+            StringFormat::RecordKey => Rkey::try_new(value).is_ok(),
+            StringFormat::Tid => Tid::decode(value).is_ok(),
+            StringFormat::Language => validate_language(value).is_ok(),
+            StringFormat::Uri => is_uri(value),
+        }
+    }
+}
+
+/// Whether `value` has the [syntax of a DID][syntax]: `did:`, a lowercase
+/// method name, `:`, and an identifier which doesn't end in `:` or `%`.
+///
+/// [syntax]: https://atproto.com/specs/did#at-protocol-did-identifier-syntax
+fn is_did(value: &str) -> bool {
+    let Some((method, id)) = value
+        .strip_prefix("did:")
+        .and_then(|rest| rest.split_once(':'))
+    else {
+        return false;
+    };
+
+    value.len() <= 2048
+        && !method.is_empty()
+        && method.bytes().all(|c| c.is_ascii_lowercase())
+        && !id.is_empty()
+        && id
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, b'.' | b'_' | b':' | b'%' | b'-'))
+        && !id.ends_with([':', '%'])
+}
+
+fn is_uri(value: &str) -> bool {
+    let Some((scheme, rest)) = value.split_once(':') else {
+        return false;
+    };
+
+    let mut scheme = scheme.chars();
+    scheme.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !rest.is_empty()
+        && !value.chars().any(char::is_whitespace)
+}
+
+impl Blob {
+    /// Check that `blob` has one of the `accept`ed MIME types, and is no larger
+    /// than `max_size`.
+    ///
+    /// An `accept` pattern may be an exact MIME type (`image/png`), a wildcard
+    /// subtype (`image/*`), or `*/*` for any type. Matching ignores ASCII case,
+    /// and any parameters (like `; charset=utf-8`) on the blob's MIME type.
+    pub fn validate_value(&self, blob: &BlobRef) -> Result<(), ValidationError> {
+        if let Some(accept) = &self.accept {
+            if !accept
+                .iter()
+                .any(|pattern| mime_matches(pattern, &blob.mime_type))
+            {
+                return Err(BlobError::MimeType(blob.mime_type.clone()).into());
+            }
+        }
+
+        if let Some(max_size) = self.max_size {
+            if blob.size > max_size {
+                return Err(BlobError::Size {
+                    max_size,
+                    size: blob.size,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn mime_essence(mime: &str) -> &str {
+    mime.split(';').next().unwrap_or_default().trim()
+}
+
+fn mime_matches(pattern: &str, mime_type: &str) -> bool {
+    let (Some((pattern_type, pattern_subtype)), Some((mime_type, mime_subtype))) = (
+        mime_essence(pattern).split_once('/'),
+        mime_essence(mime_type).split_once('/'),
+    ) else {
+        return false;
+    };
+
+    let part = |pattern: &str, value: &str| pattern == "*" || pattern.eq_ignore_ascii_case(value);
+
+    part(pattern_type, mime_type) && part(pattern_subtype, mime_subtype)
+}
+
+/// A [blob][BlobRef] which does not satisfy its [schema][Blob].
+#[derive(thiserror::Error, PartialEq, Eq, Clone, Debug)]
+pub enum BlobError {
+    #[error("blob MIME type {0:?} is not accepted")]
+    MimeType(std::string::String),
+    #[error("blob is {size} bytes, more than the maximum {max_size}")]
+    Size { max_size: usize, size: usize },
+}
+
+/// A string outside the `min_length` or `max_length` of its [schema][String],
+/// from [`String::check_length`]. Both lengths are in UTF-8 bytes.
+#[derive(thiserror::Error, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum LengthError {
+    #[error("string is {length} bytes, less than the minimum {min_length}")]
+    TooShort { min_length: usize, length: usize },
+    #[error("string is {length} bytes, more than the maximum {max_length}")]
+    TooLong { max_length: usize, length: usize },
+}
+
+/// A string which does not satisfy a constraint of its [schema][String],
+/// from [`StringValidator::check`].
+#[derive(thiserror::Error, PartialEq, Eq, Clone, Debug)]
+pub enum StringError {
+    #[error("expected constant value {expected:?}, not {value:?}")]
+    Const {
+        expected: std::string::String,
+        value: std::string::String,
+    },
+    #[error("{0:?} is not one of the allowed values")]
+    Enum(std::string::String),
+    #[error("{value:?} is not a valid {format}")]
+    Format {
+        format: StringFormat,
+        value: std::string::String,
+    },
+    #[error(transparent)]
+    Length(ValidationError),
+}
+
+/// A value which does not satisfy the constraints of its schema type.
+#[derive(thiserror::Error, PartialEq, Eq, Clone, Debug)]
+pub enum ValidationError {
+    #[error("expected constant value {expected}, not {value}")]
+    Const { expected: i64, value: i64 },
+    #[error("{value} is not one of the allowed values")]
+    Enum { value: i64 },
+    #[error("{value} is less than the minimum {minimum}")]
+    Minimum { minimum: i64, value: i64 },
+    #[error("{value} is greater than the maximum {maximum}")]
+    Maximum { maximum: i64, value: i64 },
+    #[error("string is {length} bytes, less than the minimum {min_length}")]
+    MinLength { min_length: usize, length: usize },
+    #[error("string is {length} bytes, more than the maximum {max_length}")]
+    MaxLength { max_length: usize, length: usize },
+    #[error("string is {graphemes} graphemes, less than the minimum {min_graphemes}")]
+    MinGraphemes {
+        min_graphemes: usize,
+        graphemes: usize,
+    },
+    #[error("string is {graphemes} graphemes, more than the maximum {max_graphemes}")]
+    MaxGraphemes {
+        max_graphemes: usize,
+        graphemes: usize,
+    },
+    #[error(transparent)]
+    Blob(#[from] BlobError),
+}
+
+impl From<LengthError> for ValidationError {
+    fn from(error: LengthError) -> Self {
+        match error {
+            LengthError::TooShort { min_length, length } => {
+                ValidationError::MinLength { min_length, length }
+            }
+            LengthError::TooLong { max_length, length } => {
+                ValidationError::MaxLength { max_length, length }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use atprose_types::{BlobRef, Cid};
+
+    use super::{BlobError, LengthError, StringError, ValidationError};
+    use crate::schema::{Blob, Integer, String, StringFormat};
+
+    #[test]
+    fn test_integer() {
+        let unbounded = Integer::default();
+        assert_eq!(Ok(()), unbounded.validate_value(i64::MIN));
+        assert_eq!(Ok(()), unbounded.validate_value(i64::MAX));
+
+        let bounded = Integer {
+            minimum: Some(1),
+            maximum: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(Ok(()), bounded.validate_value(1));
+        assert_eq!(Ok(()), bounded.validate_value(10));
+        assert_eq!(
+            Err(ValidationError::Minimum {
+                minimum: 1,
+                value: 0
+            }),
+            bounded.validate_value(0)
+        );
+        assert_eq!(
+            Err(ValidationError::Maximum {
+                maximum: 10,
+                value: 11
+            }),
+            bounded.validate_value(11)
+        );
+
+        let values = Integer {
+            values: Some(vec![2, 4, 8]),
+            ..Default::default()
+        };
+        assert_eq!(Ok(()), values.validate_value(4));
+        assert_eq!(
+            Err(ValidationError::Enum { value: 5 }),
+            values.validate_value(5)
+        );
+
+        let constant = Integer {
+            value: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(Ok(()), constant.validate_value(3));
+        assert_eq!(
+            Err(ValidationError::Const {
+                expected: 3,
+                value: 4
+            }),
+            constant.validate_value(4)
+        );
+    }
+
+    #[test]
+    fn test_string_length() {
+        let schema = String {
+            min_length: Some(2),
+            max_length: Some(8),
+            ..Default::default()
+        };
+        assert_eq!(Ok(()), schema.validate_length("ok"));
+        assert_eq!(Ok(()), schema.validate_length("é"));
+        assert_eq!(
+            Err(ValidationError::MinLength {
+                min_length: 2,
+                length: 1
+            }),
+            schema.validate_length("e")
+        );
+        assert_eq!(
+            Err(ValidationError::MaxLength {
+                max_length: 8,
+                length: 25
+            }),
+            schema.validate_length("👨‍👩‍👧‍👦")
+        );
+
+        assert_eq!(Ok(()), schema.check_length("é"));
+        assert_eq!(
+            Err(LengthError::TooShort {
+                min_length: 2,
+                length: 1
+            }),
+            schema.check_length("e")
+        );
+        assert_eq!(
+            Err(LengthError::TooLong {
+                max_length: 8,
+                length: 9
+            }),
+            schema.check_length("ééééa")
+        );
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_string_graphemes() {
+        let schema = String {
+            min_graphemes: Some(1),
+            max_graphemes: Some(2),
+            ..Default::default()
+        };
+
+        // one grapheme, seven code points, 25 bytes
+        assert_eq!(Ok(()), schema.validate_graphemes("👨‍👩‍👧‍👦"));
+        // "e" followed by a combining acute accent
+        assert_eq!(Ok(()), schema.validate_graphemes("e\u{301}"));
+        // flags are pairs of regional indicators
+        assert_eq!(Ok(()), schema.validate_graphemes("🇳🇴🇯🇵"));
+        assert_eq!(
+            Err(ValidationError::MaxGraphemes {
+                max_graphemes: 2,
+                graphemes: 3
+            }),
+            schema.validate_graphemes("e\u{301}e\u{301}e\u{301}")
+        );
+        assert_eq!(
+            Err(ValidationError::MinGraphemes {
+                min_graphemes: 1,
+                graphemes: 0
+            }),
+            schema.validate_graphemes("")
+        );
+    }
+
+    #[test]
+    fn test_string_validator() {
+        let schema = String {
+            format: Some(StringFormat::Nsid),
+            max_length: Some(20),
+            values: Some(vec!["com.example.a".to_owned(), "not an nsid".to_owned()]),
+            known_values: Some(vec!["com.example.a".to_owned()]),
+            ..Default::default()
+        };
+        let validator = schema.validator();
+
+        assert_eq!(Ok(()), validator.check("com.example.a"));
+        assert!(validator.is_known("com.example.a"));
+        assert_eq!(
+            Err(vec![StringError::Format {
+                format: StringFormat::Nsid,
+                value: "not an nsid".to_owned()
+            }]),
+            validator.check("not an nsid")
+        );
+        assert_eq!(
+            Err(vec![
+                StringError::Enum("com.example.abcdefghijklmnop".to_owned()),
+                StringError::Length(ValidationError::MaxLength {
+                    max_length: 20,
+                    length: 28
+                })
+            ]),
+            validator.check("com.example.abcdefghijklmnop")
+        );
+
+        let schema = String {
+            value: Some("b".to_owned()),
+            known_values: Some(vec!["a".to_owned()]),
+            ..Default::default()
+        };
+        assert_eq!(Ok(()), schema.validator().check("b"));
+        assert!(!schema.validator().is_known("b"));
+        assert_eq!(
+            Err(vec![StringError::Const {
+                expected: "b".to_owned(),
+                value: "a".to_owned()
+            }]),
+            schema.validator().check("a")
+        );
+        assert!(String::default().validator().is_known("anything"));
+    }
+
+    #[test]
+    fn test_string_format() {
+        let cases = [
+            (StringFormat::AtIdentifier, "did:web:bsky.app", "did:web:"),
+            (StringFormat::AtIdentifier, "did:example:123", "did:example"),
+            (
+                StringFormat::AtUri,
+                "at://bsky.app/app.bsky.feed.post/3k2la3b",
+                "https://bsky.app",
+            ),
+            (
+                StringFormat::Cid,
+                "bafyreidfayvfuwqa7qlnopdjiqrxzs6blmoeu4rujcjtnci5beludirz2a",
+                "bafy",
+            ),
+            (StringFormat::Datetime, "2024-02-06T14:00:00Z", "2024-02-06"),
+            (
+                StringFormat::Did,
+                "did:plc:z72i7hdynmk6r22z27h6tvur",
+                "bsky.app",
+            ),
+            (StringFormat::Handle, "bsky.app", "did:web:bsky.app"),
+            (
+                StringFormat::Did,
+                "did:key:zQ3shunBKsXixLxKtC5qeSG9E4J5RkGN57im31pcTzbNQnm5w",
+                "did:Key:z",
+            ),
+            (StringFormat::Did, "did:example:123", "did:example:123:"),
+            (StringFormat::Did, "did:web:localhost%3A8080", "did:web:a b"),
+            (StringFormat::Nsid, "app.bsky.feed.post", "post"),
+            (StringFormat::Nsid, "com.example.fooBar", "a b.c"),
+            (StringFormat::Nsid, "com.example.fooBar", "x.y"),
+            (StringFormat::Nsid, "com.example.fooBar", "1.2.3"),
+            (
+                StringFormat::Nsid,
+                "com.example.fooBar",
+                "app.bsky.feed_post",
+            ),
+            (StringFormat::RecordKey, "self", ".."),
+            (StringFormat::Tid, "3kkqvzbva22jz", "self"),
+            (StringFormat::Language, "en-US", ""),
+            (StringFormat::Uri, "https://bsky.app/", "bsky app"),
+        ];
+
+        for (format, valid, invalid) in cases {
+            assert!(
+                format.matches(valid),
+                "{valid:?} should be a valid {format}"
+            );
+            assert!(
+                !format.matches(invalid),
+                "{invalid:?} should be an invalid {format}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_blob() {
+        let cid: Cid = "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"
+            .parse()
+            .unwrap();
+        let blob = |mime_type: &str, size| BlobRef::new(cid, mime_type, size);
+
+        let schema = Blob {
+            accept: Some(vec!["image/*".to_owned(), "video/mp4".to_owned()]),
+            max_size: Some(1_000_000),
+            ..Default::default()
+        };
+
+        for mime_type in [
+            "image/png",
+            "image/jpeg",
+            "IMAGE/WebP",
+            "video/mp4",
+            "video/mp4; codecs=avc1",
+        ] {
+            assert_eq!(
+                Ok(()),
+                schema.validate_value(&blob(mime_type, 1000)),
+                "{mime_type}"
+            );
+        }
+
+        for mime_type in ["video/webm", "text/plain", "image", "imagepng", ""] {
+            assert_eq!(
+                Err(BlobError::MimeType(mime_type.to_owned()).into()),
+                schema.validate_value(&blob(mime_type, 1000)),
+                "{mime_type}"
+            );
+        }
+
+        assert_eq!(
+            Err(ValidationError::Blob(BlobError::Size {
+                max_size: 1_000_000,
+                size: 1_000_001
+            })),
+            schema.validate_value(&blob("image/png", 1_000_001))
+        );
+
+        let any = Blob {
+            accept: Some(vec!["*/*".to_owned()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            Ok(()),
+            any.validate_value(&blob("application/octet-stream", usize::MAX))
+        );
+        assert_eq!(
+            Ok(()),
+            Blob::default().validate_value(&blob("text/plain", 0))
+        );
+    }
+}
@@ -0,0 +1,26 @@
+#!/bin/sh
+# Replace the app.bsky and com.atproto lexicons in test/lexicons with the
+# published ones from bluesky-social/atproto, at REF (default: main).
+#
+#     crates/lexicon/test/sync-lexicons.sh [REF]
+#
+# The commit they were copied from is recorded in test/lexicons/UPSTREAM.
+
+set -eu
+
+repo=https://github.com/bluesky-social/atproto.git
+ref=${1:-main}
+dest=$(cd "$(dirname "$0")" && pwd)/lexicons
+
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+
+git clone --quiet --depth 1 --branch "$ref" --filter=blob:none --sparse "$repo" "$tmp"
+git -C "$tmp" sparse-checkout set lexicons/app/bsky lexicons/com/atproto
+
+rm -rf "$dest/app/bsky" "$dest/com/atproto"
+mkdir -p "$dest/app" "$dest/com"
+cp -R "$tmp/lexicons/app/bsky" "$dest/app/bsky"
+cp -R "$tmp/lexicons/com/atproto" "$dest/com/atproto"
+
+git -C "$tmp" rev-parse HEAD > "$dest/UPSTREAM"
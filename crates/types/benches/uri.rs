@@ -0,0 +1,110 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use memchr::{memchr, memchr3_iter};
+
+use atprose_types::AtUri;
+
+const URIS: &[&str] = &[
+    "at://bsky.app",
+    "at://did:plc:ewvi7nxzyoun6zhxrhs64oiz/app.bsky.feed.post",
+    "at://did:plc:ewvi7nxzyoun6zhxrhs64oiz/app.bsky.feed.post/3kkqvzbva22jz",
+    "at://alice.bsky.social/app.bsky.actor.profile/self",
+    "at://did:web:example.com/com.example.record/a-custom~key:with.punctuation",
+];
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for uri in URIS {
+        group.bench_with_input(BenchmarkId::from_parameter(uri), uri, |b, uri| {
+            b.iter(|| black_box(uri).parse::<AtUri>().unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// Where the delimiters of an `at://` URI are, as found by a scanner: a
+/// copy of the parser's private `UriFormat`, so the scanners below can run
+/// the same state machine the parser does.
+#[derive(Debug, Copy, Clone, Default)]
+enum Format {
+    #[default]
+    Repository,
+    Collection(usize),
+    Record(usize, usize),
+}
+
+impl Format {
+    fn consume(self, (index, token): (usize, char)) -> Result<Self, usize> {
+        match (self, token, index) {
+            (_, _, i) if i == SCHEME.len() => Err(i),
+            (Format::Repository, '/', i) => Ok(Format::Collection(i)),
+            (Format::Collection(i), '/', j) => Ok(Format::Record(i, j)),
+            (_, _, offset) => Err(offset),
+        }
+    }
+
+    /// Where the collection and record delimiters are.
+    fn delimiters(self) -> (Option<usize>, Option<usize>) {
+        match self {
+            Format::Repository => (None, None),
+            Format::Collection(i) => (Some(i), None),
+            Format::Record(i, j) => (Some(i), Some(j)),
+        }
+    }
+}
+
+const SCHEME: &str = "at://";
+
+/// The scanner `AtUri::from_str` used before it switched to `memchr`.
+fn scan_match_indices(s: &str) -> Result<Format, usize> {
+    let mut format = Format::default();
+    for (i, c) in s.match_indices(&['@', '/', '?', '#']) {
+        if i >= SCHEME.len() {
+            let c = c.chars().next().expect("empty match");
+            format = format.consume((i, c))?;
+        }
+    }
+    Ok(format)
+}
+
+/// The scanner `AtUri::from_str` uses now.
+fn scan_memchr(s: &str) -> Result<Format, usize> {
+    let rest = &s.as_bytes()[SCHEME.len()..];
+    let at = memchr(b'@', rest).map(|i| SCHEME.len() + i);
+
+    let mut format = Format::default();
+    for i in memchr3_iter(b'/', b'?', b'#', rest).map(|i| SCHEME.len() + i) {
+        if at.is_some_and(|at| at < i) {
+            break;
+        }
+        format = format.consume((i, char::from(s.as_bytes()[i])))?;
+    }
+    if let Some(at) = at {
+        format = format.consume((at, '@'))?;
+    }
+    Ok(format)
+}
+
+/// Compare finding the delimiters in a URI with the previous
+/// `str::match_indices` scanner (the baseline) and the current `memchr` one.
+/// The rest of parsing is the same for both.
+fn scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scan");
+    for uri in URIS {
+        assert_eq!(
+            scan_match_indices(uri).map(Format::delimiters),
+            scan_memchr(uri).map(Format::delimiters),
+            "{uri}"
+        );
+
+        group.bench_with_input(BenchmarkId::new("match_indices", uri), uri, |b, uri| {
+            b.iter(|| scan_match_indices(black_box(uri)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("memchr", uri), uri, |b, uri| {
+            b.iter(|| scan_memchr(black_box(uri)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, scan);
+criterion_main!(benches);
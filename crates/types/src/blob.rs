@@ -0,0 +1,90 @@
+use alloc::string::String;
+
+use crate::Cid;
+
+/// A reference to a [blob][] stored alongside a repository: its content hash,
+/// MIME type, and size in bytes.
+///
+/// With the `serde` feature, this (de)serializes to the JSON form used in
+/// records:
+///
+/// ```json
+/// {
+///   "$type": "blob",
+///   "ref": { "$link": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy" },
+///   "mimeType": "image/png",
+///   "size": 12345
+/// }
+/// ```
+///
+/// [blob]: https://atproto.com/specs/data-model#blob-type
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(tag = "$type", rename = "blob", rename_all = "camelCase")
+)]
+pub struct BlobRef {
+    #[cfg_attr(feature = "serde", serde(rename = "ref", with = "link"))]
+    pub cid: Cid,
+    pub mime_type: String,
+    pub size: usize,
+}
+
+impl BlobRef {
+    pub fn new(cid: Cid, mime_type: impl Into<String>, size: usize) -> Self {
+        Self {
+            cid,
+            mime_type: mime_type.into(),
+            size,
+        }
+    }
+}
+
+/// (De)serialize a [`Cid`] as a `{"$link": "..."}` object.
+#[cfg(feature = "serde")]
+mod link {
+    use alloc::string::{String, ToString};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::Cid;
+
+    #[derive(Deserialize, Serialize)]
+    struct Link {
+        #[serde(rename = "$link")]
+        link: String,
+    }
+
+    pub fn serialize<S: Serializer>(cid: &Cid, serializer: S) -> Result<S::Ok, S::Error> {
+        let link = Link {
+            link: cid.to_string(),
+        };
+        link.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Cid, D::Error> {
+        let Link { link } = Link::deserialize(deserializer)?;
+        Cid::try_from(link.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use super::BlobRef;
+
+    #[test]
+    fn test_serde() {
+        let json = r#"{"$type":"blob","ref":{"$link":"bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"},"mimeType":"image/png","size":12345}"#;
+
+        let blob: BlobRef = serde_json::from_str(json).expect("deserialize blob");
+        assert_eq!(
+            "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy",
+            blob.cid.to_string()
+        );
+        assert_eq!("image/png", blob.mime_type);
+        assert_eq!(12345, blob.size);
+
+        assert_eq!(json, serde_json::to_string(&blob).expect("serialize blob"));
+    }
+}
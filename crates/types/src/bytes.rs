@@ -0,0 +1,46 @@
+//! Helpers for (de)serializing identifiers as raw bytes.
+
+use core::fmt;
+
+use serde::de::{Deserializer, Error, SeqAccess, Visitor};
+
+/// Deserialize exactly `N` bytes, from either a byte string or a sequence of
+/// integers (which is how formats without a byte string type, like JSON,
+/// serialize bytes).
+pub(crate) fn deserialize_array<'de, D, const N: usize>(
+    deserializer: D,
+) -> Result<[u8; N], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct ArrayVisitor<const N: usize>;
+
+    impl<'de, const N: usize> Visitor<'de> for ArrayVisitor<N> {
+        type Value = [u8; N];
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{N} bytes")
+        }
+
+        fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            v.try_into().map_err(|_| E::invalid_length(v.len(), &self))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = [0u8; N];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = seq
+                    .next_element()?
+                    .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+            }
+
+            if seq.next_element::<u8>()?.is_some() {
+                return Err(A::Error::invalid_length(N + 1, &self));
+            }
+
+            Ok(bytes)
+        }
+    }
+
+    deserializer.deserialize_bytes(ArrayVisitor::<N>)
+}
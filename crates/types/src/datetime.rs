@@ -0,0 +1,229 @@
+/// Check that `value` is a valid Lexicon [datetime][].
+///
+/// Lexicon datetimes are the intersection of RFC 3339 and ISO 8601: a date
+/// and time with seconds (`YYYY-MM-DDTHH:MM:SS`), separated by an uppercase
+/// `T`, optionally followed by fractional seconds, and always ending with a
+/// timezone: either `Z` or a numeric `+HH:MM`/`-HH:MM` offset (but not
+/// `-00:00`).
+///
+/// With [`DatetimePolicy::Lenient`], a datetime with no timezone at all is
+/// also accepted; see [`DatetimePolicy`].
+///
+/// This does not depend on the `chrono` feature, so it can validate
+/// [`DateTime`][crate::DateTime] values in any configuration.
+///
+/// ```
+/// use atprose_types::{validate_datetime, DatetimePolicy::*};
+///
+/// assert!(validate_datetime("2024-02-06T14:00:00Z", Strict).is_ok());
+/// assert!(validate_datetime("2024-02-06T14:00:00.123+09:00", Strict).is_ok());
+/// assert!(validate_datetime("2024-02-06T14:00:00", Strict).is_err());
+/// assert!(validate_datetime("2024-02-06T14:00:00", Lenient).is_ok());
+/// ```
+///
+/// [datetime]: https://atproto.com/specs/lexicon#datetime
+pub fn validate_datetime(value: &str, policy: DatetimePolicy) -> Result<(), InvalidDatetime> {
+    let value = value.as_bytes();
+    if value.len() < 19 {
+        return Err(InvalidDatetime::Syntax);
+    }
+
+    let (datetime, rest) = value.split_at(19);
+    validate_date(&datetime[..10])?;
+
+    if datetime[10] != b'T' {
+        return Err(InvalidDatetime::Syntax);
+    }
+
+    validate_time(&datetime[11..])?;
+
+    let rest = match rest.strip_prefix(b".") {
+        Some(rest) => {
+            let digits = rest.iter().take_while(|c| c.is_ascii_digit()).count();
+            if digits == 0 {
+                return Err(InvalidDatetime::Syntax);
+            }
+
+            &rest[digits..]
+        }
+        None => rest,
+    };
+
+    match (rest, policy) {
+        (b"", DatetimePolicy::Lenient) => Ok(()),
+        (tz, _) => validate_timezone(tz),
+    }
+}
+
+/// How strictly [`validate_datetime`] treats a missing timezone.
+///
+/// The Lexicon spec requires a timezone, but older records (including some
+/// written by the Bluesky app) have datetimes without one. Values with a
+/// malformed timezone are rejected either way.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum DatetimePolicy {
+    /// Require a `Z` or numeric offset, as the Lexicon spec does.
+    #[default]
+    Strict,
+    /// Also accept datetimes with no timezone.
+    Lenient,
+}
+
+fn validate_date(date: &[u8]) -> Result<(), InvalidDatetime> {
+    if date[4] != b'-' || date[7] != b'-' {
+        return Err(InvalidDatetime::Syntax);
+    }
+
+    let year = number(&date[..4])?;
+    let month = number(&date[5..7])?;
+    let day = number(&date[8..10])?;
+
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => return Err(InvalidDatetime::Date),
+    };
+
+    if day == 0 || day > days {
+        return Err(InvalidDatetime::Date);
+    }
+
+    Ok(())
+}
+
+fn validate_time(time: &[u8]) -> Result<(), InvalidDatetime> {
+    if time[2] != b':' || time[5] != b':' {
+        return Err(InvalidDatetime::Syntax);
+    }
+
+    let hour = number(&time[..2])?;
+    let minute = number(&time[3..5])?;
+    let second = number(&time[6..8])?;
+
+    if hour > 23 || minute > 59 || second > 59 {
+        return Err(InvalidDatetime::Time);
+    }
+
+    Ok(())
+}
+
+fn validate_timezone(tz: &[u8]) -> Result<(), InvalidDatetime> {
+    match tz {
+        b"" => Err(InvalidDatetime::Timezone),
+        b"Z" => Ok(()),
+        b"-00:00" => Err(InvalidDatetime::Timezone),
+        [b'+' | b'-', offset @ ..] if offset.len() == 5 && offset[2] == b':' => {
+            let hours = number(&offset[..2])?;
+            let minutes = number(&offset[3..])?;
+
+            if hours > 23 || minutes > 59 {
+                Err(InvalidDatetime::Timezone)
+            } else {
+                Ok(())
+            }
+        }
+        _ => Err(InvalidDatetime::Syntax),
+    }
+}
+
+fn number(digits: &[u8]) -> Result<u32, InvalidDatetime> {
+    digits.iter().try_fold(0, |n, c| match c {
+        b'0'..=b'9' => Ok(n * 10 + u32::from(c - b'0')),
+        _ => Err(InvalidDatetime::Syntax),
+    })
+}
+
+#[derive(thiserror::Error, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum InvalidDatetime {
+    #[error("malformed datetime")]
+    Syntax,
+    #[error("invalid date in datetime")]
+    Date,
+    #[error("invalid time in datetime")]
+    Time,
+    #[error("missing or invalid timezone in datetime")]
+    Timezone,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{validate_datetime, DatetimePolicy, InvalidDatetime};
+
+    #[test]
+    fn test_validate_datetime() {
+        use DatetimePolicy::*;
+        use InvalidDatetime::*;
+
+        let valid = [
+            "1985-04-12T23:20:50.123Z",
+            "1985-04-12T23:20:50.123456Z",
+            "1985-04-12T23:20:50.120Z",
+            "1985-04-12T23:20:50Z",
+            "1985-04-12T23:20:50.0Z",
+            "1985-04-12T23:20:50.123+00:00",
+            "1985-04-12T23:20:50.123-07:00",
+            "2024-02-29T00:00:00Z",
+            "0001-01-01T00:00:00.000Z",
+        ];
+
+        let invalid = [
+            ("", Syntax),
+            ("1985-04-12", Syntax),
+            ("1985-04-12T23:20Z", Syntax),
+            ("1985-04-12t23:20:50.123Z", Syntax),
+            ("1985-04-12 23:20:50.123Z", Syntax),
+            ("1985-04-12T23:20:50.123z", Syntax),
+            ("1985-04-12T23:20:50.Z", Syntax),
+            ("1985-04-12T23:20:50.123+0000", Syntax),
+            ("85-04-12T23:20:50.123Z", Syntax),
+            ("1985-4-12T23:20:50.123Z", Syntax),
+            ("1985-04-12T23:20:50.123", Timezone),
+            ("1985-04-12T23:20:50.123-00:00", Timezone),
+            ("1985-04-12T23:20:50.123+24:00", Timezone),
+            ("1985-00-12T23:20:50.123Z", Date),
+            ("1985-13-12T23:20:50.123Z", Date),
+            ("1985-04-31T23:20:50.123Z", Date),
+            ("2023-02-29T23:20:50.123Z", Date),
+            ("1985-04-12T24:20:50.123Z", Time),
+            ("1985-04-12T23:60:50.123Z", Time),
+            ("1985-04-12T23:20:61.123Z", Time),
+        ];
+
+        for value in valid {
+            assert_eq!(Ok(()), validate_datetime(value, Strict), "{value}");
+            assert_eq!(Ok(()), validate_datetime(value, Lenient), "{value}");
+        }
+
+        for (value, expected) in invalid {
+            assert_eq!(Err(expected), validate_datetime(value, Strict), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_datetime_policy() {
+        use DatetimePolicy::*;
+
+        assert_eq!(DatetimePolicy::default(), Strict);
+
+        for value in ["1985-04-12T23:20:50", "1985-04-12T23:20:50.123"] {
+            assert_eq!(
+                Err(InvalidDatetime::Timezone),
+                validate_datetime(value, Strict)
+            );
+            assert_eq!(Ok(()), validate_datetime(value, Lenient), "{value}");
+        }
+
+        let invalid = [
+            ("1985-04-12T23:20:50.123-00:00", InvalidDatetime::Timezone),
+            ("1985-04-12T23:20:50.123+0000", InvalidDatetime::Syntax),
+            ("1985-04-12T23:20:50.", InvalidDatetime::Syntax),
+            ("1985-04-31T23:20:50", InvalidDatetime::Date),
+        ];
+
+        for (value, expected) in invalid {
+            assert_eq!(Err(expected), validate_datetime(value, Lenient), "{value}");
+        }
+    }
+}
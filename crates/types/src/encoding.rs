@@ -1,50 +1,242 @@
-use fast32::make_base32_alpha as alphabet;
-pub use fast32::DecodeError;
+use alloc::string::String;
 
 #[cfg(feature = "plc")]
-alphabet!(BASE32, DECODE_BASE32, b"abcdefghijklmnopqrstuvwxyz234567");
-#[cfg(feature = "rkey")]
-alphabet!(
-    BASE32_SORTABLE,
-    DECODE_BASE32_SORTABLE,
-    b"234567abcdefghijklmnopqrstuvwxyz"
-);
+const BASE32: Alphabet = Alphabet::new(b"abcdefghijklmnopqrstuvwxyz234567");
+#[cfg(feature = "tid")]
+const BASE32_SORTABLE: Alphabet = Alphabet::new(b"234567abcdefghijklmnopqrstuvwxyz");
+
+/// A base32 alphabet, with a table to decode it.
+struct Alphabet {
+    encode: &'static [u8; 32],
+    decode: [u8; 256],
+}
+
+impl Alphabet {
+    const INVALID: u8 = 0xff;
+
+    const fn new(encode: &'static [u8; 32]) -> Self {
+        let mut decode = [Self::INVALID; 256];
+
+        let mut i = 0;
+        while i < encode.len() {
+            decode[encode[i] as usize] = i as u8;
+            i += 1;
+        }
+
+        Self { encode, decode }
+    }
+
+    fn char(&self, value: u64) -> char {
+        char::from(self.encode[(value & 0x1f) as usize])
+    }
+
+    /// Decode the character at byte `index` of `input`.
+    fn value(&self, input: &str, index: usize) -> Result<u64, DecodeError> {
+        match self.decode[usize::from(input.as_bytes()[index])] {
+            Self::INVALID => Err(DecodeError::char_at(input, index)),
+            value => Ok(u64::from(value)),
+        }
+    }
+}
 
 #[cfg(feature = "plc")]
 pub fn encode(data: impl AsRef<[u8]>) -> String {
-    BASE32.encode(data.as_ref())
+    let mut encoded = String::new();
+    encode_into(data, &mut encoded);
+    encoded
 }
 
-#[allow(dead_code)]
+/// Append the encoding of `data` to `encoded`.
 #[cfg(feature = "plc")]
-pub fn decode(data: impl AsRef<str>) -> Result<Vec<u8>, DecodeError> {
-    BASE32.decode_str(data)
+pub fn encode_into(data: impl AsRef<[u8]>, encoded: &mut String) {
+    let data = data.as_ref();
+    encoded.reserve((data.len() * 8).div_ceil(5));
+
+    let (mut buffer, mut bits) = (0u64, 0);
+    for &byte in data {
+        buffer = (buffer << 8) | u64::from(byte);
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32.char(buffer >> bits));
+        }
+    }
+
+    if bits > 0 {
+        encoded.push(BASE32.char(buffer << (5 - bits)));
+    }
 }
 
+/// Decode `data` into exactly `N` bytes.
+///
+/// `data` must be exactly as long as the encoding of `N` bytes, and any bits
+/// left over in its last character must be zero.
 #[cfg(feature = "plc")]
 pub fn decode_into<const N: usize>(
     data: impl AsRef<str>,
     dest: &mut [u8; N],
 ) -> Result<(), DecodeError> {
-    let data = data.as_ref().as_bytes();
-    if BASE32.capacity_decode(data) < N {
+    let data = data.as_ref();
+    if data.len() != (N * 8).div_ceil(5) {
         return Err(DecodeError::InvalidLength { length: data.len() });
     }
 
-    // fast32 can only decode into a Vec, so we give it a phony one
-    let mut dest = unsafe { Vec::from_raw_parts(dest.as_mut_ptr(), 0, N) };
-    BASE32.decode_into(data, &mut dest)?;
-    std::mem::forget(dest);
+    let (mut buffer, mut bits, mut written) = (0u64, 0, 0);
+    for index in 0..data.len() {
+        buffer = (buffer << 5) | BASE32.value(data, index)?;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            dest[written] = (buffer >> bits) as u8;
+            written += 1;
+        }
+    }
+
+    if buffer & ((1 << bits) - 1) != 0 {
+        return Err(DecodeError::char_at(data, data.len() - 1));
+    }
 
     Ok(())
 }
 
-#[cfg(feature = "rkey")]
+/// Encode `value` as 13 characters of sortable base32.
+#[cfg(feature = "tid")]
 pub fn encode_u64(value: u64) -> String {
-    BASE32_SORTABLE.encode_u64(value)
+    let mut encoded = String::new();
+    encode_u64_into(value, &mut encoded);
+    encoded
+}
+
+/// Append the 13-character sortable base32 encoding of `value` to `encoded`.
+#[cfg(feature = "tid")]
+pub fn encode_u64_into(value: u64, encoded: &mut String) {
+    encoded.extend(
+        (0..13)
+            .rev()
+            .map(|i| BASE32_SORTABLE.char(value >> (i * 5))),
+    );
 }
 
-#[cfg(feature = "rkey")]
+/// Decode at most 13 characters of sortable base32 into an integer.
+#[cfg(feature = "tid")]
 pub fn decode_u64(data: impl AsRef<str>) -> Result<u64, DecodeError> {
-    BASE32_SORTABLE.decode_u64_str(data)
+    let data = data.as_ref();
+    if data.len() > 13 {
+        return Err(DecodeError::InvalidLength { length: data.len() });
+    }
+
+    (0..data.len()).try_fold(0u64, |value, index| {
+        let digit = BASE32_SORTABLE.value(data, index)?;
+        value
+            .checked_mul(32)
+            .map(|value| value | digit)
+            .ok_or_else(|| DecodeError::char_at(data, 0))
+    })
+}
+
+/// An error decoding a base32-encoded identifier.
+#[derive(thiserror::Error, PartialEq, Eq, Clone, Debug)]
+pub enum DecodeError {
+    #[error("invalid character {char:?} at position {index}")]
+    InvalidChar { char: char, index: usize },
+    #[error("invalid length of {length}")]
+    InvalidLength { length: usize },
+}
+
+impl DecodeError {
+    fn char_at(input: &str, index: usize) -> Self {
+        // `index` may fall inside a multi-byte character; report the whole
+        // character it belongs to.
+        let start = (0..=index)
+            .rev()
+            .find(|&i| input.is_char_boundary(i))
+            .unwrap_or(0);
+        let char = input[start..].chars().next().unwrap_or_default();
+
+        Self::InvalidChar { char, index: start }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DecodeError;
+
+    #[cfg(feature = "plc")]
+    #[test]
+    fn test_base32() {
+        use super::{decode_into, encode};
+
+        let data = *b"hello, world!!!";
+        let encoded = encode(data);
+        assert_eq!("nbswy3dpfqqho33snrsccijb", encoded);
+
+        let mut decoded = [0; 15];
+        decode_into(&encoded, &mut decoded).expect("decode");
+        assert_eq!(data, decoded);
+
+        let mut short = [0; 2];
+        assert_eq!(Ok(()), decode_into("nbsq", &mut short));
+        assert_eq!(*b"he", short);
+        assert_eq!(
+            Err(DecodeError::InvalidChar {
+                char: 'r',
+                index: 3
+            }),
+            decode_into("nbsr", &mut short)
+        );
+
+        let cases = [
+            ("", DecodeError::InvalidLength { length: 0 }),
+            (
+                "nbswy3dpfqqho33snrsccijbaa",
+                DecodeError::InvalidLength { length: 26 },
+            ),
+            (
+                "nbswy3dpfqqho33snrsccij1",
+                DecodeError::InvalidChar {
+                    char: '1',
+                    index: 23,
+                },
+            ),
+            (
+                "nbswy3dpfqqho33snrsccié",
+                DecodeError::InvalidChar {
+                    char: 'é',
+                    index: 22,
+                },
+            ),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(Err(expected), decode_into(value, &mut decoded), "{value}");
+        }
+    }
+
+    #[cfg(feature = "tid")]
+    #[test]
+    fn test_base32_sortable() {
+        use super::{decode_u64, encode_u64};
+
+        for value in [0, 1, 31, 32, 0x1842_dbf9_f660_01ff, u64::MAX] {
+            let encoded = encode_u64(value);
+            assert_eq!(13, encoded.len());
+            assert_eq!(Ok(value), decode_u64(&encoded), "{encoded}");
+        }
+
+        assert_eq!("2222222222223", encode_u64(1));
+        assert_eq!(Ok(1), decode_u64("3"));
+        assert_eq!(
+            Err(DecodeError::InvalidChar {
+                char: 'k',
+                index: 0
+            }),
+            decode_u64("kzzzzzzzzzzzz")
+        );
+        assert_eq!(
+            Err(DecodeError::InvalidLength { length: 14 }),
+            decode_u64("22222222222222")
+        );
+    }
 }
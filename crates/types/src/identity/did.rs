@@ -1,7 +1,18 @@
-use std::{fmt, str::FromStr};
+#[cfg(not(feature = "plc"))]
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+#[cfg(feature = "serde")]
+use alloc::string::ToString;
+use core::{fmt, str::FromStr};
+
+use percent_encoding::percent_decode_str;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use super::handle::{Handle, InvalidHandle};
-use crate::encoding::{decode_into, encode, DecodeError};
+#[cfg(feature = "plc")]
+use crate::encoding::{decode_into, encode, encode_into, DecodeError};
 
 /// A valid [AT protocol DID][did]: either a `did:plc` [identifier][PlcId], or a
 /// `did:web` [handle][Handle].
@@ -20,6 +31,61 @@ pub enum Did {
     Web(Handle),
 }
 
+impl Did {
+    /// Create a `did:web` DID for the host `host`.
+    ///
+    /// `host` may be percent-encoded, as it is in the text of a `did:web`,
+    /// and must decode to a valid [handle][Handle]. AT protocol doesn't allow
+    /// a port in a `did:web`, so an encoded `:` is rejected, as is an
+    /// encoding of bytes which aren't UTF-8.
+    ///
+    /// ```
+    /// use atprose_types::Did;
+    ///
+    /// let did = Did::web("bsky.app").unwrap();
+    /// assert_eq!(did.to_string(), "did:web:bsky.app");
+    /// assert!(Did::web("example.com%3A3000").is_err());
+    /// ```
+    pub fn web(host: impl AsRef<str>) -> Result<Self, InvalidDid> {
+        let host = percent_decode_str(host.as_ref())
+            .decode_utf8()
+            .map_err(InvalidDid::Encoding)?;
+        let handle = Handle::parse(&*host)?;
+
+        Ok(Self::Web(handle))
+    }
+
+    /// Append this DID to `buf`, as it is [displayed][fmt::Display].
+    ///
+    /// ```
+    /// use atprose_types::Did;
+    ///
+    /// # fn main() -> Result<(), atprose_types::InvalidDid> {
+    /// let did: Did = "did:plc:z72i7hdynmk6r22z27h6tvur".parse()?;
+    ///
+    /// let mut uri = String::from("at://");
+    /// did.fmt_into(&mut uri);
+    /// assert_eq!("at://did:plc:z72i7hdynmk6r22z27h6tvur", uri);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fmt_into(&self, buf: &mut String) {
+        match self {
+            Did::Plc(id) => {
+                buf.push_str("did:plc:");
+                #[cfg(feature = "plc")]
+                id.fmt_into(buf);
+                #[cfg(not(feature = "plc"))]
+                buf.push_str(id);
+            }
+            Did::Web(handle) => {
+                buf.push_str("did:web:");
+                buf.push_str(handle.as_ref());
+            }
+        }
+    }
+}
+
 #[cfg(feature = "plc")]
 #[cfg_attr(docsrs, doc(cfg(feature = "plc")))]
 impl From<PlcId> for Did {
@@ -43,8 +109,11 @@ impl FromStr for Did {
         let (scheme, id) = did.split_once(':').ok_or(InvalidDid::Scheme)?;
 
         let parsed = match scheme {
+            #[cfg(feature = "plc")]
             "plc" => Self::Plc(id.parse().map_err(InvalidDid::from)?),
-            "web" => Self::Web(id.parse().map_err(InvalidDid::from)?),
+            #[cfg(not(feature = "plc"))]
+            "plc" => Self::Plc(id.to_owned()),
+            "web" => Self::web(id)?,
             _ => return Err(InvalidDid::Scheme),
         };
 
@@ -52,27 +121,86 @@ impl FromStr for Did {
     }
 }
 
+impl TryFrom<&str> for Did {
+    type Error = InvalidDid;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for Did {
+    type Error = InvalidDid;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 impl fmt::Display for Did {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Did::Plc(id) => write!(f, "did:plc:{id}"),
+            // Handles never contain characters which need percent-encoding.
             Did::Web(handle) => write!(f, "did:web:{handle}"),
         }
     }
 }
 
+/// DIDs compare equal to the string they are written as, so a `did:plc`
+/// compares equal only to its lowercase form.
+impl PartialEq<str> for Did {
+    fn eq(&self, other: &str) -> bool {
+        crate::display_eq(self, other)
+    }
+}
+
+impl PartialEq<&str> for Did {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for Did {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for Did {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 #[derive(thiserror::Error, PartialEq, Clone, Debug)]
 pub enum InvalidDid {
     #[error("missing did: prefix")]
     Prefix,
     #[error("unknown did scheme")]
     Scheme,
+    #[cfg(feature = "plc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "plc")))]
     #[error("invalid did:plc: {0}")]
     Plc(DecodeError),
     #[error("invalid did:web: {0}")]
     Web(#[from] InvalidHandle),
+    #[error("invalid percent-encoding in did:web")]
+    Encoding(#[source] core::str::Utf8Error),
 }
 
+#[cfg(feature = "plc")]
 impl From<DecodeError> for InvalidDid {
     fn from(value: DecodeError) -> Self {
         Self::Plc(value)
@@ -88,6 +216,7 @@ impl From<DecodeError> for InvalidDid {
 #[repr(transparent)]
 pub struct PlcId([u8; Self::SIZE]);
 
+#[cfg(feature = "plc")]
 impl PlcId {
     pub const SIZE: usize = 15;
 
@@ -96,18 +225,38 @@ impl PlcId {
         Self(id)
     }
 
+    /// Decode a `did:plc` identifier (without the `did:plc:` prefix).
+    ///
+    /// The canonical form is lowercase, but uppercase letters are accepted
+    /// and decoded as their lowercase equivalents, so the result always
+    /// [encodes][Self::encode] in canonical form. This only applies to
+    /// `did:plc`; the handle in a `did:web` is kept as given, and is compared
+    /// as such (see [`Handle`]).
     pub fn decode(input: impl AsRef<str>) -> Result<Self, DecodeError> {
+        let input = input.as_ref();
         let mut buffer = [0u8; Self::SIZE];
-        decode_into(input, &mut buffer)?;
+
+        if input.bytes().any(|c| c.is_ascii_uppercase()) {
+            decode_into(input.to_ascii_lowercase(), &mut buffer)?;
+        } else {
+            decode_into(input, &mut buffer)?;
+        }
 
         Ok(Self(buffer))
     }
 
+    /// Encode this identifier in its canonical lowercase form.
     pub fn encode(&self) -> String {
         encode(self.0)
     }
+
+    /// Append this identifier's [canonical form][Self::encode] to `buf`.
+    pub fn fmt_into(&self, buf: &mut String) {
+        encode_into(self.0, buf);
+    }
 }
 
+#[cfg(feature = "plc")]
 impl FromStr for PlcId {
     type Err = DecodeError;
 
@@ -116,6 +265,7 @@ impl FromStr for PlcId {
     }
 }
 
+#[cfg(feature = "plc")]
 impl fmt::Display for PlcId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let id = self.encode();
@@ -123,6 +273,7 @@ impl fmt::Display for PlcId {
     }
 }
 
+#[cfg(feature = "plc")]
 impl fmt::Debug for PlcId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let id = self.encode();
@@ -130,19 +281,81 @@ impl fmt::Debug for PlcId {
     }
 }
 
+#[cfg(all(feature = "plc", feature = "serde"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "plc", feature = "serde"))))]
+impl<'de> Deserialize<'de> for PlcId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::decode(s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(all(feature = "plc", feature = "serde"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "plc", feature = "serde"))))]
+impl Serialize for PlcId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(feature = "plc")]
 impl AsRef<[u8]> for PlcId {
     fn as_ref(&self) -> &[u8] {
         &self.0[..]
     }
 }
 
-#[cfg(not(feature = "language"))]
-pub type PlcId = string;
+/// (De)serialize a [`PlcId`] as its 15 raw bytes.
+///
+/// Use with `#[serde(with = "atprose_types::plc::bytes")]`.
+#[cfg(all(feature = "plc", feature = "serde"))]
+pub mod bytes {
+    use serde::{Deserializer, Serializer};
+
+    use super::PlcId;
+
+    pub fn serialize<S: Serializer>(id: &PlcId, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(id.as_ref())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PlcId, D::Error> {
+        crate::bytes::deserialize_array(deserializer).map(PlcId::new)
+    }
+}
+
+/// (De)serialize a [`PlcId`] in its base32 string form, as its [`Serialize`]
+/// and [`Deserialize`] implementations do.
+///
+/// Use with `#[serde(with = "atprose_types::plc::string")]`, to be explicit
+/// alongside [`bytes`] fields.
+#[cfg(all(feature = "plc", feature = "serde"))]
+pub mod string {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::PlcId;
+
+    pub fn serialize<S: Serializer>(id: &PlcId, serializer: S) -> Result<S::Ok, S::Error> {
+        id.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PlcId, D::Error> {
+        PlcId::deserialize(deserializer)
+    }
+}
+
+#[cfg(not(feature = "plc"))]
+pub type PlcId = String;
 
 #[cfg(all(test, feature = "plc"))]
 mod test {
-    use super::{Did, PlcId};
-    use crate::Handle;
+    use super::{Did, InvalidDid, PlcId};
+    use crate::{Handle, InvalidHandle};
 
     #[test]
     fn test_parse_did() {
@@ -151,15 +364,69 @@ mod test {
                 0x25, 0xaa, 0x8f, 0xb6, 0xf9, 0xc3, 0xa8, 0xdf, 0x64, 0xf7, 0x89, 0xe5, 0xee, 0x39,
                 0x19
             ])),
-            "did:plc:ewvi7nxzyoun6zhxrhs64oiz".parse().unwrap()
+            "did:plc:ewvi7nxzyoun6zhxrhs64oiz".parse::<Did>().unwrap()
         );
 
         assert_eq!(
             Did::Web(Handle::new("bsky.app")),
-            "did:web:bsky.app".parse().unwrap()
+            "did:web:bsky.app".parse::<Did>().unwrap()
         );
     }
 
+    #[test]
+    fn test_eq_str() {
+        let did: Did = "did:plc:ewvi7nxzyoun6zhxrhs64oiz".parse().unwrap();
+        assert!(did == "did:plc:ewvi7nxzyoun6zhxrhs64oiz");
+        assert!(did != "did:plc:EWVI7NXZYOUN6ZHXRHS64OIZ");
+        assert!(did != "did:plc:ewvi7nxzyoun6zhxrhs64oi");
+        assert!(did != "did:plc:ewvi7nxzyoun6zhxrhs64oizz");
+
+        let did = Did::Web(Handle::new("bsky.app"));
+        assert_eq!(did, "did:web:bsky.app");
+        assert_ne!(did, "did:web:bsky.ap");
+    }
+
+    #[test]
+    fn test_fmt_into() {
+        for value in ["did:plc:ewvi7nxzyoun6zhxrhs64oiz", "did:web:bsky.app"] {
+            let mut buf = "at://".to_owned();
+            value.parse::<Did>().unwrap().fmt_into(&mut buf);
+            assert_eq!(format!("at://{value}"), buf);
+        }
+    }
+
+    #[test]
+    fn test_web() {
+        let did = Did::web("bsky.app").unwrap();
+        assert_eq!(Did::Web(Handle::new("bsky.app")), did);
+        assert_eq!("did:web:bsky.app", did.to_string());
+        assert_eq!(Ok(did.clone()), did.to_string().parse());
+
+        assert_eq!(Ok(did.clone()), Did::web("bsky%2Eapp"));
+        assert_eq!(Ok(did), "did:web:bsky%2eapp".parse());
+
+        let cases = [
+            ("", InvalidHandle::Empty),
+            ("example.com%3A3000", InvalidHandle::Character(':')),
+            ("example.com:3000", InvalidHandle::Character(':')),
+        ];
+
+        for (host, expected) in cases {
+            assert_eq!(Err(InvalidDid::Web(expected)), Did::web(host), "{host}");
+        }
+
+        for host in ["bsky%FF.app", "bsky%C3.app"] {
+            assert!(
+                matches!(Did::web(host), Err(InvalidDid::Encoding(_))),
+                "{host}"
+            );
+        }
+        assert!(matches!(
+            "did:web:bsky%FF.app".parse::<Did>(),
+            Err(InvalidDid::Encoding(_))
+        ));
+    }
+
     #[test]
     fn test_decode_plc_id() {
         let cases = [
@@ -189,4 +456,49 @@ mod test {
         assert_eq!(id.to_string(), "j67mwmangcbxch7knfm7jo2b");
         assert_eq!(format!("{id:?}"), "PlcId(\"j67mwmangcbxch7knfm7jo2b\")");
     }
+
+    #[cfg(all(feature = "plc", feature = "serde"))]
+    #[test]
+    fn test_plc_id_serde_bytes() {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Entry {
+            #[serde(with = "crate::plc::bytes")]
+            bytes: PlcId,
+            #[serde(with = "crate::plc::string")]
+            string: PlcId,
+        }
+
+        let id: PlcId = "ewvi7nxzyoun6zhxrhs64oiz".parse().unwrap();
+        let entry = Entry {
+            bytes: id.clone(),
+            string: id.clone(),
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        let bytes: Vec<_> = id.as_ref().iter().map(u8::to_string).collect();
+        assert_eq!(
+            format!(
+                r#"{{"bytes":[{}],"string":"ewvi7nxzyoun6zhxrhs64oiz"}}"#,
+                bytes.join(",")
+            ),
+            json
+        );
+        assert_eq!(entry, serde_json::from_str(&json).unwrap());
+
+        let short = r#"{"bytes":[1,2,3],"string":"ewvi7nxzyoun6zhxrhs64oiz"}"#;
+        assert!(serde_json::from_str::<Entry>(short).is_err());
+    }
+
+    #[test]
+    fn test_plc_case() {
+        let id: PlcId = "ewvi7nxzyoun6zhxrhs64oiz".parse().unwrap();
+        assert_eq!(Ok(id.clone()), "EWVI7NXZYOUN6ZHXRHS64OIZ".parse());
+        assert_eq!(Ok(id.clone()), "ewvi7NXZyoun6zhxrhs64oiz".parse());
+        assert_eq!("ewvi7nxzyoun6zhxrhs64oiz", id.encode());
+
+        let did: Did = "did:plc:EWVI7NXZYOUN6ZHXRHS64OIZ".parse().unwrap();
+        assert_eq!(Did::Plc(id), did);
+        assert_eq!("did:plc:ewvi7nxzyoun6zhxrhs64oiz", did.to_string());
+    }
 }
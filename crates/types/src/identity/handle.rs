@@ -1,6 +1,11 @@
-use std::fmt::Display;
-use std::ops::Deref;
-use std::str::FromStr;
+use alloc::{string::String, vec::Vec};
+use core::borrow::Borrow;
+use core::fmt::Display;
+use core::ops::Deref;
+use core::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// An [AT protocol handle][handle].
 ///
@@ -30,12 +35,112 @@ where
             Err(err) => Err(err),
         }
     }
+
+    /// Parse a handle as a user might type or paste it.
+    ///
+    /// Surrounding whitespace is trimmed, and then a single leading `@` (as in
+    /// `@alice.bsky.social`) is removed. The remainder is validated exactly
+    /// like [`parse`][Self::parse]; no other normalization (such as case
+    /// folding) is applied.
+    pub fn parse_lenient<'a>(value: &'a str) -> Result<Self, InvalidHandle>
+    where
+        T: From<&'a str>,
+    {
+        Self::parse(lenient(value))
+    }
+
+    /// This handle in lowercase, its canonical form.
+    ///
+    /// Handles are case-insensitive, so two handles are the same if their
+    /// lowercase forms are equal.
+    pub fn to_lowercase(&self) -> Handle {
+        // Lowercasing a valid handle leaves it valid.
+        Handle(self.0.to_ascii_lowercase())
+    }
+
+    /// The last label of this handle, like `social` in `alice.bsky.social`.
+    pub fn tld(&self) -> &str {
+        let (_, tld) = self.0.rsplit_once('.').expect("handle has a dot");
+        tld
+    }
+
+    /// The last two labels of this handle, like `bsky.social` in
+    /// `alice.bsky.social`.
+    ///
+    /// This doesn't consult the [public suffix list][psl], so it isn't always
+    /// the registrable domain: the domain of `alice.example.co.uk` is
+    /// `co.uk`.
+    ///
+    /// [psl]: https://publicsuffix.org/
+    pub fn domain(&self) -> &str {
+        &self.0[self.domain_start()..]
+    }
+
+    /// The labels of this handle before its [`domain`][Self::domain], like
+    /// `alice` in `alice.bsky.social`, or `None` if it has only two labels.
+    pub fn subdomain(&self) -> Option<&str> {
+        match self.domain_start() {
+            0 => None,
+            start => Some(&self.0[..start - 1]),
+        }
+    }
+
+    /// Whether this handle begins with a dotted IPv4 address, like
+    /// `10.0.0.1.sslip.io`.
+    ///
+    /// A handle can't itself be an IP address, since its last label can't
+    /// start with a digit. But a handle can embed one, in a domain whose DNS
+    /// answers with the address in its name; such handles can't be delegated
+    /// to an account, as their owner doesn't control their DNS records.
+    ///
+    /// ```
+    /// use atprose_types::Handle;
+    ///
+    /// assert!(Handle::<&str>::new("127.0.0.1.nip.io").looks_like_ip());
+    /// assert!(!Handle::<&str>::new("1.2.3.example.com").looks_like_ip());
+    /// assert!(!Handle::<&str>::new("300.0.0.1.nip.io").looks_like_ip());
+    /// ```
+    pub fn looks_like_ip(&self) -> bool {
+        let mut labels = self.0.split('.');
+        let octets = labels
+            .by_ref()
+            .take(4)
+            .filter(|label| label.parse::<u8>().is_ok())
+            .count();
+
+        octets == 4 && labels.next().is_some()
+    }
+
+    /// Whether every label of this handle before its [`tld`][Self::tld] is
+    /// made of digits, like `8.cn` or `123.456.com`.
+    ///
+    /// These are syntactically valid, but are rarely anyone's real handle;
+    /// they are more often a mistyped number or address.
+    pub fn is_numeric(&self) -> bool {
+        let (rest, _) = self.0.rsplit_once('.').expect("handle has a dot");
+        rest.split('.').all(is_numeric)
+    }
+
+    /// The byte offset of the second-last label.
+    fn domain_start(&self) -> usize {
+        let (rest, _) = self.0.rsplit_once('.').expect("handle has a dot");
+        rest.rfind('.').map_or(0, |i| i + 1)
+    }
 }
 
 impl<T> Handle<T> {
-    const MAX_LENGTH: usize = 0x100 - 3;
-    const MAX_SEGMENT_LENGTH: usize = 0x40 - 1;
+    /// The maximum length of a handle, in bytes (253).
+    pub const MAX_LENGTH: usize = 0x100 - 3;
+    /// The maximum length of each dot-separated label of a handle, in bytes
+    /// (63).
+    pub const MAX_SEGMENT_LENGTH: usize = 0x40 - 1;
 
+    /// Wrap `value` as a handle without validating it.
+    ///
+    /// # Safety
+    ///
+    /// `value` must be a valid AT protocol handle; other methods assume the
+    /// handle syntax has already been checked.
     pub const unsafe fn new_unchecked(value: T) -> Self {
         Self(value)
     }
@@ -49,12 +154,104 @@ impl FromStr for Handle<String> {
     }
 }
 
+impl<'a, T> TryFrom<&'a str> for Handle<T>
+where
+    T: Deref<Target = str> + From<&'a str>,
+{
+    type Error = InvalidHandle;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Self::parse(value)
+    }
+}
+
+/// Convert a string to a handle, reusing its allocation.
+impl<T> TryFrom<String> for Handle<T>
+where
+    T: Deref<Target = str> + From<String>,
+{
+    type Error = InvalidHandle;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(value)
+    }
+}
+
+impl<T: AsRef<str>> AsRef<str> for Handle<T> {
+    fn as_ref(&self) -> &str {
+        self.0.as_ref()
+    }
+}
+
+/// Handles compare, hash, and are [borrowed][Borrow] as their exact string
+/// value. Though handles are case-insensitive, `Handle::new("Bsky.App")` and
+/// `Handle::new("bsky.app")` are not equal, and a set of handles can only be
+/// searched for a `&str` with the same case as its members; lowercase
+/// handles before storing or searching for them to match regardless of case.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use atprose_types::Handle;
+///
+/// let handles: HashSet<Handle> = [Handle::new("bsky.app")].into();
+/// assert!(handles.contains("bsky.app"));
+/// assert!(!handles.contains("Bsky.App"));
+/// ```
+impl<T: Borrow<str>> Borrow<str> for Handle<T> {
+    fn borrow(&self) -> &str {
+        self.0.borrow()
+    }
+}
+
+/// Handles compare equal to their exact string value; like [`Borrow`], this
+/// is case-sensitive.
+impl<T: AsRef<str>> PartialEq<str> for Handle<T> {
+    fn eq(&self, other: &str) -> bool {
+        self.0.as_ref() == other
+    }
+}
+
+impl<T: AsRef<str>> PartialEq<&str> for Handle<T> {
+    fn eq(&self, other: &&str) -> bool {
+        self.0.as_ref() == *other
+    }
+}
+
 impl<T: Display> Display for Handle<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de, T> Deserialize<'de> for Handle<T>
+where
+    T: Deref<Target = str> + From<String>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::parse(s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<T> Serialize for Handle<T>
+where
+    T: AsRef<str>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+
 #[derive(thiserror::Error, PartialEq, Eq, Clone, Debug)]
 pub enum InvalidHandle {
     #[error("empty AT handle")]
@@ -71,6 +268,16 @@ pub enum InvalidHandle {
     Character(char),
 }
 
+fn is_numeric(label: &str) -> bool {
+    label.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Trim whitespace and a leading `@` from user-provided handle input.
+pub(crate) fn lenient(value: &str) -> &str {
+    let value = value.trim();
+    value.strip_prefix('@').unwrap_or(value)
+}
+
 fn validate_handle(value: &str) -> Result<(), InvalidHandle> {
     if value.is_empty() {
         return Err(InvalidHandle::Empty);
@@ -114,7 +321,7 @@ fn validate_handle(value: &str) -> Result<(), InvalidHandle> {
 
 #[cfg(test)]
 mod test {
-    use super::{validate_handle, InvalidHandle};
+    use super::{validate_handle, Handle, InvalidHandle};
 
     #[test]
     fn test_validate_handle() {
@@ -163,4 +370,96 @@ mod test {
             assert_eq!(Err(expected), validate_handle(value));
         }
     }
+
+    #[test]
+    fn test_max_length() {
+        let label = "a".repeat(Handle::<&str>::MAX_SEGMENT_LENGTH);
+        let longest = format!("{label}.{label}.{label}.{}.com", "a".repeat(57));
+        assert_eq!(Handle::<String>::MAX_LENGTH, longest.len());
+        assert_eq!(Ok(()), validate_handle(&longest));
+        assert_eq!(
+            Err(InvalidHandle::Length),
+            validate_handle(&format!("a{longest}"))
+        );
+        assert_eq!(
+            Err(InvalidHandle::SegmentLength),
+            validate_handle(&format!("a{label}.com"))
+        );
+    }
+
+    #[test]
+    fn test_eq_str() {
+        let handle: Handle = Handle::new("bsky.app");
+        assert_eq!(handle, "bsky.app");
+        assert!(Handle::<&str>::new("bsky.app") == *"bsky.app");
+        assert_ne!(handle, "Bsky.App");
+    }
+
+    #[test]
+    fn test_domain() {
+        let cases = [
+            ("alice.bsky.social", "social", "bsky.social", Some("alice")),
+            ("bsky.app", "app", "bsky.app", None),
+            ("a.b.example.co.uk", "uk", "co.uk", Some("a.b.example")),
+        ];
+
+        for (value, tld, domain, subdomain) in cases {
+            let handle = Handle::<&str>::new(value);
+            assert_eq!(tld, handle.tld(), "{value}");
+            assert_eq!(domain, handle.domain(), "{value}");
+            assert_eq!(subdomain, handle.subdomain(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_numeric() {
+        let cases = [
+            ("alice.bsky.social", false, false),
+            ("8.cn", false, true),
+            ("123.456.com", false, true),
+            ("1.2.3.example.com", false, false),
+            ("10.0.0.1.sslip.io", true, false),
+            ("10.0.0.1.io", true, true),
+            ("10.0.0.256.sslip.io", false, false),
+            ("10.0.0.01.sslip.io", true, false),
+            ("a10.0.0.1.sslip.io", false, false),
+        ];
+
+        for (value, ip, numeric) in cases {
+            let handle = Handle::<&str>::new(value);
+            assert_eq!(ip, handle.looks_like_ip(), "{value}");
+            assert_eq!(numeric, handle.is_numeric(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let expected: Handle = Handle::new("alice.bsky.social");
+
+        for value in [
+            "alice.bsky.social",
+            "@alice.bsky.social",
+            "  @alice.bsky.social\n",
+            "\talice.bsky.social ",
+        ] {
+            assert_eq!(
+                Ok(expected.clone()),
+                Handle::parse_lenient(value),
+                "{value:?}"
+            );
+        }
+
+        assert_eq!(
+            Err(InvalidHandle::Character('@')),
+            Handle::<String>::parse_lenient("@@alice.bsky.social")
+        );
+        assert_eq!(
+            Err(InvalidHandle::Character(' ')),
+            Handle::<String>::parse_lenient("@ alice.bsky.social")
+        );
+        assert_eq!(
+            Err(InvalidHandle::Character('@')),
+            "@alice.bsky.social".parse::<Handle>()
+        );
+    }
 }
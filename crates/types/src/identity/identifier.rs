@@ -1,8 +1,16 @@
-use std::{fmt, str::FromStr};
+use alloc::string::String;
+#[cfg(feature = "serde")]
+use alloc::string::ToString;
+use core::{fmt, str::FromStr};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "plc")]
+use super::did::PlcId;
 use super::{
-    did::{Did, InvalidDid, PlcId},
-    handle::{Handle, InvalidHandle},
+    did::{Did, InvalidDid},
+    handle::{lenient, Handle, InvalidHandle},
 };
 
 /// An [`at-identifier`][]: a [DID][Did] or a [handle][Handle].
@@ -14,6 +22,88 @@ pub enum Identifier {
     Handle(Handle),
 }
 
+impl Identifier {
+    /// Parse an identifier as a user might type or paste it.
+    ///
+    /// Surrounding whitespace is trimmed, and then a single leading `@` is
+    /// removed, before parsing the remainder exactly like
+    /// [`from_str`][FromStr::from_str]. See [`Handle::parse_lenient`].
+    pub fn parse_lenient(value: &str) -> Result<Self, InvalidIdentifier> {
+        lenient(value).parse()
+    }
+
+    /// Parse an identifier as either a DID or a handle, whatever its prefix.
+    ///
+    /// [`from_str`][FromStr::from_str] decides which to parse by whether
+    /// `value` starts with `did:`. This tries both, and if neither succeeds,
+    /// returns the error from the one `value` most resembles: a DID if it
+    /// contains a `:` (which a handle never does), and otherwise a handle.
+    /// So `DID:plc:…` is reported as a DID with a bad prefix, rather than a
+    /// handle with a bad character.
+    ///
+    /// ```
+    /// use atprose_types::{Identifier, InvalidDid, InvalidIdentifier};
+    ///
+    /// assert_eq!(
+    ///     Err(InvalidIdentifier::Did(InvalidDid::Prefix)),
+    ///     Identifier::parse_either("DID:web:bsky.app")
+    /// );
+    /// assert!(Identifier::parse_either("bsky.app").unwrap().is_handle());
+    /// ```
+    pub fn parse_either(value: &str) -> Result<Self, InvalidIdentifier> {
+        let did = match value.parse::<Did>() {
+            Ok(did) => return Ok(Self::Did(did)),
+            Err(error) => error,
+        };
+        let handle = match value.parse::<Handle>() {
+            Ok(handle) => return Ok(Self::Handle(handle)),
+            Err(error) => error,
+        };
+
+        Err(if value.contains(':') {
+            InvalidIdentifier::Did(did)
+        } else {
+            InvalidIdentifier::Handle(handle)
+        })
+    }
+
+    pub fn is_did(&self) -> bool {
+        matches!(self, Self::Did(_))
+    }
+
+    pub fn is_handle(&self) -> bool {
+        matches!(self, Self::Handle(_))
+    }
+
+    pub fn as_did(&self) -> Option<&Did> {
+        match self {
+            Self::Did(did) => Some(did),
+            Self::Handle(_) => None,
+        }
+    }
+
+    pub fn as_handle(&self) -> Option<&Handle> {
+        match self {
+            Self::Did(_) => None,
+            Self::Handle(handle) => Some(handle),
+        }
+    }
+
+    pub fn into_did(self) -> Option<Did> {
+        match self {
+            Self::Did(did) => Some(did),
+            Self::Handle(_) => None,
+        }
+    }
+
+    pub fn into_handle(self) -> Option<Handle> {
+        match self {
+            Self::Did(_) => None,
+            Self::Handle(handle) => Some(handle),
+        }
+    }
+}
+
 impl From<Did> for Identifier {
     fn from(value: Did) -> Self {
         Self::Did(value)
@@ -46,6 +136,27 @@ impl FromStr for Identifier {
     }
 }
 
+impl TryFrom<&str> for Identifier {
+    type Error = InvalidIdentifier;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Convert a string to an identifier, reusing its allocation for a handle.
+impl TryFrom<String> for Identifier {
+    type Error = InvalidIdentifier;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if value.starts_with("did:") {
+            value.parse()
+        } else {
+            Ok(Self::Handle(Handle::try_from(value)?))
+        }
+    }
+}
+
 impl fmt::Display for Identifier {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -55,10 +166,38 @@ impl fmt::Display for Identifier {
     }
 }
 
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for Identifier {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::try_from(s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for Identifier {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// An invalid [`Identifier`], which says whether it was parsed as a DID or
+/// as a handle.
 #[derive(thiserror::Error, PartialEq, Clone, Debug)]
 pub enum InvalidIdentifier {
+    /// The identifier looked like a DID (it started with `did:`), but was not
+    /// a valid one.
     #[error("invalid DID: {0}")]
     Did(#[from] InvalidDid),
+    /// The identifier looked like a handle, but was not a valid one.
     #[error("invalid handle: {0}")]
     Handle(#[from] InvalidHandle),
 }
@@ -70,3 +209,93 @@ impl InvalidIdentifier {
         Self::Handle(InvalidHandle::Empty)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Identifier, InvalidIdentifier};
+    use crate::{Did, Handle, InvalidDid, InvalidHandle};
+
+    #[test]
+    fn test_identifier_accessors() {
+        let did: Did = "did:web:bsky.app".parse().unwrap();
+        let id = Identifier::from(did.clone());
+        assert!(id.is_did());
+        assert!(!id.is_handle());
+        assert_eq!(Some(&did), id.as_did());
+        assert_eq!(None, id.as_handle());
+        assert_eq!(Some(did), id.into_did());
+
+        let handle = Handle::new("bsky.app");
+        let id = Identifier::from(handle.clone());
+        assert!(id.is_handle());
+        assert!(!id.is_did());
+        assert_eq!(Some(&handle), id.as_handle());
+        assert_eq!(None, id.as_did());
+        assert_eq!(Some(handle), id.clone().into_handle());
+        assert_eq!(None, id.into_did());
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        assert_eq!(
+            Ok(Identifier::Handle(Handle::new("alice.bsky.social"))),
+            Identifier::parse_lenient(" @alice.bsky.social ")
+        );
+        assert_eq!(
+            Ok(Identifier::Did("did:web:bsky.app".parse().unwrap())),
+            Identifier::parse_lenient("did:web:bsky.app\n")
+        );
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(
+            Ok(Identifier::Handle(Handle::new("bsky.app"))),
+            Identifier::try_from("bsky.app")
+        );
+        assert_eq!(
+            Ok(Identifier::Handle(Handle::new("bsky.app"))),
+            Identifier::try_from("bsky.app".to_owned())
+        );
+        assert_eq!(
+            Ok(Identifier::Did("did:web:bsky.app".parse().unwrap())),
+            Identifier::try_from("did:web:bsky.app".to_owned())
+        );
+        assert!(Identifier::try_from("bsky").is_err());
+        assert!(Handle::<String>::try_from("-bsky.app".to_owned()).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("did:foo:bar", InvalidIdentifier::Did(InvalidDid::Scheme)),
+            (
+                "did:web:",
+                InvalidIdentifier::Did(InvalidDid::Web(InvalidHandle::Empty)),
+            ),
+            ("bsky", InvalidIdentifier::Handle(InvalidHandle::Domain)),
+            ("", InvalidIdentifier::empty()),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(
+                Err(expected.clone()),
+                value.parse::<Identifier>(),
+                "{value:?}"
+            );
+            assert_eq!(Err(expected), Identifier::parse_either(value), "{value:?}");
+        }
+
+        assert_eq!(
+            Err(InvalidIdentifier::Handle(InvalidHandle::Character(':'))),
+            "DID:web:bsky.app".parse::<Identifier>()
+        );
+        assert_eq!(
+            Err(InvalidIdentifier::Did(InvalidDid::Prefix)),
+            Identifier::parse_either("DID:web:bsky.app")
+        );
+        assert_eq!(
+            Ok(Identifier::Did("did:web:bsky.app".parse().unwrap())),
+            Identifier::parse_either("did:web:bsky.app")
+        );
+    }
+}
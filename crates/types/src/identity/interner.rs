@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::identifier::{Identifier, InvalidIdentifier};
+
+/// A cache of parsed [identifiers][Identifier], which shares one allocation
+/// among every occurrence of the same identifier.
+///
+/// This is meant for workloads which see the same DIDs and handles over and
+/// over, such as indexing a firehose: each distinct string is parsed once,
+/// and later calls to [`intern`][Self::intern] return the existing
+/// [`Arc`]. Identifiers are matched by their exact string, so handles which
+/// differ only in case are interned separately.
+///
+/// The interner is unbounded: nothing is evicted automatically. Call
+/// [`purge`][Self::purge] periodically to drop identifiers which are no
+/// longer used outside the interner, or [`clear`][Self::clear] to drop all of
+/// them.
+///
+/// ```
+/// use std::sync::Arc;
+/// use atprose_types::Interner;
+///
+/// # fn main() -> Result<(), atprose_types::InvalidIdentifier> {
+/// let mut interner = Interner::new();
+/// let a = interner.intern("did:plc:z72i7hdynmk6r22z27h6tvur")?;
+/// let b = interner.intern("did:plc:z72i7hdynmk6r22z27h6tvur")?;
+/// assert!(Arc::ptr_eq(&a, &b));
+/// assert_eq!(1, interner.len());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Interner {
+    identifiers: HashMap<String, Arc<Identifier>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the shared instance of the identifier `value`, parsing and
+    /// caching it if it hasn't been seen before.
+    ///
+    /// Invalid identifiers are not cached.
+    pub fn intern(&mut self, value: &str) -> Result<Arc<Identifier>, InvalidIdentifier> {
+        if let Some(identifier) = self.identifiers.get(value) {
+            return Ok(Arc::clone(identifier));
+        }
+
+        let identifier = Arc::new(value.parse::<Identifier>()?);
+        self.identifiers
+            .insert(value.to_owned(), Arc::clone(&identifier));
+
+        Ok(identifier)
+    }
+
+    /// Return the shared instance of the identifier `value`, if it has been
+    /// interned.
+    pub fn get(&self, value: &str) -> Option<Arc<Identifier>> {
+        self.identifiers.get(value).cloned()
+    }
+
+    /// Drop every identifier which isn't referenced outside the interner.
+    pub fn purge(&mut self) {
+        self.identifiers
+            .retain(|_, identifier| Arc::strong_count(identifier) > 1);
+    }
+
+    /// Drop every interned identifier.
+    ///
+    /// Instances already returned by [`intern`][Self::intern] remain valid,
+    /// but are no longer shared with later calls.
+    pub fn clear(&mut self) {
+        self.identifiers.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.identifiers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.identifiers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::Interner;
+    use crate::{Handle, Identifier, InvalidHandle, InvalidIdentifier};
+
+    #[test]
+    fn test_intern() {
+        let mut interner = Interner::new();
+        assert!(interner.is_empty());
+
+        let did = interner.intern("did:plc:z72i7hdynmk6r22z27h6tvur").unwrap();
+        assert!(did.is_did());
+        let handle = interner.intern("bsky.app").unwrap();
+        assert_eq!(Identifier::Handle(Handle::new("bsky.app")), *handle);
+
+        let again = interner.intern("did:plc:z72i7hdynmk6r22z27h6tvur").unwrap();
+        assert!(Arc::ptr_eq(&did, &again));
+        assert!(Arc::ptr_eq(&handle, &interner.get("bsky.app").unwrap()));
+        assert_eq!(2, interner.len());
+
+        assert_eq!(
+            Err(InvalidIdentifier::Handle(InvalidHandle::Empty)),
+            interner.intern("")
+        );
+        assert_eq!(2, interner.len());
+
+        drop((did, again));
+        interner.purge();
+        assert_eq!(1, interner.len());
+        assert_eq!(None, interner.get("did:plc:z72i7hdynmk6r22z27h6tvur"));
+
+        interner.clear();
+        assert!(interner.is_empty());
+        assert_eq!(Identifier::Handle(Handle::new("bsky.app")), *handle);
+    }
+}
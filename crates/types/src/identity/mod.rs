@@ -1,3 +1,9 @@
 pub(crate) mod did;
 pub(crate) mod handle;
 pub(crate) mod identifier;
+#[cfg(feature = "interner")]
+pub(crate) mod interner;
+#[cfg(feature = "resolve")]
+pub(crate) mod resolve;
+#[cfg(feature = "handle-set")]
+pub(crate) mod set;
@@ -0,0 +1,281 @@
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+use core::future::Future;
+
+use super::{did::Did, handle::Handle};
+
+/// Resolves [DIDs][Did] to their [DID documents][DidDocument].
+///
+/// This crate doesn't resolve DIDs itself, which takes HTTP requests to the
+/// [PLC directory][plc] or a `did:web` host. Implementing this trait lets
+/// code which needs DID documents be written against any resolver; for
+/// tests, a [`StaticResolver`] returns fixed documents.
+///
+/// [plc]: https://web.plc.directory/
+pub trait DidResolver {
+    /// Find the current DID document for `did`.
+    fn resolve(&self, did: &Did) -> impl Future<Output = Result<DidDocument, ResolveError>> + Send;
+}
+
+/// The parts of a [DID document][spec] used by AT protocol.
+///
+/// [spec]: https://atproto.com/specs/did#did-documents
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct DidDocument {
+    pub id: Did,
+    /// Other URIs for the subject; the first valid `at://` URI among these is
+    /// its [handle][Self::handle].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub also_known_as: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub service: Vec<Service>,
+}
+
+impl DidDocument {
+    pub fn new(id: Did) -> Self {
+        Self {
+            id,
+            also_known_as: Vec::new(),
+            service: Vec::new(),
+        }
+    }
+
+    /// The handle claimed by this document, from its first `at://` URI in
+    /// [`also_known_as`][Self::also_known_as].
+    ///
+    /// The handle should be confirmed to resolve back to this DID before
+    /// it's trusted.
+    pub fn handle(&self) -> Option<Handle> {
+        self.also_known_as
+            .iter()
+            .filter_map(|uri| uri.strip_prefix("at://"))
+            .find_map(|handle| Handle::parse(handle).ok())
+    }
+
+    /// The URL of the subject's personal data server (PDS), from its
+    /// `#atproto_pds` service.
+    pub fn pds_endpoint(&self) -> Option<&str> {
+        self.service
+            .iter()
+            .find(|service| {
+                service.id.ends_with("#atproto_pds") && service.kind == "AtprotoPersonalDataServer"
+            })
+            .map(|service| service.endpoint.as_str())
+    }
+}
+
+/// Check the half of a [handle][spec]–DID binding that doesn't need the
+/// network: that `document` is the document for `did`, and that its
+/// [handle][DidDocument::handle] is `handle`.
+///
+/// Only the first `at://` handle in `alsoKnownAs` counts, as the spec
+/// requires, and handles are compared ignoring case. The binding is only
+/// confirmed once `handle` has also been resolved (through DNS or HTTPS) to
+/// `did`.
+///
+/// [spec]: https://atproto.com/specs/handle#handle-resolution
+pub fn verify_handle_binding<T: AsRef<str>>(
+    did: &Did,
+    handle: &Handle<T>,
+    document: &DidDocument,
+) -> bool {
+    document.id == *did
+        && document
+            .handle()
+            .is_some_and(|claimed| claimed.as_ref().eq_ignore_ascii_case(handle.as_ref()))
+}
+
+/// A service endpoint listed in a [`DidDocument`].
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Service {
+    /// The service ID, like `#atproto_pds`.
+    pub id: String,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub kind: String,
+    #[cfg_attr(feature = "serde", serde(rename = "serviceEndpoint"))]
+    pub endpoint: String,
+}
+
+/// An error resolving a [`Did`] with a [`DidResolver`].
+#[derive(thiserror::Error, Debug)]
+pub enum ResolveError {
+    #[error("DID {0} not found")]
+    NotFound(Did),
+    #[error("could not resolve DID: {0}")]
+    Failed(#[source] Box<dyn core::error::Error + Send + Sync>),
+}
+
+/// A [`DidResolver`] which resolves only the documents it's given.
+///
+/// ```
+/// use atprose_types::{Did, DidDocument, StaticResolver};
+///
+/// let did: Did = "did:plc:z72i7hdynmk6r22z27h6tvur".parse().unwrap();
+/// let resolver: StaticResolver = [DidDocument::new(did.clone())].into_iter().collect();
+/// assert!(resolver.get(&did).is_some());
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct StaticResolver {
+    documents: BTreeMap<Did, DidDocument>,
+}
+
+impl StaticResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `document`, replacing any document with the same ID.
+    pub fn insert(&mut self, document: DidDocument) {
+        self.documents.insert(document.id.clone(), document);
+    }
+
+    pub fn get(&self, did: &Did) -> Option<&DidDocument> {
+        self.documents.get(did)
+    }
+}
+
+impl DidResolver for StaticResolver {
+    async fn resolve(&self, did: &Did) -> Result<DidDocument, ResolveError> {
+        self.get(did)
+            .cloned()
+            .ok_or_else(|| ResolveError::NotFound(did.clone()))
+    }
+}
+
+impl FromIterator<DidDocument> for StaticResolver {
+    fn from_iter<T: IntoIterator<Item = DidDocument>>(iter: T) -> Self {
+        let mut resolver = Self::new();
+        resolver.extend(iter);
+        resolver
+    }
+}
+
+impl Extend<DidDocument> for StaticResolver {
+    fn extend<T: IntoIterator<Item = DidDocument>>(&mut self, iter: T) {
+        for document in iter {
+            self.insert(document);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    use super::{
+        verify_handle_binding, DidDocument, DidResolver, ResolveError, Service, StaticResolver,
+    };
+    use crate::{Did, Handle};
+
+    /// Run a future which never waits.
+    fn ready<F: Future>(future: F) -> F::Output {
+        let mut context = Context::from_waker(Waker::noop());
+        match pin!(future).poll(&mut context) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future is not ready"),
+        }
+    }
+
+    fn document() -> DidDocument {
+        DidDocument {
+            id: "did:plc:z72i7hdynmk6r22z27h6tvur".parse().unwrap(),
+            also_known_as: vec!["https://bsky.app".into(), "at://bsky.app".into()],
+            service: vec![Service {
+                id: "#atproto_pds".into(),
+                kind: "AtprotoPersonalDataServer".into(),
+                endpoint: "https://puffball.us-east.host.bsky.network".into(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_did_document() {
+        let document = document();
+        assert_eq!(Some(Handle::new("bsky.app")), document.handle());
+        assert_eq!(
+            Some("https://puffball.us-east.host.bsky.network"),
+            document.pds_endpoint()
+        );
+
+        let empty = DidDocument::new(document.id.clone());
+        assert_eq!(None, empty.handle());
+        assert_eq!(None, empty.pds_endpoint());
+    }
+
+    #[test]
+    fn test_verify_handle_binding() {
+        let document = document();
+        let did = document.id.clone();
+
+        assert!(verify_handle_binding(
+            &did,
+            &Handle::<&str>::new("bsky.app"),
+            &document
+        ));
+        assert!(verify_handle_binding(
+            &did,
+            &Handle::<&str>::new("BSKY.app"),
+            &document
+        ));
+        assert!(!verify_handle_binding(
+            &did,
+            &Handle::<&str>::new("bsky.social"),
+            &document
+        ));
+
+        let other: Did = "did:web:bsky.app".parse().unwrap();
+        assert!(!verify_handle_binding(
+            &other,
+            &Handle::<&str>::new("bsky.app"),
+            &document
+        ));
+
+        let mut second = document.clone();
+        second.also_known_as = vec!["at://bsky.social".into(), "at://bsky.app".into()];
+        assert!(!verify_handle_binding(
+            &did,
+            &Handle::<&str>::new("bsky.app"),
+            &second
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_did_document_serde() {
+        let json = r##"{
+            "@context": ["https://www.w3.org/ns/did/v1"],
+            "id": "did:plc:z72i7hdynmk6r22z27h6tvur",
+            "alsoKnownAs": ["https://bsky.app", "at://bsky.app"],
+            "verificationMethod": [],
+            "service": [{
+                "id": "#atproto_pds",
+                "type": "AtprotoPersonalDataServer",
+                "serviceEndpoint": "https://puffball.us-east.host.bsky.network"
+            }]
+        }"##;
+
+        let parsed: DidDocument = serde_json::from_str(json).unwrap();
+        assert_eq!(document(), parsed);
+    }
+
+    #[test]
+    fn test_static_resolver() {
+        let resolver: StaticResolver = [document()].into_iter().collect();
+
+        let did = document().id;
+        assert_eq!(document(), ready(resolver.resolve(&did)).unwrap());
+
+        let other: Did = "did:web:example.com".parse().unwrap();
+        assert!(matches!(
+            ready(resolver.resolve(&other)),
+            Err(ResolveError::NotFound(did)) if did == other
+        ));
+    }
+}
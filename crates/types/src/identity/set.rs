@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+
+use super::handle::Handle;
+
+/// A set of [handles][Handle], matched regardless of case.
+///
+/// Handles are stored in lowercase, and [`contains`][Self::contains] looks
+/// up the lowercase form of its argument, so `Bsky.App` matches `bsky.app`.
+/// This is meant for checking many handles against a large, mostly-static
+/// set, such as a blocklist.
+///
+/// A set created [with a bloom filter][Self::with_bloom_filter] checks the
+/// filter before the set itself, which makes the (usually common) case of a
+/// handle that isn't in the set cheaper. The filter never causes a false
+/// match.
+///
+/// ```
+/// use atprose_types::{Handle, HandleSet};
+///
+/// let blocked: HandleSet = [Handle::<String>::new("spam.example.com")].into_iter().collect();
+/// assert!(blocked.contains(&Handle::<&str>::new("SPAM.example.com")));
+/// assert!(!blocked.contains(&Handle::<&str>::new("bsky.app")));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HandleSet {
+    handles: HashSet<String>,
+    bloom: Option<Bloom>,
+}
+
+impl HandleSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty set with a bloom filter sized for `capacity` handles.
+    ///
+    /// The filter uses about 10 bits per handle, for a false-positive rate of
+    /// roughly 1% at capacity. Inserting more handles than `capacity` is
+    /// allowed, but makes the filter less effective.
+    pub fn with_bloom_filter(capacity: usize) -> Self {
+        Self {
+            handles: HashSet::with_capacity(capacity),
+            bloom: Some(Bloom::new(capacity)),
+        }
+    }
+
+    /// Add a handle to the set, returning whether it was newly added.
+    pub fn insert<T: AsRef<str>>(&mut self, handle: &Handle<T>) -> bool {
+        let handle = handle.as_ref().to_ascii_lowercase();
+
+        if let Some(bloom) = &mut self.bloom {
+            bloom.insert(&handle);
+        }
+
+        self.handles.insert(handle)
+    }
+
+    /// Whether the set contains `handle`, ignoring case.
+    pub fn contains<T: AsRef<str>>(&self, handle: &Handle<T>) -> bool {
+        let handle = handle.as_ref();
+
+        if handle.bytes().any(|c| c.is_ascii_uppercase()) {
+            self.contains_normalized(&handle.to_ascii_lowercase())
+        } else {
+            self.contains_normalized(handle)
+        }
+    }
+
+    fn contains_normalized(&self, handle: &str) -> bool {
+        match &self.bloom {
+            Some(bloom) if !bloom.contains(handle) => false,
+            _ => self.handles.contains(handle),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Iterate over the (lowercase) handles in the set, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = Handle<&str>> {
+        // SAFETY: only valid handles are inserted, and lowercasing a valid
+        // handle leaves it valid.
+        self.handles
+            .iter()
+            .map(|handle| unsafe { Handle::new_unchecked(handle.as_str()) })
+    }
+}
+
+impl<T: AsRef<str>> FromIterator<Handle<T>> for HandleSet {
+    fn from_iter<I: IntoIterator<Item = Handle<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: AsRef<str>> Extend<Handle<T>> for HandleSet {
+    fn extend<I: IntoIterator<Item = Handle<T>>>(&mut self, iter: I) {
+        for handle in iter {
+            self.insert(&handle);
+        }
+    }
+}
+
+/// A fixed-size bloom filter over strings.
+#[derive(Clone, Debug)]
+struct Bloom {
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl Bloom {
+    const BITS_PER_ITEM: usize = 10;
+    const HASHES: u32 = 7;
+
+    fn new(capacity: usize) -> Self {
+        let words = (capacity.max(1) * Self::BITS_PER_ITEM).div_ceil(64);
+
+        Self {
+            bits: vec![0; words],
+            hashes: Self::HASHES,
+        }
+    }
+
+    fn insert(&mut self, value: &str) {
+        for bit in self.positions(value) {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    fn contains(&self, value: &str) -> bool {
+        self.positions(value)
+            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// The bits for `value`, derived from one 64-bit hash by
+    /// [double hashing][dh].
+    ///
+    /// The hash is [FNV-1a][fnv] rather than the set's SipHash: the filter
+    /// only pays off if checking it costs much less than the lookup it
+    /// saves, and it doesn't need SipHash's resistance to chosen inputs.
+    ///
+    /// [dh]: https://doi.org/10.1002/rsa.20208
+    /// [fnv]: http://www.isthe.com/chongo/tech/comp/fnv/
+    fn positions(&self, value: &str) -> impl Iterator<Item = usize> {
+        let hash = value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3)
+        });
+
+        let (a, b) = (hash & 0xffff_ffff, (hash >> 32) | 1);
+        let size = (self.bits.len() * 64) as u64;
+
+        (0..u64::from(self.hashes))
+            .map(move |i| (a.wrapping_add(i.wrapping_mul(b)) % size) as usize)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HandleSet;
+    use crate::Handle;
+
+    fn handle(value: impl AsRef<str>) -> Handle {
+        Handle::new(value.as_ref())
+    }
+
+    #[test]
+    fn test_handle_set() {
+        for mut set in [HandleSet::new(), HandleSet::with_bloom_filter(100)] {
+            assert!(set.is_empty());
+            assert!(set.insert(&handle("Alice.bsky.social")));
+            assert!(!set.insert(&handle("alice.BSKY.social")));
+            assert!(set.insert(&Handle::<&str>::new("bsky.app")));
+            assert_eq!(2, set.len());
+
+            assert!(set.contains(&handle("alice.bsky.social")));
+            assert!(set.contains(&Handle::<&str>::new("ALICE.BSKY.SOCIAL")));
+            assert!(set.contains(&handle("bsky.app")));
+            assert!(!set.contains(&handle("bob.bsky.social")));
+
+            let mut handles: Vec<_> = set.iter().map(|h| h.to_string()).collect();
+            handles.sort();
+            assert_eq!(vec!["alice.bsky.social", "bsky.app"], handles);
+        }
+    }
+
+    #[test]
+    fn test_bloom_filter() {
+        let handles: Vec<Handle> = (0..1000)
+            .map(|i| handle(format!("user{i}.bsky.social")))
+            .collect();
+
+        let mut set = HandleSet::with_bloom_filter(handles.len());
+        set.extend(handles.iter().cloned());
+
+        for handle in &handles {
+            assert!(set.contains(handle), "{handle}");
+        }
+
+        let bloom = set.bloom.as_ref().unwrap();
+        let false_positives = (0..1000)
+            .map(|i| format!("other{i}.bsky.social"))
+            .filter(|value| {
+                assert!(!set.contains(&handle(value)), "{value}");
+                bloom.contains(value)
+            })
+            .count();
+        assert!(false_positives < 50, "{false_positives} false positives");
+    }
+}
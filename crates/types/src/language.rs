@@ -0,0 +1,108 @@
+/// Check that `value` has the general shape of a [BCP 47][] language tag.
+///
+/// This is a lightweight check which doesn't need the `language` feature (and
+/// its `oxilangtag` dependency): the primary subtag must be 2–8 ASCII letters
+/// (or `x`/`i` for private-use and grandfathered tags), and every following
+/// subtag must be 1–8 ASCII letters or digits, separated by `-`. Singleton
+/// subtags (like the `x` of `en-x-private`) must be followed by another
+/// subtag. Subtags are not checked against the IANA registry.
+///
+/// ```
+/// use atprose_types::validate_language;
+///
+/// assert!(validate_language("en").is_ok());
+/// assert!(validate_language("pt-BR").is_ok());
+/// assert!(validate_language("zh-Hant-TW").is_ok());
+/// assert!(validate_language("english").is_ok());
+/// assert!(validate_language("en_US").is_err());
+/// ```
+///
+/// [BCP 47]: https://www.rfc-editor.org/info/bcp47
+pub fn validate_language(value: &str) -> Result<(), InvalidLanguage> {
+    if value.is_empty() {
+        return Err(InvalidLanguage::Empty);
+    }
+
+    let mut subtags = value.split('-');
+    let primary = subtags.next().unwrap_or_default();
+
+    let singleton = match primary.len() {
+        1 if primary.eq_ignore_ascii_case("x") || primary.eq_ignore_ascii_case("i") => true,
+        2..=8 if primary.bytes().all(|c| c.is_ascii_alphabetic()) => false,
+        _ => return Err(InvalidLanguage::Primary),
+    };
+
+    let mut expect_subtag = singleton;
+    for subtag in subtags {
+        if subtag.is_empty()
+            || subtag.len() > 8
+            || !subtag.bytes().all(|c| c.is_ascii_alphanumeric())
+        {
+            return Err(InvalidLanguage::Subtag);
+        }
+
+        expect_subtag = subtag.len() == 1;
+    }
+
+    if expect_subtag {
+        return Err(InvalidLanguage::Subtag);
+    }
+
+    Ok(())
+}
+
+#[derive(thiserror::Error, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum InvalidLanguage {
+    #[error("empty language tag")]
+    Empty,
+    #[error("invalid primary language subtag")]
+    Primary,
+    #[error("invalid language subtag")]
+    Subtag,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{validate_language, InvalidLanguage};
+
+    #[test]
+    fn test_validate_language() {
+        use InvalidLanguage::*;
+
+        let valid = [
+            "ja",
+            "ase",
+            "en-US",
+            "sr-Latn-RS",
+            "es-419",
+            "de-CH-1996",
+            "zh-cmn-Hans-CN",
+            "en-x-private",
+            "x-whatever",
+            "i-klingon",
+        ];
+
+        let invalid = [
+            ("", Empty),
+            ("e", Primary),
+            ("1a", Primary),
+            ("toolonglang", Primary),
+            ("en_US", Primary),
+            ("en-", Subtag),
+            ("en--US", Subtag),
+            ("en-US-", Subtag),
+            ("en-abcdefghi", Subtag),
+            ("en-ü", Subtag),
+            ("x", Subtag),
+            ("en-x", Subtag),
+        ];
+
+        for value in valid {
+            assert_eq!(Ok(()), validate_language(value), "{value}");
+        }
+
+        for (value, expected) in invalid {
+            assert_eq!(Err(expected), validate_language(value), "{value}");
+        }
+    }
+}
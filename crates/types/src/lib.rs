@@ -1,37 +1,141 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 pub use cid::Cid;
 
+/// A Lexicon [datetime][].
+///
+/// The representation depends on the enabled features: with `chrono`, this is
+/// a [`chrono::DateTime`] in UTC; with `time` (and not `chrono`), it is a
+/// [`time::OffsetDateTime`]; and otherwise it is an unparsed `String`. When
+/// both `chrono` and `time` are enabled, `chrono` takes precedence.
+///
+/// [datetime]: https://atproto.com/specs/lexicon#datetime
 #[cfg(feature = "chrono")]
 #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
 pub type DateTime = chrono::DateTime<chrono::Utc>;
 
-#[cfg(not(feature = "chrono"))]
-pub type DateTime = String;
+/// A Lexicon [datetime][].
+///
+/// [datetime]: https://atproto.com/specs/lexicon#datetime
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "time", not(feature = "chrono")))))]
+pub type DateTime = time::OffsetDateTime;
+
+#[cfg(not(any(feature = "chrono", feature = "time")))]
+pub type DateTime = alloc::string::String;
 
 #[cfg(feature = "language")]
 #[cfg_attr(docsrs, doc(cfg(feature = "language")))]
-pub type Language = oxilangtag::LanguageTag<String>;
+pub type Language = oxilangtag::LanguageTag<alloc::string::String>;
 
 #[cfg(not(feature = "language"))]
-pub type Language = String;
+pub type Language = alloc::string::String;
+
+pub(crate) mod blob;
+pub use blob::BlobRef;
+
+#[cfg(all(feature = "serde", any(feature = "plc", feature = "tid")))]
+pub(crate) mod bytes;
 
-#[cfg(any(feature = "plc", feature = "rkey"))]
+pub(crate) mod datetime;
+pub use datetime::{validate_datetime, DatetimePolicy, InvalidDatetime};
+
+#[cfg(any(feature = "plc", feature = "tid"))]
 pub(crate) mod encoding;
 
+#[cfg(feature = "json")]
+pub(crate) mod unknown;
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub use unknown::{InvalidUnknown, Unknown};
+
+pub(crate) mod language;
+pub use language::{validate_language, InvalidLanguage};
+
 mod identity;
+#[cfg(feature = "interner")]
+#[cfg_attr(docsrs, doc(cfg(feature = "interner")))]
+pub use identity::interner::Interner;
+#[cfg(feature = "resolve")]
+#[cfg_attr(docsrs, doc(cfg(feature = "resolve")))]
+pub use identity::resolve::{
+    verify_handle_binding, DidDocument, DidResolver, ResolveError, Service, StaticResolver,
+};
+#[cfg(feature = "handle-set")]
+#[cfg_attr(docsrs, doc(cfg(feature = "handle-set")))]
+pub use identity::set::HandleSet;
 pub use identity::{
     did::{Did, InvalidDid, PlcId},
     handle::{Handle, InvalidHandle},
-    identifier::Identifier,
+    identifier::{Identifier, InvalidIdentifier},
 };
 
+pub mod prelude;
+
 pub(crate) mod ns;
-pub use ns::{InvalidNsid, Nsid, TypeId};
+pub use ns::{InvalidNsid, Nsid, NsidPattern, TypeId};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::ns::is_valid_const as is_valid_nsid;
+}
 
 pub(crate) mod record;
+#[cfg(feature = "tid")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tid")))]
+pub use record::key::tid::Tid;
+
+/// Serde modules to choose how a [`Tid`] field is (de)serialized.
+///
+/// ```
+/// use atprose_types::Tid;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct Entry {
+///     #[serde(with = "atprose_types::tid::bytes")]
+///     id: Tid,
+/// }
+/// ```
+#[cfg(all(feature = "tid", feature = "serde"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "tid", feature = "serde"))))]
+pub mod tid {
+    pub use crate::record::key::tid::{bytes, string};
+}
+
+/// Serde modules to choose how a [`PlcId`] field is (de)serialized.
+#[cfg(all(feature = "plc", feature = "serde"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "plc", feature = "serde"))))]
+pub mod plc {
+    pub use crate::identity::did::{bytes, string};
+}
 #[cfg(feature = "rkey")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rkey")))]
-pub use record::key::tid::Tid;
+pub use record::key::InvalidRkey;
 pub use record::{
     key::Rkey,
-    uri::{AtUri, AtUriResource, AtUriTarget, InvalidUri},
+    uri::{AtUri, AtUriResource, AtUriTarget, AtUriTargetRef, AuthorityError, InvalidUri},
 };
+
+#[cfg(all(test, feature = "plc", feature = "rkey"))]
+mod roundtrip;
+
+/// Whether `value` [displays][core::fmt::Display] as exactly `other`, without
+/// allocating a string.
+pub(crate) fn display_eq(value: &impl core::fmt::Display, other: &str) -> bool {
+    use core::fmt::Write;
+
+    struct Compare<'a>(&'a str);
+
+    impl Write for Compare<'_> {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            self.0 = self.0.strip_prefix(s).ok_or(core::fmt::Error)?;
+            Ok(())
+        }
+    }
+
+    let mut compare = Compare(other);
+    write!(compare, "{value}").is_ok() && compare.0.is_empty()
+}
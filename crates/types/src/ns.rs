@@ -1,4 +1,11 @@
-use std::{fmt, ops::Deref, str::FromStr};
+#[cfg(feature = "serde")]
+use alloc::string::ToString;
+use alloc::{
+    borrow::{Cow, ToOwned},
+    string::String,
+    vec::Vec,
+};
+use core::{fmt, iter, ops::Deref, str::FromStr};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -8,7 +15,7 @@ use serde::{Deserialize, Serialize};
 /// ```
 /// use atprose_types::Nsid;
 ///
-/// # fn main() -> Result<(), ()> {
+/// # fn main() -> Result<(), atprose_types::InvalidNsid> {
 /// let id: Nsid = "app.bsky.feed.post".parse()?;
 /// assert_eq!(id.authority, "app.bsky.feed");
 /// assert_eq!(id.package, "post");
@@ -22,14 +29,183 @@ pub struct Nsid {
 }
 
 impl Nsid {
+    /// Create an NSID from its authority and name, without validating them.
+    ///
+    /// Use [`try_new`][Self::try_new] to check that the parts form a valid
+    /// NSID.
     pub fn new(authority: impl Into<String>, package: impl Into<String>) -> Self {
         Self {
             authority: authority.into(),
             package: package.into(),
         }
     }
+
+    /// Create an NSID from its authority and name, checking that they form a
+    /// valid NSID.
+    ///
+    /// ```
+    /// use atprose_types::{InvalidNsid, Nsid};
+    ///
+    /// assert!(Nsid::try_new("app.bsky.feed", "post").is_ok());
+    /// assert_eq!(Nsid::try_new("", ""), Err(InvalidNsid::Authority));
+    /// ```
+    pub fn try_new(
+        authority: impl Into<String>,
+        package: impl Into<String>,
+    ) -> Result<Self, InvalidNsid> {
+        let nsid = Self::new(authority, package);
+        validate_nsid(&nsid.authority, &nsid.package)?;
+
+        Ok(nsid)
+    }
+
+    /// Create an NSID from a known-valid authority and name, like
+    /// `Nsid::from_static("app.bsky.feed", "post")`.
+    ///
+    /// To also check the NSID at compile time, and parse it only once, use
+    /// the `nsid!` macro.
+    ///
+    /// # Panics
+    ///
+    /// If the parts do not form a [valid][Self::try_new] NSID.
+    pub fn from_static(authority: &'static str, package: &'static str) -> Self {
+        Self::try_new(authority, package).expect("invalid NSID")
+    }
+
+    /// Parse an NSID, checking it against the full NSID syntax: the length
+    /// limits, the characters allowed in each segment, and that the name
+    /// starts with a letter.
+    ///
+    /// [`from_str`][FromStr::from_str] is lenient by comparison, and only
+    /// checks that there is more than one segment.
+    ///
+    /// ```
+    /// use atprose_types::{InvalidNsid, Nsid};
+    ///
+    /// assert!(Nsid::parse_strict("app.bsky.feed.post").is_ok());
+    /// assert_eq!(Err(InvalidNsid::Character('_')), Nsid::parse_strict("app.bsky.feed_post.get"));
+    /// assert!("app.bsky.feed_post.get".parse::<Nsid>().is_ok());
+    /// ```
+    pub fn parse_strict(value: &str) -> Result<Self, InvalidNsid> {
+        let (authority, name) = value.rsplit_once('.').ok_or(InvalidNsid::Authority)?;
+        Self::try_new(authority, name)
+    }
+
+    /// Check this NSID against the full syntax, like
+    /// [`parse_strict`][Self::parse_strict].
+    pub fn validate(&self) -> Result<(), InvalidNsid> {
+        validate_nsid(&self.authority, &self.package)
+    }
+
+    /// Whether `value` is a valid NSID, checked without allocating.
+    ///
+    /// ```
+    /// use atprose_types::Nsid;
+    ///
+    /// assert!(Nsid::is_valid("app.bsky.feed.post"));
+    /// assert!(!Nsid::is_valid("app.bsky"));
+    /// assert!(!Nsid::is_valid("app.bsky.feed.post#main"));
+    /// ```
+    pub fn is_valid(value: &str) -> bool {
+        value
+            .rsplit_once('.')
+            .is_some_and(|(authority, name)| validate_nsid(authority, name).is_ok())
+    }
+
+    /// This NSID as a string.
+    ///
+    /// An NSID stores its authority and name separately, so this always
+    /// allocates to join them; it returns a [`Cow`] for symmetry with other
+    /// identifiers which can sometimes be borrowed. To avoid the allocation
+    /// when building a larger string, use [`fmt_into`][Self::fmt_into].
+    pub fn to_cow(&self) -> Cow<'_, str> {
+        let mut value = String::with_capacity(self.authority.len() + 1 + self.package.len());
+        self.fmt_into(&mut value);
+        Cow::Owned(value)
+    }
+
+    /// Append this NSID to `buf`, as it is [displayed][fmt::Display].
+    ///
+    /// ```
+    /// use atprose_types::Nsid;
+    ///
+    /// # fn main() -> Result<(), atprose_types::InvalidNsid> {
+    /// let nsid: Nsid = "app.bsky.feed.post".parse()?;
+    ///
+    /// let mut uri = String::from("at://bsky.app/");
+    /// nsid.fmt_into(&mut uri);
+    /// assert_eq!("at://bsky.app/app.bsky.feed.post", uri);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fmt_into(&self, buf: &mut String) {
+        buf.reserve(self.authority.len() + 1 + self.package.len());
+        buf.push_str(&self.authority);
+        buf.push('.');
+        buf.push_str(&self.package);
+    }
+
+    /// Iterate over the dot-separated segments of this NSID, ending with the
+    /// name.
+    ///
+    /// ```
+    /// use atprose_types::Nsid;
+    ///
+    /// let id = Nsid::new("app.bsky.feed", "post");
+    /// assert_eq!(vec!["app", "bsky", "feed", "post"], id.segments().collect::<Vec<_>>());
+    /// ```
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.authority
+            .split('.')
+            .chain(iter::once(self.package.as_str()))
+    }
+
+    /// Format this NSID with its segments in reverse order, so the name comes
+    /// first and the top-level domain last, like a hostname.
+    ///
+    /// ```
+    /// use atprose_types::Nsid;
+    ///
+    /// let id = Nsid::new("app.bsky.feed", "post");
+    /// assert_eq!(id.to_reverse_dns(), "post.feed.bsky.app");
+    /// ```
+    pub fn to_reverse_dns(&self) -> String {
+        let mut reversed = String::with_capacity(self.authority.len() + 1 + self.package.len());
+        reversed.push_str(&self.package);
+
+        for segment in self.authority.rsplit('.') {
+            reversed.push('.');
+            reversed.push_str(segment);
+        }
+
+        reversed
+    }
+
+    /// Parse an NSID from the [reversed form][Self::to_reverse_dns] of its
+    /// segments.
+    ///
+    /// Like [`try_new`][Self::try_new], this checks the full NSID syntax.
+    ///
+    /// ```
+    /// use atprose_types::Nsid;
+    ///
+    /// # fn main() -> Result<(), atprose_types::InvalidNsid> {
+    /// let id = Nsid::from_reverse_dns("post.feed.bsky.app")?;
+    /// assert_eq!(id, Nsid::new("app.bsky.feed", "post"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_reverse_dns(value: &str) -> Result<Self, InvalidNsid> {
+        let Some((package, authority)) = value.split_once('.') else {
+            return Err(InvalidNsid::Authority);
+        };
+
+        let authority: Vec<_> = authority.rsplit('.').collect();
+        Self::try_new(authority.join("."), package)
+    }
 }
 
+/// Create an NSID from an `(authority, name)` pair, without validating it.
 impl<S: Into<String>> From<(S, S)> for Nsid {
     fn from(value: (S, S)) -> Self {
         let (authority, package) = value;
@@ -38,6 +214,11 @@ impl<S: Into<String>> From<(S, S)> for Nsid {
     }
 }
 
+/// Split an NSID into its authority and name at the last `.`.
+///
+/// This is lenient: it only checks that there is more than one segment, and
+/// not the characters or lengths of the segments. Use
+/// [`Nsid::parse_strict`] to check the full NSID syntax.
 impl FromStr for Nsid {
     type Err = InvalidNsid;
 
@@ -50,6 +231,22 @@ impl FromStr for Nsid {
     }
 }
 
+impl TryFrom<&str> for Nsid {
+    type Error = InvalidNsid;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for Nsid {
+    type Error = InvalidNsid;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 #[cfg(feature = "serde")]
 #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
 impl<'de> Deserialize<'de> for Nsid {
@@ -58,8 +255,7 @@ impl<'de> Deserialize<'de> for Nsid {
         D: serde::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        s.parse()
-            .map_err(|_| serde::de::Error::custom("unknown record key"))
+        s.parse().map_err(serde::de::Error::custom)
     }
 }
 
@@ -69,6 +265,22 @@ impl fmt::Display for Nsid {
     }
 }
 
+/// NSIDs compare equal to the string they are written as.
+impl PartialEq<str> for Nsid {
+    fn eq(&self, other: &str) -> bool {
+        other
+            .strip_suffix(self.package.as_str())
+            .and_then(|rest| rest.strip_suffix('.'))
+            .is_some_and(|authority| authority == self.authority)
+    }
+}
+
+impl PartialEq<&str> for Nsid {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
 #[cfg(feature = "serde")]
 #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
 impl Serialize for Nsid {
@@ -84,6 +296,292 @@ impl Serialize for Nsid {
 pub enum InvalidNsid {
     #[error("invalid nsid authority")]
     Authority,
+    #[error("NSID too long")]
+    Length,
+    #[error("empty NSID segment")]
+    SegmentEmpty,
+    #[error("NSID segment too long")]
+    SegmentLength,
+    #[error("invalid character in NSID: {0:?}")]
+    Character(char),
+}
+
+impl Nsid {
+    /// The maximum length of an NSID, in bytes.
+    pub const MAX_LENGTH: usize = 317;
+    /// The maximum length of an NSID's [authority][Self::authority], in
+    /// bytes.
+    pub const MAX_AUTHORITY_LENGTH: usize = 253;
+    /// The maximum length of each dot-separated segment of an NSID, in bytes.
+    pub const MAX_SEGMENT_LENGTH: usize = 63;
+}
+
+fn validate_nsid(authority: &str, name: &str) -> Result<(), InvalidNsid> {
+    if authority.len() > Nsid::MAX_AUTHORITY_LENGTH
+        || authority.len() + 1 + name.len() > Nsid::MAX_LENGTH
+    {
+        return Err(InvalidNsid::Length);
+    }
+
+    if !authority.contains('.') {
+        return Err(InvalidNsid::Authority);
+    }
+
+    for (i, segment) in authority.split('.').enumerate() {
+        validate_segment(segment)?;
+
+        let lc = segment.len() - 1;
+        for (j, c) in segment.chars().enumerate() {
+            match (i, j, c) {
+                (_, _, 'a'..='z' | 'A'..='Z') => {}
+                (i, j, '0'..='9') if i > 0 || j > 0 => {}
+                (_, j, '-') if j > 0 && j < lc => {}
+                (_, _, c) => return Err(InvalidNsid::Character(c)),
+            }
+        }
+    }
+
+    validate_segment(name)?;
+    for (j, c) in name.chars().enumerate() {
+        match (j, c) {
+            (_, 'a'..='z' | 'A'..='Z') => {}
+            (j, '0'..='9') if j > 0 => {}
+            (_, c) => return Err(InvalidNsid::Character(c)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `value` is a valid NSID, in a form which can be evaluated at
+/// compile time by the `nsid!` macro. This implements the same rules as
+/// [`validate_nsid`].
+#[doc(hidden)]
+pub const fn is_valid_const(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    if bytes.len() > Nsid::MAX_LENGTH {
+        return false;
+    }
+
+    // The name is everything after the last dot.
+    let mut name = bytes.len();
+    while name > 0 && bytes[name - 1] != b'.' {
+        name -= 1;
+    }
+    if name == 0 || name - 1 > Nsid::MAX_AUTHORITY_LENGTH {
+        return false;
+    }
+
+    let (mut i, mut start, mut segments) = (0, 0, 0);
+    while i <= bytes.len() {
+        if i < bytes.len() && bytes[i] != b'.' {
+            i += 1;
+            continue;
+        }
+
+        // `bytes[start..i]` is a segment.
+        let length = i - start;
+        if length == 0 || length > Nsid::MAX_SEGMENT_LENGTH {
+            return false;
+        }
+
+        let mut j = start;
+        while j < i {
+            let valid = match bytes[j] {
+                b'a'..=b'z' | b'A'..=b'Z' => true,
+                b'0'..=b'9' if start < name => j > 0,
+                b'0'..=b'9' => j > start,
+                b'-' => start < name && j > start && j < i - 1,
+                _ => false,
+            };
+            if !valid {
+                return false;
+            }
+            j += 1;
+        }
+
+        segments += 1;
+        i += 1;
+        start = i;
+    }
+
+    segments >= 3
+}
+
+/// An [`Nsid`] checked at compile time, and parsed once, on first use.
+///
+/// This evaluates to a `&'static Nsid`. An invalid NSID fails to compile.
+///
+/// ```
+/// use atprose_types::{nsid, Nsid};
+///
+/// let post: &'static Nsid = nsid!("app.bsky.feed.post");
+/// assert_eq!(post, &Nsid::new("app.bsky.feed", "post"));
+/// ```
+///
+/// ```compile_fail
+/// let post = atprose_types::nsid!("app.bsky.feed_post");
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[macro_export]
+macro_rules! nsid {
+    ($nsid:literal) => {{
+        const _: () = ::core::assert!($crate::__private::is_valid_nsid($nsid), "invalid NSID");
+
+        static NSID: ::std::sync::LazyLock<$crate::Nsid> =
+            ::std::sync::LazyLock::new(|| $crate::Nsid::parse_strict($nsid).unwrap());
+        &*NSID
+    }};
+}
+
+fn validate_segment(segment: &str) -> Result<(), InvalidNsid> {
+    if segment.is_empty() {
+        Err(InvalidNsid::SegmentEmpty)
+    } else if segment.len() > Nsid::MAX_SEGMENT_LENGTH {
+        Err(InvalidNsid::SegmentLength)
+    } else {
+        Ok(())
+    }
+}
+
+/// A pattern matching a set of [NSIDs][Nsid], like `app.bsky.*`.
+///
+/// A pattern is made of dot-separated segments, each matching one segment of
+/// an NSID. `*` matches any single segment, and `**` matches one or more.
+///
+/// ```
+/// use atprose_types::{Nsid, NsidPattern};
+///
+/// # fn main() -> Result<(), atprose_types::InvalidNsid> {
+/// let pattern: NsidPattern = "app.bsky.*".parse()?;
+/// assert!(pattern.matches(&"app.bsky.feed".parse()?));
+/// assert!(!pattern.matches(&"app.bsky.feed.post".parse()?));
+///
+/// let pattern: NsidPattern = "app.bsky.**".parse()?;
+/// assert!(pattern.matches(&"app.bsky.feed.post".parse()?));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct NsidPattern {
+    segments: Vec<PatternSegment>,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+enum PatternSegment {
+    Literal(String),
+    /// `*`
+    One,
+    /// `**`
+    Many,
+}
+
+impl NsidPattern {
+    /// Whether `nsid` matches this pattern.
+    pub fn matches(&self, nsid: &Nsid) -> bool {
+        let segments: Vec<_> = nsid.segments().collect();
+        match_segments(&self.segments, &segments)
+    }
+}
+
+fn match_segments(pattern: &[PatternSegment], segments: &[&str]) -> bool {
+    match (pattern.split_first(), segments.split_first()) {
+        (None, None) => true,
+        (None, Some(_)) | (Some(_), None) => false,
+        (Some((PatternSegment::Many, rest)), Some(_)) => {
+            (1..=segments.len()).any(|n| match_segments(rest, &segments[n..]))
+        }
+        (Some((PatternSegment::One, rest)), Some((_, remaining))) => {
+            match_segments(rest, remaining)
+        }
+        (Some((PatternSegment::Literal(literal), rest)), Some((segment, remaining))) => {
+            literal == segment && match_segments(rest, remaining)
+        }
+    }
+}
+
+impl FromStr for NsidPattern {
+    type Err = InvalidNsid;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() > Nsid::MAX_LENGTH {
+            return Err(InvalidNsid::Length);
+        }
+
+        let segments = s
+            .split('.')
+            .map(|segment| match segment {
+                "*" => Ok(PatternSegment::One),
+                "**" => Ok(PatternSegment::Many),
+                segment => {
+                    validate_segment(segment)?;
+                    match segment
+                        .chars()
+                        .find(|&c| !(c.is_ascii_alphanumeric() || c == '-'))
+                    {
+                        Some(c) => Err(InvalidNsid::Character(c)),
+                        None => Ok(PatternSegment::Literal(segment.to_owned())),
+                    }
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if segments.len() < 2 {
+            return Err(InvalidNsid::Authority);
+        }
+
+        Ok(Self { segments })
+    }
+}
+
+impl TryFrom<&str> for NsidPattern {
+    type Error = InvalidNsid;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for NsidPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+
+            match segment {
+                PatternSegment::Literal(literal) => f.write_str(literal)?,
+                PatternSegment::One => f.write_str("*")?,
+                PatternSegment::Many => f.write_str("**")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for NsidPattern {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for NsidPattern {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
 }
 
 /// A lexicon type, identified by its [namespace][Nsid] and local name.
@@ -91,7 +589,7 @@ pub enum InvalidNsid {
 /// ```
 /// use atprose_types::TypeId;
 ///
-/// # fn main() -> Result<(), ()> {
+/// # fn main() -> Result<(), atprose_types::InvalidNsid> {
 /// let id: TypeId = "app.bsky.feed.post".parse()?;
 /// assert_eq!(id.authority, "app.bsky.feed");
 /// assert_eq!(id.package, "post");
@@ -126,6 +624,30 @@ impl TypeId {
         }
     }
 
+    /// Whether `value` is a valid type ID, checked without allocating: a valid
+    /// [NSID][Nsid::is_valid], optionally followed by `#` and a name made of
+    /// ASCII letters and digits.
+    ///
+    /// ```
+    /// use atprose_types::TypeId;
+    ///
+    /// assert!(TypeId::is_valid("app.bsky.feed.post"));
+    /// assert!(TypeId::is_valid("app.bsky.feed.defs#postView"));
+    /// assert!(!TypeId::is_valid("app.bsky.feed.defs#"));
+    /// assert!(!TypeId::is_valid("#postView"));
+    /// ```
+    pub fn is_valid(value: &str) -> bool {
+        match value.split_once('#') {
+            Some((ns, name)) => {
+                Nsid::is_valid(ns)
+                    && !name.is_empty()
+                    && name.bytes().all(|c| c.is_ascii_alphanumeric())
+            }
+            None => Nsid::is_valid(value),
+        }
+    }
+
+    #[allow(clippy::result_unit_err)]
     pub fn resolve(target: &str, base: &Nsid) -> Result<Self, ()> {
         let Some((nsid, name)) = target.split_once('#') else {
             return Err(());
@@ -158,15 +680,54 @@ impl FromStr for TypeId {
     }
 }
 
+impl TryFrom<&str> for TypeId {
+    type Error = InvalidNsid;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for TypeId {
+    type Error = InvalidNsid;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 impl fmt::Display for TypeId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match (&self.ns, self.name.as_ref()) {
             (ns, None) => write!(f, "{ns}"),
-            (ns, Some(name)) => write!(f, "{ns}:{name}"),
+            (ns, Some(name)) => write!(f, "{ns}#{name}"),
         }
     }
 }
 
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for TypeId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for TypeId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 impl Deref for TypeId {
     type Target = Nsid;
 
@@ -174,3 +735,196 @@ impl Deref for TypeId {
         &self.ns
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{InvalidNsid, Nsid, NsidPattern, TypeId};
+
+    #[test]
+    fn test_validate_nsid() {
+        use InvalidNsid::*;
+
+        let valid = [
+            "com.example.fooBar",
+            "net.users.bob.ping",
+            "a-0.b-1.c",
+            "a.b.c",
+            "com.example.fooBarV2",
+            "cn.8.lex.stuff",
+            "com.exa-mple.foo",
+        ];
+
+        let invalid = [
+            ("", Authority),
+            ("com.example", Authority),
+            ("com..foo", SegmentEmpty),
+            ("com.example.", SegmentEmpty),
+            ("com.example.foo-bar", Character('-')),
+            ("com.example.3", Character('3')),
+            ("com.-example.foo", Character('-')),
+            ("com.example-.foo", Character('-')),
+            ("8.example.foo", Character('8')),
+            ("com.exa💩ple.thing", Character('💩')),
+            ("com.example.foo_bar", Character('_')),
+            (
+                "com.abcdefghijklmnopqrstuvwxyzabcdefghijklmnopqrstuvwxyzabcdefghijklm.foo",
+                SegmentLength,
+            ),
+        ];
+
+        for value in valid {
+            let (authority, name) = value.rsplit_once('.').unwrap();
+            assert_eq!(Ok(()), super::validate_nsid(authority, name), "{value}");
+            assert!(Nsid::is_valid(value), "{value}");
+            assert!(TypeId::is_valid(value), "{value}");
+            assert!(TypeId::is_valid(&format!("{value}#defV2")), "{value}");
+        }
+
+        for (value, expected) in invalid {
+            let (authority, name) = value.rsplit_once('.').unwrap_or((value, ""));
+            assert_eq!(
+                Err(expected),
+                super::validate_nsid(authority, name),
+                "{value}"
+            );
+            assert!(!Nsid::is_valid(value), "{value}");
+            assert!(!TypeId::is_valid(value), "{value}");
+            assert!(!TypeId::is_valid(&format!("{value}#main")), "{value}");
+        }
+
+        for value in ["a.b.c#", "a.b.c#d#e", "a.b.c#foo-bar", "#main"] {
+            assert!(!TypeId::is_valid(value), "{value}");
+        }
+
+        let long = format!("{}.foo", vec!["a".repeat(63); 4].join("."));
+        assert_eq!(Err(Length), Nsid::try_new(long, "bar"));
+    }
+
+    #[test]
+    fn test_eq_str() {
+        let nsid = Nsid::new("app.bsky.feed", "post");
+        assert_eq!(nsid, "app.bsky.feed.post");
+        assert!(nsid == *"app.bsky.feed.post");
+        for other in [
+            "app.bsky.feed",
+            "app.bsky.feedpost",
+            "app.bsky.feed.posts",
+            "x.app.bsky.feed.post",
+        ] {
+            assert_ne!(nsid, other);
+        }
+    }
+
+    #[test]
+    fn test_is_valid_const() {
+        let cases = [
+            "com.example.fooBar",
+            "net.users.bob.ping",
+            "a-0.b-1.c",
+            "a.b.c",
+            "cn.8.lex.stuff",
+            "com.example.foo2",
+            "com.example",
+            "com..foo",
+            "com.example.foo-bar",
+            "com.example.2foo",
+            "com.exa💩ple.thing",
+            "1com.example.foo",
+            "-com.example.foo",
+            "com-.example.foo",
+            "com.example.",
+            ".com.example",
+            "",
+            ".",
+        ];
+
+        for value in cases {
+            assert_eq!(
+                Nsid::is_valid(value),
+                super::is_valid_const(value),
+                "{value:?}"
+            );
+        }
+
+        let segment = "a".repeat(63);
+        for count in [4, 5] {
+            let long = format!("{}.b", vec![segment.as_str(); count].join("."));
+            assert_eq!(Nsid::is_valid(&long), super::is_valid_const(&long));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_nsid_macro() {
+        let post = || crate::nsid!("app.bsky.feed.post");
+        assert_eq!(&Nsid::from_static("app.bsky.feed", "post"), post());
+        assert!(std::ptr::eq(post(), post()));
+    }
+
+    #[test]
+    fn test_reverse_dns() {
+        let cases = [
+            ("app.bsky.feed.post", "post.feed.bsky.app"),
+            ("com.example.fooBar", "fooBar.example.com"),
+        ];
+
+        for (nsid, reversed) in cases {
+            let id: Nsid = nsid.parse().unwrap();
+            assert_eq!(reversed, id.to_reverse_dns());
+            assert_eq!(Ok(id), Nsid::from_reverse_dns(reversed));
+        }
+
+        let long = format!("post.{}.com", "a".repeat(Nsid::MAX_SEGMENT_LENGTH + 1));
+        let cases = [
+            ("post", InvalidNsid::Authority),
+            ("b.a", InvalidNsid::Authority),
+            ("post.", InvalidNsid::Authority),
+            ("post.feed.", InvalidNsid::SegmentEmpty),
+            ("post..bsky.app", InvalidNsid::SegmentEmpty),
+            ("feed_post.bsky.app", InvalidNsid::Character('_')),
+            (&long[..], InvalidNsid::SegmentLength),
+        ];
+        for (reversed, error) in cases {
+            assert_eq!(Err(error), Nsid::from_reverse_dns(reversed), "{reversed}");
+        }
+    }
+
+    #[test]
+    fn test_pattern() {
+        let cases = [
+            ("app.bsky.*", "app.bsky.feed", true),
+            ("app.bsky.*", "app.bsky.feed.post", false),
+            ("app.bsky.*", "app.bsky", false),
+            ("app.bsky.**", "app.bsky.feed", true),
+            ("app.bsky.**", "app.bsky.feed.post", true),
+            ("app.bsky.**", "com.atproto.repo.getRecord", false),
+            ("com.atproto.repo.*", "com.atproto.repo.getRecord", true),
+            ("com.atproto.repo.*", "com.atproto.sync.getRecord", false),
+            ("*.bsky.feed.post", "app.bsky.feed.post", true),
+            ("app.**.post", "app.bsky.feed.post", true),
+            ("app.**.post", "app.post", false),
+            ("app.**.post", "app.bsky.feed.like", false),
+            ("app.bsky.feed.post", "app.bsky.feed.post", true),
+            ("app.bsky.feed.post", "app.bsky.feed.like", false),
+        ];
+
+        for (pattern, nsid, expected) in cases {
+            let parsed: NsidPattern = pattern.parse().unwrap();
+            assert_eq!(pattern, parsed.to_string());
+
+            let nsid: Nsid = nsid.parse().unwrap();
+            assert_eq!(expected, parsed.matches(&nsid), "{pattern} {nsid}");
+        }
+
+        let invalid = [
+            ("app", InvalidNsid::Authority),
+            ("app..*", InvalidNsid::SegmentEmpty),
+            ("app.bsky.*feed", InvalidNsid::Character('*')),
+            ("app.bsky.f_eed", InvalidNsid::Character('_')),
+        ];
+
+        for (pattern, expected) in invalid {
+            assert_eq!(Err(expected), pattern.parse::<NsidPattern>(), "{pattern}");
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! The most commonly-used types, for glob import.
+//!
+//! ```
+//! use atprose_types::prelude::*;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let uri: AtUri = "at://bsky.app/app.bsky.feed.post".parse()?;
+//! let id: Nsid = "app.bsky.feed.post".parse()?;
+//! assert_eq!(uri.collection(), Some(&id));
+//! # Ok(())
+//! # }
+//! ```
+
+pub use crate::{AtUri, AtUriTarget, Did, Handle, Identifier, Nsid, TypeId};
+
+#[cfg(feature = "rkey")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rkey")))]
+pub use crate::{Rkey, Tid};
@@ -1,9 +1,15 @@
-use std::{convert::Infallible, fmt, str::FromStr};
+#[cfg(feature = "rkey")]
+use alloc::borrow::{Cow, ToOwned};
+use alloc::string::String;
+#[cfg(all(feature = "rkey", feature = "serde"))]
+use alloc::string::ToString;
+#[cfg(feature = "rkey")]
+use core::{fmt, str::FromStr};
 
-#[cfg(feature = "serde")]
+#[cfg(all(feature = "rkey", feature = "serde"))]
 use serde::{Deserialize, Serialize};
 
-#[cfg(feature = "rkey")]
+#[cfg(feature = "tid")]
 pub(crate) mod tid;
 
 /// The [key] of an AT protocol record; the unique identifier of the record
@@ -11,8 +17,8 @@ pub(crate) mod tid;
 ///
 /// [key]: https://atproto.com/specs/record-key
 /// [repository]: https://atproto.com/guides/overview#data-repositories
-#[cfg(feature = "plc")]
-#[cfg_attr(docsrs, doc(cfg(feature = "plc")))]
+#[cfg(feature = "rkey")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rkey")))]
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
 pub enum Rkey {
     /// The record key is the literal string `self`, indicating the "collection"
@@ -35,39 +41,143 @@ pub enum Rkey {
     Custom(String),
 }
 
+#[cfg(feature = "rkey")]
 impl Rkey {
+    /// The maximum length of a record key, in bytes.
+    pub const MAX_LENGTH: usize = 512;
+
+    /// Create a record key without checking its syntax.
+    #[deprecated(note = "use `Rkey::try_new`, which checks the record key syntax")]
     pub fn new(value: impl AsRef<str>) -> Self {
-        value.as_ref().parse().unwrap()
+        Self::parse_unchecked(value.as_ref())
     }
-}
 
-impl FromStr for Rkey {
-    type Err = Infallible;
+    /// Create a record key, checking that `value` has valid [record key
+    /// syntax][syntax].
+    ///
+    /// A [TID][Rkey::Tid] or [`self`][Rkey::self_] key can't be invalid, so
+    /// those are created infallibly with [`From<Tid>`][Rkey::from] and
+    /// [`Rkey::self_`].
+    ///
+    /// [syntax]: https://atproto.com/specs/record-key#record-key-syntax
+    ///
+    /// ```
+    /// use atprose_types::{InvalidRkey, Rkey};
+    ///
+    /// assert_eq!(Ok(Rkey::self_()), Rkey::try_new("self"));
+    /// assert!(matches!(Rkey::try_new("3kkqvzbva22jz"), Ok(Rkey::Tid(_))));
+    /// assert_eq!(Err(InvalidRkey::Character('/')), Rkey::try_new("a/b"));
+    /// ```
+    pub fn try_new(value: impl AsRef<str>) -> Result<Self, InvalidRkey> {
+        value.as_ref().parse()
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(if s == "self" {
+    /// Create a record key from `value`, without checking its syntax.
+    fn parse_unchecked(value: &str) -> Self {
+        if value == "self" {
             Self::Unique
-        } else if let Ok(tid) = s.parse() {
+        } else if let Ok(tid) = value.parse() {
             Self::Tid(tid)
         } else {
-            Self::Custom(s.to_owned())
+            Self::Custom(value.to_owned())
+        }
+    }
+
+    /// The literal key `self`, used by records of which a repository has only
+    /// one (like `app.bsky.actor.profile`); this is [`Rkey::Unique`].
+    #[inline]
+    pub const fn self_() -> Self {
+        Self::Unique
+    }
+
+    /// Whether this is the literal key `self` ([`Rkey::Unique`]).
+    pub fn is_self(&self) -> bool {
+        matches!(self, Self::Unique)
+    }
+
+    /// Return the [TID][Rkey::Tid] of this key, if it is one.
+    pub fn as_tid(&self) -> Option<&tid::Tid> {
+        match self {
+            Self::Tid(tid) => Some(tid),
+            _ => None,
+        }
+    }
+
+    /// This key as a string, borrowed unless it is a [TID][Rkey::Tid], which
+    /// must be encoded.
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    /// use atprose_types::Rkey;
+    ///
+    /// assert!(matches!(Rkey::self_().to_cow(), Cow::Borrowed("self")));
+    /// assert!(matches!(Rkey::try_new("3kkqvzbva22jz").unwrap().to_cow(), Cow::Owned(_)));
+    /// ```
+    pub fn to_cow(&self) -> Cow<'_, str> {
+        match self {
+            Self::Unique => Cow::Borrowed("self"),
+            Self::Tid(tid) => Cow::Owned(tid.encode()),
+            Self::Custom(key) => Cow::Borrowed(key),
+        }
+    }
+
+    /// Append this key to `buf`, as it is [displayed][fmt::Display].
+    pub fn fmt_into(&self, buf: &mut String) {
+        match self {
+            Self::Unique => buf.push_str("self"),
+            Self::Tid(tid) => tid.fmt_into(buf),
+            Self::Custom(key) => buf.push_str(key),
+        }
+    }
+}
+
+/// Parse a record key, checking its [syntax][Rkey::try_new].
+#[cfg(feature = "rkey")]
+impl FromStr for Rkey {
+    type Err = InvalidRkey;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        validate_rkey(s)?;
+        Ok(Self::parse_unchecked(s))
+    }
+}
+
+#[cfg(feature = "rkey")]
+impl TryFrom<&str> for Rkey {
+    type Error = InvalidRkey;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Convert a string to a record key, reusing its allocation for a
+/// [custom][Rkey::Custom] key.
+#[cfg(feature = "rkey")]
+impl TryFrom<String> for Rkey {
+    type Error = InvalidRkey;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(match value.parse()? {
+            Self::Custom(_) => Self::Custom(value),
+            key => key,
         })
     }
 }
 
-#[cfg(feature = "serde")]
-#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(all(feature = "rkey", feature = "serde"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "rkey", feature = "serde"))))]
 impl<'de> Deserialize<'de> for Rkey {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        s.parse()
-            .map_err(|_| serde::de::Error::custom("unknown record key"))
+        Self::try_from(s).map_err(serde::de::Error::custom)
     }
 }
 
+#[cfg(feature = "rkey")]
 impl fmt::Display for Rkey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -78,8 +188,34 @@ impl fmt::Display for Rkey {
     }
 }
 
-#[cfg(feature = "serde")]
-#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+/// Record keys compare equal to the string they are written as.
+///
+/// ```
+/// use atprose_types::Rkey;
+///
+/// assert!(Rkey::self_() == "self");
+/// assert!(Rkey::try_new("3kkqvzbva22jz").unwrap() == "3kkqvzbva22jz");
+/// ```
+#[cfg(feature = "rkey")]
+impl PartialEq<str> for Rkey {
+    fn eq(&self, other: &str) -> bool {
+        match self {
+            Rkey::Unique => other == "self",
+            Rkey::Tid(_) => crate::display_eq(self, other),
+            Rkey::Custom(id) => id == other,
+        }
+    }
+}
+
+#[cfg(feature = "rkey")]
+impl PartialEq<&str> for Rkey {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+#[cfg(all(feature = "rkey", feature = "serde"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "rkey", feature = "serde"))))]
 impl Serialize for Rkey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -89,11 +225,145 @@ impl Serialize for Rkey {
     }
 }
 
+#[cfg(feature = "rkey")]
 impl From<tid::Tid> for Rkey {
     fn from(value: tid::Tid) -> Self {
         Self::Tid(value)
     }
 }
 
-#[cfg(not(feature = "plc"))]
+/// Extract the TID from a [TID key][Rkey::Tid], or return the key unchanged.
+#[cfg(feature = "rkey")]
+impl TryFrom<Rkey> for tid::Tid {
+    type Error = Rkey;
+
+    fn try_from(value: Rkey) -> Result<Self, Self::Error> {
+        match value {
+            Rkey::Tid(tid) => Ok(tid),
+            key => Err(key),
+        }
+    }
+}
+
+/// An invalid [record key][Rkey].
+#[cfg(feature = "rkey")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rkey")))]
+#[derive(thiserror::Error, PartialEq, Eq, Clone, Debug)]
+pub enum InvalidRkey {
+    #[error("empty record key")]
+    Empty,
+    #[error("record key too long")]
+    Length,
+    #[error("invalid character in record key: {0:?}")]
+    Character(char),
+    #[error("record key cannot be \".\" or \"..\"")]
+    Reserved,
+}
+
+#[cfg(feature = "rkey")]
+fn validate_rkey(value: &str) -> Result<(), InvalidRkey> {
+    if value.is_empty() {
+        return Err(InvalidRkey::Empty);
+    } else if value.len() > Rkey::MAX_LENGTH {
+        return Err(InvalidRkey::Length);
+    } else if value == "." || value == ".." {
+        return Err(InvalidRkey::Reserved);
+    }
+
+    match value
+        .chars()
+        .find(|&c| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ':' | '~')))
+    {
+        Some(c) => Err(InvalidRkey::Character(c)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(all(test, feature = "rkey"))]
+mod test {
+    use super::{tid::Tid, InvalidRkey, Rkey};
+
+    fn key(value: &str) -> Rkey {
+        Rkey::try_new(value).expect(value)
+    }
+
+    #[test]
+    fn test_self() {
+        assert_eq!(Rkey::Unique, Rkey::self_());
+        assert_eq!(Rkey::self_(), key("self"));
+        assert_eq!("self", Rkey::self_().to_string());
+        assert!(Rkey::self_().is_self());
+        assert!(!key("Self").is_self());
+        assert!(!key("3kkqvzbva22jz").is_self());
+    }
+
+    #[test]
+    fn test_tid() {
+        let tid: Tid = "3kkqvzbva22jz".parse().unwrap();
+        let key = Rkey::from(tid);
+        assert_eq!(Some(&tid), key.as_tid());
+        assert_eq!(Ok(tid), Tid::try_from(key));
+
+        for key in [Rkey::Unique, Rkey::Custom("3kkqvzbva22j".to_owned())] {
+            assert_eq!(None, key.as_tid());
+            assert_eq!(Err(key.clone()), Tid::try_from(key));
+        }
+    }
+
+    #[test]
+    fn test_eq_str() {
+        assert_eq!(Rkey::self_(), "self");
+        assert_ne!(key("Self"), "self");
+        assert_eq!(key("3kkqvzbva22jz"), "3kkqvzbva22jz");
+        assert_ne!(key("3kkqvzbva22jz"), "3kkqvzbva22j");
+        assert_ne!(key("3kkqvzbva22jz"), "3kkqvzbva22jzz");
+        assert!(key("a-b") == *"a-b");
+    }
+
+    #[test]
+    fn test_fmt_into() {
+        for value in ["self", "3kkqvzbva22jz", "a-b"] {
+            let mut buf = "/".to_owned();
+            key(value).fmt_into(&mut buf);
+            assert_eq!(format!("/{value}"), buf);
+            assert_eq!(value, key(value).to_cow());
+        }
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(Rkey::Custom("a-b_c.d:e~f".to_owned()), key("a-b_c.d:e~f"));
+        assert_eq!(Rkey::Custom("...".to_owned()), key("..."));
+        assert!(Rkey::try_new("a".repeat(512)).is_ok());
+
+        let cases = [
+            ("", InvalidRkey::Empty),
+            (".", InvalidRkey::Reserved),
+            ("..", InvalidRkey::Reserved),
+            ("a/b", InvalidRkey::Character('/')),
+            ("a b", InvalidRkey::Character(' ')),
+            ("caf\u{e9}", InvalidRkey::Character('\u{e9}')),
+            ("a#b", InvalidRkey::Character('#')),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(Err(expected), Rkey::try_new(value), "{value:?}");
+        }
+        assert_eq!(Err(InvalidRkey::Length), Rkey::try_new("a".repeat(513)));
+
+        assert_eq!(Err(InvalidRkey::Reserved), "..".parse::<Rkey>());
+        assert_eq!(Err(InvalidRkey::Reserved), Rkey::try_from(".."));
+        assert_eq!(Err(InvalidRkey::Empty), Rkey::try_from(String::new()));
+        assert_eq!(Ok(key("a-b")), Rkey::try_from("a-b".to_owned()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize() {
+        let key: Rkey = serde_json::from_str(r#""self""#).unwrap();
+        assert_eq!(Rkey::self_(), key);
+        assert!(serde_json::from_str::<Rkey>(r#""a/b""#).is_err());
+    }
+}
+
+#[cfg(not(feature = "rkey"))]
 pub type Rkey = String;
@@ -1,34 +1,109 @@
-use std::{fmt, str::FromStr};
+use alloc::string::String;
+#[cfg(feature = "serde")]
+use alloc::string::ToString;
+use core::{fmt, str::FromStr};
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[cfg(feature = "chrono")]
 use chrono::{DateTime, Utc};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use crate::encoding::{decode_u64, encode_u64, DecodeError};
+use crate::encoding::{decode_u64, encode_u64, encode_u64_into, DecodeError};
 
 /// A [timestamp identifier][tid].
 ///
+/// A TID packs a 53-bit [timestamp][Self::timestamp] in microseconds above a
+/// 10-bit [clock identifier][Self::clock_id]. The clock identifier is chosen
+/// at random by each TID generator, so that TIDs made in the same
+/// microsecond by different generators still differ; it isn't a counter.
+///
+/// TIDs are ordered chronologically: by timestamp, and then by clock
+/// identifier. Since they are encoded in sortable base32, their
+/// [string forms][Self::encode] sort in the same order.
+///
 /// [tid]: https://atproto.com/specs/record-key#record-key-type-tid
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
 #[repr(transparent)]
 pub struct Tid(u64);
 
 impl Tid {
+    /// The length of a TID's string form.
+    pub const LENGTH: usize = 13;
+
     #[inline]
-    pub fn new(ts: u64, seq: u16) -> Self {
+    pub const fn new(ts: u64, seq: u16) -> Self {
         let ts = (ts & 0x1F_FFFF_FFFF_FFFF) << 10;
         let seq = (seq & 0x3FF) as u64;
 
         Self(ts | seq)
     }
 
+    /// Create a TID from its packed 64-bit representation.
+    ///
+    /// The top bit of a TID is always 0; if it is set in `raw`, it is cleared.
+    #[inline]
+    pub const fn from_raw(raw: u64) -> Self {
+        Self(raw & 0x7FFF_FFFF_FFFF_FFFF)
+    }
+
+    /// The packed 64-bit representation of this TID.
+    #[inline]
+    pub const fn as_raw(&self) -> u64 {
+        self.0
+    }
+
     pub const fn timestamp(&self) -> u64 {
         (self.0 >> 10) & 0x1FFF_FFFF_FFFF_FFFF
     }
 
+    /// The low 10 bits of this TID, its [clock identifier][Self::clock_id].
     pub const fn seq(&self) -> u16 {
         (self.0 & 0x3FF) as u16
     }
 
+    /// The clock identifier of this TID: a random 10-bit value chosen by the
+    /// generator which made it. This is the same as [`seq`][Self::seq].
+    pub const fn clock_id(&self) -> u16 {
+        self.seq()
+    }
+
+    /// This TID with its clock identifier replaced by the low 10 bits of
+    /// `clock_id`.
+    pub const fn with_clock_id(self, clock_id: u16) -> Self {
+        Self::new(self.timestamp(), clock_id)
+    }
+
+    /// The [timestamp][Self::timestamp] and [clock identifier][Self::clock_id]
+    /// of this TID.
+    pub const fn parts(&self) -> (u64, u16) {
+        (self.timestamp(), self.clock_id())
+    }
+
+    /// Compare two TIDs by [timestamp][Self::timestamp], and then by
+    /// [clock identifier][Self::clock_id].
+    ///
+    /// This is always the same as [`Ord`], whose order follows from the
+    /// timestamp occupying the high bits of the packed value.
+    pub fn cmp_chronological(&self, other: &Self) -> core::cmp::Ordering {
+        self.parts().cmp(&other.parts())
+    }
+
+    /// The TID's timestamp, in microseconds since the Unix epoch.
+    ///
+    /// This is the same value as [`timestamp`][Self::timestamp].
+    pub const fn unix_micros(&self) -> u64 {
+        self.timestamp()
+    }
+
+    /// The TID's timestamp as a [`SystemTime`], with microsecond resolution.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_micros(self.unix_micros())
+    }
+
     #[cfg(feature = "chrono")]
     #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     pub fn datetime(&self) -> DateTime<Utc> {
@@ -36,15 +111,81 @@ impl Tid {
             .expect("beyond domain of chrono::DateTime")
     }
 
+    /// Whether this TID's timestamp is strictly after `dt`, compared in whole
+    /// microseconds (any finer part of `dt` is ignored).
+    ///
+    /// ```
+    /// use atprose_types::Tid;
+    /// use chrono::DateTime;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let since = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")?.to_utc();
+    /// let id: Tid = "3kkqvzbva22jz".parse()?;
+    /// assert!(id.is_after(since));
+    /// assert!(!id.is_before(since));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    pub fn is_after(&self, dt: DateTime<Utc>) -> bool {
+        (self.timestamp() as i64) > dt.timestamp_micros()
+    }
+
+    /// Whether this TID's timestamp is strictly before `dt`, compared in whole
+    /// microseconds (any finer part of `dt` is ignored).
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    pub fn is_before(&self, dt: DateTime<Utc>) -> bool {
+        (self.timestamp() as i64) < dt.timestamp_micros()
+    }
+
+    /// Decode a TID from its canonical string form.
+    ///
+    /// The input must be exactly [`LENGTH`][Self::LENGTH] characters of
+    /// sortable base32, and must not set the top bit of the 64-bit value; this
+    /// limits the first character to `2`–`7`, `a`, or `b`. Otherwise, the
+    /// returned [`DecodeError`] identifies the offending length or character.
     pub fn decode(input: impl AsRef<str>) -> Result<Self, DecodeError> {
+        let input = input.as_ref();
+
+        if input.len() != Self::LENGTH {
+            return Err(DecodeError::InvalidLength {
+                length: input.len(),
+            });
+        }
+
+        // The first character only carries 4 bits of the value (plus a padding
+        // bit), and the top bit is always 0, so only 8 characters may lead.
+        match input.chars().next() {
+            Some('2'..='7' | 'a' | 'b') => {}
+            Some(c) => return Err(DecodeError::InvalidChar { char: c, index: 0 }),
+            None => unreachable!(),
+        }
+
         let tid = decode_u64(input)?;
 
         Ok(Self(tid))
     }
 
+    /// Encode this TID in its canonical string form: always
+    /// [`LENGTH`][Self::LENGTH] characters, padded with leading `2`s (zeros).
     pub fn encode(&self) -> String {
         encode_u64(self.0)
     }
+
+    /// Append this TID's [string form][Self::encode] to `buf`.
+    ///
+    /// ```
+    /// use atprose_types::Tid;
+    ///
+    /// let mut uri = String::from("at://bsky.app/app.bsky.feed.post/");
+    /// Tid::new(1_707_228_000_000_000, 511).fmt_into(&mut uri);
+    /// assert_eq!("at://bsky.app/app.bsky.feed.post/3kkqvzbva22jz", uri);
+    /// ```
+    pub fn fmt_into(&self, buf: &mut String) {
+        encode_u64_into(self.0, buf);
+    }
 }
 
 impl FromStr for Tid {
@@ -55,6 +196,22 @@ impl FromStr for Tid {
     }
 }
 
+impl TryFrom<&str> for Tid {
+    type Error = DecodeError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::decode(value)
+    }
+}
+
+impl TryFrom<String> for Tid {
+    type Error = DecodeError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::decode(value)
+    }
+}
+
 impl fmt::Display for Tid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let id = self.encode();
@@ -69,17 +226,88 @@ impl fmt::Debug for Tid {
     }
 }
 
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for Tid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::decode(s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for Tid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 impl From<Tid> for u64 {
     fn from(value: Tid) -> Self {
         value.0
     }
 }
 
+/// (De)serialize a [`Tid`] as the 8 bytes of its packed value, big-endian.
+///
+/// Use with `#[serde(with = "atprose_types::tid::bytes")]`.
+#[cfg(feature = "serde")]
+pub mod bytes {
+    use serde::{Deserializer, Serializer};
+
+    use super::Tid;
+
+    pub fn serialize<S: Serializer>(tid: &Tid, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&tid.as_raw().to_be_bytes())
+    }
+
+    /// The top bit of a TID must be 0.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Tid, D::Error> {
+        let raw = u64::from_be_bytes(crate::bytes::deserialize_array(deserializer)?);
+        if raw >> 63 != 0 {
+            return Err(serde::de::Error::custom("TID has its top bit set"));
+        }
+
+        Ok(Tid::from_raw(raw))
+    }
+}
+
+/// (De)serialize a [`Tid`] in its string form, as its [`Serialize`] and
+/// [`Deserialize`] implementations do.
+///
+/// Use with `#[serde(with = "atprose_types::tid::string")]`, to be explicit
+/// alongside [`bytes`] fields.
+#[cfg(feature = "serde")]
+pub mod string {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Tid;
+
+    pub fn serialize<S: Serializer>(tid: &Tid, serializer: S) -> Result<S::Ok, S::Error> {
+        tid.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Tid, D::Error> {
+        Tid::deserialize(deserializer)
+    }
+}
+
 #[cfg(test)]
 mod test {
     #![cfg_attr(not(feature = "chrono"), allow(unused_variables))]
 
+    #[cfg(feature = "std")]
+    use std::time::{Duration, UNIX_EPOCH};
+
     use super::Tid;
+    use crate::encoding::DecodeError;
 
     #[test]
     fn test_create_tid() {
@@ -89,6 +317,108 @@ mod test {
         assert_eq!(511, id.seq());
         assert_eq!(id.0, 0x1842dbf9f66001ff);
         assert_eq!("3kkqvzbva22jz".to_owned(), id.to_string());
+
+        assert_eq!((ts, 511), id.parts());
+        assert_eq!(511, id.clock_id());
+        let other = id.with_clock_id(0x7ff);
+        assert_eq!((ts, 0x3ff), other.parts());
+        assert_eq!(id, other.with_clock_id(511));
+
+        const ID: Tid = Tid::new(1_707_228_000_000_000, 511);
+        const RAW: u64 = ID.as_raw();
+        assert_eq!(id, ID);
+        assert_eq!(id, Tid::from_raw(RAW));
+        assert_eq!(id, Tid::from_raw(RAW | 1 << 63));
+    }
+
+    #[test]
+    fn test_encode_padding() {
+        assert_eq!("2222222222222", Tid::from_raw(0).encode());
+        assert_eq!("2222222222223", Tid::from_raw(1).encode());
+        assert_eq!(Ok(Tid::from_raw(1)), Tid::decode("2222222222223"));
+    }
+
+    #[test]
+    fn test_encode_sorts() {
+        let mut ids: Vec<Tid> = [
+            Tid::new(1_707_228_000_000_000, 511),
+            Tid::new(0, 0),
+            Tid::new(0, 1),
+            Tid::new(1, 0),
+            Tid::new(31, 1023),
+            Tid::new(32, 0),
+            Tid::new(1_000_000, 7),
+            Tid::new(1_682_739_741_953_005, 30),
+            Tid::from_raw(0x7fff_ffff_ffff_ffff),
+        ]
+        .into();
+        ids.sort();
+
+        let mut encoded: Vec<String> = ids.iter().map(Tid::encode).collect();
+        assert!(encoded.iter().all(|id| id.len() == Tid::LENGTH));
+
+        let sorted = encoded.clone();
+        encoded.sort();
+        assert_eq!(sorted, encoded);
+    }
+
+    #[test]
+    fn test_chronological_order() {
+        let ids = [
+            Tid::new(1_707_228_000_000_000, 511),
+            Tid::new(1_707_228_000_000_000, 0),
+            Tid::new(1_707_228_000_000_001, 0),
+            Tid::new(0, 1023),
+            Tid::new(1, 0),
+            Tid::new(0x1F_FFFF_FFFF_FFFF, 0),
+            Tid::new(1_682_739_741_953_005, 30),
+        ];
+
+        let mut ordered = ids.to_vec();
+        ordered.sort();
+        let mut chronological = ids.to_vec();
+        chronological.sort_by_key(|id| (id.timestamp(), id.seq()));
+        assert_eq!(chronological, ordered);
+
+        for a in ids {
+            for b in ids {
+                assert_eq!(a.cmp(&b), a.cmp_chronological(&b), "{a} <=> {b}");
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_bytes() {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Deserialize, Serialize, PartialEq, Debug)]
+        struct Entry {
+            #[serde(with = "crate::tid::bytes")]
+            bytes: Tid,
+            #[serde(with = "crate::tid::string")]
+            string: Tid,
+        }
+
+        let id = Tid::from_raw(0x1842_dbf9_f660_01ff);
+        let entry = Entry {
+            bytes: id,
+            string: id,
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        assert_eq!(
+            r#"{"bytes":[24,66,219,249,246,96,1,255],"string":"3kkqvzbva22jz"}"#,
+            json
+        );
+        assert_eq!(entry, serde_json::from_str(&json).unwrap());
+
+        for json in [
+            r#"{"bytes":[24,66,219,249,246,96,1],"string":"3kkqvzbva22jz"}"#,
+            r#"{"bytes":[24,66,219,249,246,96,1,255,0],"string":"3kkqvzbva22jz"}"#,
+            r#"{"bytes":[128,0,0,0,0,0,0,0],"string":"3kkqvzbva22jz"}"#,
+        ] {
+            assert!(serde_json::from_str::<Entry>(json).is_err(), "{json}");
+        }
     }
 
     #[test]
@@ -131,8 +461,72 @@ mod test {
             assert_eq!(ts, id.timestamp(), "{ts:016x} != {:016x}", id.timestamp());
             assert_eq!(clock, id.seq());
 
+            assert_eq!(ts, id.unix_micros());
+            #[cfg(feature = "std")]
+            assert_eq!(
+                Duration::from_micros(ts),
+                id.system_time().duration_since(UNIX_EPOCH).unwrap()
+            );
+
             #[cfg(feature = "chrono")]
             assert_eq!(dt.to_owned(), id.datetime().to_string());
         }
     }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_datetime_comparison() {
+        use chrono::DateTime;
+
+        let id = Tid::new(1_707_228_000_000_000, 511);
+        let dt = id.datetime();
+        assert!(!id.is_after(dt) && !id.is_before(dt));
+
+        let micro = chrono::TimeDelta::microseconds(1);
+        assert!(id.is_after(dt - micro) && !id.is_before(dt - micro));
+        assert!(id.is_before(dt + micro) && !id.is_after(dt + micro));
+        assert!(!id.is_before(dt + chrono::TimeDelta::nanoseconds(999)));
+
+        assert!(id.is_after(DateTime::UNIX_EPOCH - micro));
+        assert!(Tid::from_raw(0).is_before(DateTime::<chrono::Utc>::MAX_UTC));
+    }
+
+    #[test]
+    fn test_decode_non_canonical_tid() {
+        let cases = [
+            ("", DecodeError::InvalidLength { length: 0 }),
+            ("3kkqvzbva22j", DecodeError::InvalidLength { length: 12 }),
+            ("3kkqvzbva22jzz", DecodeError::InvalidLength { length: 14 }),
+            (
+                "kkkqvzbva22jz",
+                DecodeError::InvalidChar {
+                    char: 'k',
+                    index: 0,
+                },
+            ),
+            (
+                "ckkqvzbva22jz",
+                DecodeError::InvalidChar {
+                    char: 'c',
+                    index: 0,
+                },
+            ),
+            (
+                "3kkqvzbva22j1",
+                DecodeError::InvalidChar {
+                    char: '1',
+                    index: 12,
+                },
+            ),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(Err(expected), Tid::decode(value), "{value:?}");
+        }
+
+        assert_eq!(
+            0x7fff_ffff_ffff_ffff,
+            u64::from(Tid::decode("bzzzzzzzzzzzz").unwrap())
+        );
+    }
 }
@@ -1,11 +1,25 @@
-use std::fmt;
-use std::str::FromStr;
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{self, Write as _};
+use core::str::FromStr;
 
+use memchr::{memchr, memchr3_iter};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "rkey")]
+use super::key::InvalidRkey;
 use super::key::Rkey;
-use crate::identity::identifier::{Identifier, InvalidIdentifier};
+use crate::identity::{
+    did::Did,
+    handle::Handle,
+    identifier::{Identifier, InvalidIdentifier},
+};
 use crate::ns::{InvalidNsid, Nsid};
 
 /// A parsed [`at://` URI][uri].
@@ -49,10 +63,243 @@ impl AtUri {
         }
     }
 
+    /// The URI of the repository `authority`, like `at://bsky.app`.
+    pub fn for_repo(authority: impl Into<Identifier>) -> Self {
+        Self::new(AtUriTarget::Repository(authority.into()))
+    }
+
+    /// The URI of the `collection` in the repository `authority`, like
+    /// `at://bsky.app/app.bsky.feed.post`.
+    pub fn for_collection(authority: impl Into<Identifier>, collection: Nsid) -> Self {
+        Self::new(AtUriTarget::Collection(authority.into(), collection))
+    }
+
+    /// The URI of the record `rkey` in the `collection` of the repository
+    /// `authority`.
+    ///
+    /// ```
+    /// use atprose_types::{AtUri, Did, Nsid, Rkey};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let did: Did = "did:plc:z72i7hdynmk6r22z27h6tvur".parse()?;
+    /// let collection: Nsid = "app.bsky.feed.post".parse()?;
+    /// let rkey: Rkey = "3k2la3b".parse()?;
+    ///
+    /// let uri = AtUri::for_record(did, collection, rkey);
+    /// assert_eq!(
+    ///     "at://did:plc:z72i7hdynmk6r22z27h6tvur/app.bsky.feed.post/3k2la3b",
+    ///     uri.to_string()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn for_record(authority: impl Into<Identifier>, collection: Nsid, rkey: Rkey) -> Self {
+        Self::new(AtUriTarget::Record(authority.into(), collection, rkey))
+    }
+
+    /// Parse an `at://` URI, requiring its authority to be a [DID][Did], and
+    /// its collection (if any) to be a [strictly valid][Nsid::parse_strict]
+    /// NSID.
+    ///
+    /// Records are canonically addressed by the DID of their repository;
+    /// handles can change, and are best accepted only from user input.
+    pub fn parse_canonical(value: &str) -> Result<Self, InvalidUri> {
+        let uri: Self = value.parse()?;
+        uri.require_did()?;
+
+        if let Some(collection) = uri.collection() {
+            collection.validate().map_err(|error| {
+                let authority = value[SCHEME.len()..].find('/').unwrap_or_default();
+                InvalidUri::Collection {
+                    offset: SCHEME.len() + authority + 1,
+                    error,
+                }
+            })?;
+        }
+
+        Ok(uri)
+    }
+
+    /// Parse a Bluesky web app URL for a profile or post, like
+    /// `https://bsky.app/profile/alice.bsky.social/post/3k2la3b`, into the
+    /// `at://` URI of the same repository or record.
+    ///
+    /// ```
+    /// use atprose_types::AtUri;
+    ///
+    /// # fn main() -> Result<(), atprose_types::InvalidUri> {
+    /// let uri = AtUri::from_bsky_url("https://bsky.app/profile/bsky.app/post/3k2la3b")?;
+    /// assert_eq!("at://bsky.app/app.bsky.feed.post/3k2la3b", uri.to_string());
+    /// assert_eq!(
+    ///     Some("https://bsky.app/profile/bsky.app/post/3k2la3b".to_owned()),
+    ///     uri.to_bsky_url()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_bsky_url(url: &str) -> Result<Self, InvalidUri> {
+        let Some(path) = url.strip_prefix(BSKY_APP) else {
+            return Err(InvalidUri::Scheme);
+        };
+
+        let start = BSKY_APP.len();
+        if let Some(offset) = path.find('?') {
+            return Err(InvalidUri::Query {
+                offset: start + offset,
+            });
+        }
+        if let Some(offset) = path.find('#') {
+            return Err(InvalidUri::Fragment {
+                offset: start + offset,
+            });
+        }
+
+        let segments: Vec<(usize, &str)> = path
+            .split('/')
+            .scan(start, |offset, segment| {
+                let segment_offset = *offset;
+                *offset += segment.len() + 1;
+                Some((segment_offset, segment))
+            })
+            .collect();
+
+        let target = match segments.as_slice() {
+            [(_, "profile"), (_, authority)] => {
+                AtUriTarget::Repository(authority.parse().map_err(InvalidUri::from)?)
+            }
+            [(_, "profile"), (_, authority), (_, "post"), (offset, record)] => {
+                let authority: Identifier = authority.parse().map_err(InvalidUri::from)?;
+                if record.is_empty() {
+                    return Err(InvalidUri::Path { offset: *offset });
+                }
+
+                let record = decode_segment(record, *offset)?;
+
+                #[cfg(feature = "rkey")]
+                let record: Rkey = record.parse().map_err(|error| InvalidUri::Record {
+                    offset: *offset,
+                    error,
+                })?;
+                #[cfg(not(feature = "rkey"))]
+                let record = record.into_owned();
+
+                AtUriTarget::Record(authority, Nsid::new("app.bsky.feed", "post"), record)
+            }
+            _ => return Err(InvalidUri::Path { offset: start }),
+        };
+
+        Ok(Self::new(target))
+    }
+
+    /// Return the Bluesky web app URL for this URI, if it is a repository or
+    /// an `app.bsky.feed.post` record.
+    ///
+    /// This is the inverse of [`from_bsky_url`][Self::from_bsky_url].
+    pub fn to_bsky_url(&self) -> Option<String> {
+        match self.target_ref() {
+            AtUriTargetRef::Repository(authority) => Some(format!("{BSKY_APP}profile/{authority}")),
+            AtUriTargetRef::Record(authority, collection, record)
+                if collection.authority == "app.bsky.feed" && collection.package == "post" =>
+            {
+                let record = record.to_string();
+                let record = utf8_percent_encode(&record, SEGMENT);
+
+                Some(format!("{BSKY_APP}profile/{authority}/post/{record}"))
+            }
+            _ => None,
+        }
+    }
+
     pub fn authority(&self) -> &Identifier {
         &self.authority
     }
 
+    /// Return the authority of this URI if it is a [DID][Did].
+    pub fn require_did(&self) -> Result<&Did, AuthorityError> {
+        self.authority.as_did().ok_or(AuthorityError::Handle)
+    }
+
+    /// Return the authority of this URI if it is a [handle][Handle].
+    pub fn require_handle(&self) -> Result<&Handle, AuthorityError> {
+        self.authority.as_handle().ok_or(AuthorityError::Did)
+    }
+
+    /// Rewrite this URI to use the DID of its repository as the authority.
+    ///
+    /// If the authority is a handle, `resolve` is called to find its DID, and
+    /// `None` is returned if it can't. A URI whose authority is already a DID
+    /// is returned unchanged.
+    pub fn resolve_authority(&self, resolve: impl Fn(&Handle) -> Option<Did>) -> Option<AtUri> {
+        let authority = match &self.authority {
+            Identifier::Did(_) => return Some(self.clone()),
+            Identifier::Handle(handle) => Identifier::Did(resolve(handle)?),
+        };
+
+        Some(Self {
+            authority,
+            resource: self.resource.clone(),
+        })
+    }
+
+    /// This URI in a canonical form, for comparing or deduplicating URIs.
+    ///
+    /// A handle authority is [lowercased][Handle::to_lowercase], as is the
+    /// domain authority of the collection NSID; both are case-insensitive.
+    /// A DID authority and the record key are case-sensitive, and are left
+    /// as-is.
+    ///
+    /// The collection is also checked against the [full NSID
+    /// syntax][Nsid::validate], which parsing doesn't require; a URI with a
+    /// collection like `app.bsky.feed_post` has no canonical form.
+    ///
+    /// ```
+    /// use atprose_types::AtUri;
+    ///
+    /// # fn main() -> Result<(), atprose_types::InvalidUri> {
+    /// let uri: AtUri = "at://Alice.Bsky.Social/app.bsky.feed.post/3k2la3b".parse()?;
+    /// assert_eq!(
+    ///     "at://alice.bsky.social/app.bsky.feed.post/3k2la3b",
+    ///     uri.normalized()?.to_string()
+    /// );
+    ///
+    /// let uri: AtUri = "at://alice.bsky.social/app.bsky.feed_post".parse()?;
+    /// assert!(uri.normalized().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn normalized(&self) -> Result<AtUri, InvalidUri> {
+        let authority = match &self.authority {
+            Identifier::Handle(handle) => Identifier::Handle(handle.to_lowercase()),
+            did => did.clone(),
+        };
+
+        let resource = match &self.resource {
+            Some(resource) => {
+                resource
+                    .collection
+                    .validate()
+                    .map_err(|error| InvalidUri::Collection {
+                        offset: SCHEME.len() + self.authority.to_string().len() + 1,
+                        error,
+                    })?;
+
+                Some(AtUriResource {
+                    collection: Nsid {
+                        authority: resource.collection.authority.to_ascii_lowercase(),
+                        package: resource.collection.package.clone(),
+                    },
+                    record: resource.record.clone(),
+                })
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            authority,
+            resource,
+        })
+    }
+
     pub fn resource(&self) -> Option<&AtUriResource> {
         self.resource.as_ref()
     }
@@ -61,22 +308,100 @@ impl AtUri {
         self.resource().map(|resource| &resource.collection)
     }
 
+    /// Whether this URI is for the collection `nsid`, or a record in it.
+    ///
+    /// To avoid parsing the NSID on every call, keep it in a static:
+    ///
+    /// ```
+    /// use std::sync::LazyLock;
+    /// use atprose_types::{AtUri, Nsid};
+    ///
+    /// static POST: LazyLock<Nsid> = LazyLock::new(|| Nsid::new("app.bsky.feed", "post"));
+    ///
+    /// # fn main() -> Result<(), atprose_types::InvalidUri> {
+    /// let uri: AtUri = "at://bsky.app/app.bsky.feed.post/3k2la3b".parse()?;
+    /// assert!(uri.is_collection(&POST));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_collection(&self, nsid: &Nsid) -> bool {
+        self.collection() == Some(nsid)
+    }
+
+    /// Whether this URI is for the collection `nsid` in the repository
+    /// `authority`, or a record in it.
+    ///
+    /// Authorities are compared exactly, so a URI using a handle is not in
+    /// the collection of the DID it resolves to.
+    pub fn in_collection(&self, authority: &Identifier, nsid: &Nsid) -> bool {
+        self.authority == *authority && self.is_collection(nsid)
+    }
+
     pub fn record(&self) -> Option<&Rkey> {
         self.resource()
             .and_then(|resource| resource.record.as_ref())
     }
 
     pub fn target(&self) -> AtUriTarget {
-        let authority = self.authority.clone();
+        self.target_ref().into()
+    }
 
-        if let Some(collection) = self.collection().cloned() {
-            if let Some(record) = self.record().cloned() {
-                AtUriTarget::Record(authority, collection, record)
-            } else {
-                AtUriTarget::Collection(authority, collection)
+    /// Borrow the parts of this URI as an [`AtUriTargetRef`], which (unlike
+    /// [`target`][Self::target]) doesn't clone them.
+    ///
+    /// ```
+    /// use atprose_types::{AtUri, AtUriTargetRef};
+    ///
+    /// # fn main() -> Result<(), atprose_types::InvalidUri> {
+    /// let uri: AtUri = "at://bsky.app/app.bsky.feed.post".parse()?;
+    /// let AtUriTargetRef::Collection(_, collection) = uri.target_ref() else {
+    ///     panic!("expected a collection URI");
+    /// };
+    /// assert_eq!(collection.package, "post");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn target_ref(&self) -> AtUriTargetRef<'_> {
+        let authority = &self.authority;
+
+        match &self.resource {
+            Some(AtUriResource {
+                collection,
+                record: Some(record),
+            }) => AtUriTargetRef::Record(authority, collection, record),
+            Some(AtUriResource { collection, .. }) => {
+                AtUriTargetRef::Collection(authority, collection)
             }
-        } else {
-            AtUriTarget::Repository(authority)
+            None => AtUriTargetRef::Repository(authority),
+        }
+    }
+
+    /// Borrow this URI's authority, collection, and record key together.
+    ///
+    /// ```
+    /// use atprose_types::AtUri;
+    ///
+    /// # fn main() -> Result<(), atprose_types::InvalidUri> {
+    /// let uri: AtUri = "at://bsky.app/app.bsky.feed.post/3k2la3b".parse()?;
+    /// let (authority, collection, record) = uri.components();
+    /// assert_eq!("bsky.app", authority.to_string());
+    /// assert_eq!(Some("app.bsky.feed.post".to_owned()), collection.map(|c| c.to_string()));
+    /// assert_eq!(Some("3k2la3b".to_owned()), record.map(|r| r.to_string()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn components(&self) -> (&Identifier, Option<&Nsid>, Option<&Rkey>) {
+        (&self.authority, self.collection(), self.record())
+    }
+
+    /// Split this URI into its authority, collection, and record key, without
+    /// cloning them as [`target`][Self::target] does.
+    pub fn into_parts(self) -> (Identifier, Option<Nsid>, Option<Rkey>) {
+        match self.resource {
+            Some(AtUriResource { collection, record }) => {
+                (self.authority, Some(collection), record)
+            }
+            None => (self.authority, None, None),
         }
     }
 }
@@ -94,26 +419,108 @@ pub enum AtUriTarget {
     Record(Identifier, Nsid, Rkey),
 }
 
+/// A borrowed [`AtUriTarget`], from [`AtUri::target_ref`].
+///
+/// This formats as the URI it was borrowed from.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub enum AtUriTargetRef<'a> {
+    Repository(&'a Identifier),
+    Collection(&'a Identifier, &'a Nsid),
+    Record(&'a Identifier, &'a Nsid, &'a Rkey),
+}
+
+impl From<AtUriTargetRef<'_>> for AtUriTarget {
+    fn from(value: AtUriTargetRef<'_>) -> Self {
+        match value {
+            AtUriTargetRef::Repository(authority) => Self::Repository(authority.clone()),
+            AtUriTargetRef::Collection(authority, collection) => {
+                Self::Collection(authority.clone(), collection.clone())
+            }
+            AtUriTargetRef::Record(authority, collection, record) => {
+                Self::Record(authority.clone(), collection.clone(), record.clone())
+            }
+        }
+    }
+}
+
+impl fmt::Display for AtUriTargetRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            AtUriTargetRef::Repository(authority) => write!(f, "at://{authority}"),
+            AtUriTargetRef::Collection(authority, collection) => {
+                write!(f, "at://{authority}/")?;
+                write!(Encoded(f), "{collection}")
+            }
+            AtUriTargetRef::Record(authority, collection, record) => {
+                write!(f, "at://{authority}/")?;
+                write!(Encoded(&mut *f), "{collection}")?;
+                f.write_char('/')?;
+                write!(Encoded(f), "{record}")
+            }
+        }
+    }
+}
+
+/// A writer which [percent-encodes][SEGMENT] everything written to it as
+/// part of a path segment, so a segment can be displayed without first
+/// collecting it into a string.
+struct Encoded<W>(W);
+
+impl<W: fmt::Write> fmt::Write for Encoded<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for part in utf8_percent_encode(s, SEGMENT) {
+            self.0.write_str(part)?;
+        }
+        Ok(())
+    }
+}
+
 impl FromStr for AtUri {
     type Err = InvalidUri;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let Some(uri) = s.strip_prefix("at://") else {
+        if !s.starts_with(SCHEME) {
             return Err(InvalidUri::Scheme);
-        };
+        }
+
+        // `@` is never valid after the scheme, so find the first one (if
+        // any), and stop there when scanning for the other delimiters.
+        let rest = &s.as_bytes()[SCHEME.len()..];
+        let at = memchr(b'@', rest).map(|i| SCHEME.len() + i);
 
         let mut format = UriFormat::default();
-        for (i, s) in uri.match_indices(&['@', '/', '?', '#']) {
-            let c = s.chars().next().expect("empty match");
-            format = format.consume(uri, (i, c))?;
+        for i in memchr3_iter(b'/', b'?', b'#', rest).map(|i| SCHEME.len() + i) {
+            if at.is_some_and(|at| at < i) {
+                break;
+            }
+            format = format.consume((i, char::from(s.as_bytes()[i])))?;
+        }
+        if let Some(at) = at {
+            format = format.consume((at, '@'))?;
         }
 
-        let target = format.target(uri)?;
+        let target = format.target(s)?;
 
         Ok(Self::new(target))
     }
 }
 
+impl TryFrom<&str> for AtUri {
+    type Error = InvalidUri;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for AtUri {
+    type Error = InvalidUri;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 #[cfg(feature = "serde")]
 #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
 impl<'de> Deserialize<'de> for AtUri {
@@ -128,36 +535,102 @@ impl<'de> Deserialize<'de> for AtUri {
 
 impl fmt::Display for AtUri {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let authority = self.authority();
+        self.target_ref().fmt(f)
+    }
+}
 
-        if let Some(collection) = self.collection() {
-            if let Some(record) = self.record() {
-                write!(f, "at://{authority}/{collection}/{record}")
-            } else {
-                write!(f, "at://{authority}/{collection}")
-            }
-        } else {
-            write!(f, "at://{authority}")
-        }
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for AtUri {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
     }
 }
 
+/// Characters which must be percent-encoded in an `at://` URI path segment:
+/// everything outside the URI `pchar` set, plus `@`, which the parser would
+/// otherwise take to introduce credentials.
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'@')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+const SCHEME: &str = "at://";
+
+/// The prefix of Bluesky web app URLs, for [`AtUri::from_bsky_url`].
+const BSKY_APP: &str = "https://bsky.app/";
+
+/// Percent-decode the path segment starting at `offset`.
+fn decode_segment(segment: &str, offset: usize) -> Result<Cow<'_, str>, InvalidUri> {
+    percent_decode_str(segment)
+        .decode_utf8()
+        .map_err(|error| InvalidUri::Encoding { offset, error })
+}
+
+/// An error parsing an [`AtUri`].
+///
+/// Offsets are in bytes from the start of the URI being parsed.
 #[derive(thiserror::Error, PartialEq, Debug, Clone)]
 pub enum InvalidUri {
     #[error("invalid at:// URI scheme")]
     Scheme,
-    #[error("unrecognized at:// URI path")]
-    Path,
-    #[error(transparent)]
+    #[error("unrecognized at:// URI path at offset {offset}")]
+    Path { offset: usize },
+    #[error("invalid percent-encoding in at:// URI path at offset {offset}")]
+    Encoding {
+        offset: usize,
+        #[source]
+        error: core::str::Utf8Error,
+    },
+    #[error("invalid at:// URI authority")]
     Authority(#[from] InvalidIdentifier),
-    #[error(transparent)]
-    Collection(#[from] InvalidNsid),
-    #[error("unexpected ?query in at:// URI")]
-    Query,
-    #[error("unexpected #fragment in at:// URI")]
-    Fragment,
-    #[error("unexpected credentials@ in at:// URI")]
-    Credentials,
+    #[error("invalid at:// URI collection at offset {offset}")]
+    Collection {
+        offset: usize,
+        #[source]
+        error: InvalidNsid,
+    },
+    #[cfg(feature = "rkey")]
+    #[error("invalid at:// URI record key at offset {offset}")]
+    Record {
+        offset: usize,
+        #[source]
+        error: InvalidRkey,
+    },
+    #[error("unexpected ?query in at:// URI at offset {offset}")]
+    Query { offset: usize },
+    #[error("unexpected #fragment in at:// URI at offset {offset}")]
+    Fragment { offset: usize },
+    #[error("unexpected credentials@ in at:// URI at offset {offset}")]
+    Credentials { offset: usize },
+    #[error("non-canonical at:// URI authority")]
+    NotCanonical(#[from] AuthorityError),
+}
+
+/// An `at://` URI authority of the wrong kind.
+#[derive(thiserror::Error, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum AuthorityError {
+    #[error("expected a handle at:// URI authority, not a DID")]
+    Did,
+    #[error("expected a DID at:// URI authority, not a handle")]
+    Handle,
 }
 
 #[derive(Debug, Copy, Clone, Default)]
@@ -169,40 +642,60 @@ enum UriFormat {
 }
 
 impl UriFormat {
-    pub fn consume(self, input: &str, (index, token): (usize, char)) -> Result<Self, InvalidUri> {
+    /// Advance past the delimiter `token` at byte `index` of the URI.
+    pub fn consume(self, (index, token): (usize, char)) -> Result<Self, InvalidUri> {
         use UriFormat::{Collection, Record, Repository};
 
-        let end = input.len() - 1;
-
         match (self, token, index) {
-            (_, _, 0) => Err(InvalidUri::Authority(InvalidIdentifier::empty())),
-            (state, '/', i) if i == end => Ok(state),
+            (_, _, i) if i == SCHEME.len() => {
+                Err(InvalidUri::Authority(InvalidIdentifier::empty()))
+            }
             (Repository, '/', i) => Ok(Collection(i)),
             (Collection(i), '/', j) => Ok(Record(i, j)),
-            (_, '?', _) => Err(InvalidUri::Query),
-            (_, '#', _) => Err(InvalidUri::Scheme),
-            (_, '@', _) => Err(InvalidUri::Credentials),
-            _ => Err(InvalidUri::Path),
+            (_, '?', offset) => Err(InvalidUri::Query { offset }),
+            (_, '#', offset) => Err(InvalidUri::Fragment { offset }),
+            (_, '@', offset) => Err(InvalidUri::Credentials { offset }),
+            (_, _, offset) => Err(InvalidUri::Path { offset }),
         }
     }
 
     pub fn target(self, input: &str) -> Result<AtUriTarget, InvalidUri> {
+        let start = SCHEME.len();
         let (authority, collection, record) = match self {
-            UriFormat::Repository => (input, None, None),
-            UriFormat::Collection(i) => (&input[..i], Some(&input[i + 1..]), None),
-            UriFormat::Record(i, j) => (&input[..i], Some(&input[i + 1..j]), Some(&input[j + 1..])),
+            UriFormat::Repository => (&input[start..], None, None),
+            UriFormat::Collection(i) => (&input[start..i], Some(i + 1), None),
+            UriFormat::Record(i, j) => (&input[start..i], Some(i + 1), Some(j + 1)),
         };
 
         let authority: Identifier = authority.parse().map_err(InvalidUri::from)?;
 
-        Ok(if let Some(collection) = collection {
-            let collection: Nsid = collection.parse().map_err(InvalidUri::from)?;
+        Ok(if let Some(offset) = collection {
+            let end = record.map_or(input.len(), |j| j - 1);
+            let collection = &input[offset..end];
+            let invalid = |error| InvalidUri::Collection { offset, error };
+
+            if collection.is_empty() {
+                return Err(invalid(InvalidNsid::SegmentEmpty));
+            }
+
+            let collection: Nsid = decode_segment(collection, offset)?
+                .parse()
+                .map_err(invalid)?;
+
+            if let Some(offset) = record {
+                let record = &input[offset..];
+                if record.is_empty() {
+                    return Err(InvalidUri::Path { offset });
+                }
+
+                let record = decode_segment(record, offset)?;
 
-            if let Some(record) = record {
                 #[cfg(feature = "rkey")]
-                let record: Rkey = record.parse().map_err(|_| InvalidUri::Path)?;
+                let record: Rkey = record
+                    .parse()
+                    .map_err(|error| InvalidUri::Record { offset, error })?;
                 #[cfg(not(feature = "rkey"))]
-                let record = record.to_owned();
+                let record = record.into_owned();
 
                 AtUriTarget::Record(authority, collection, record)
             } else {
@@ -214,9 +707,13 @@ impl UriFormat {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "rkey"))]
 mod test {
-    use crate::{AtUri, Handle, Identifier, InvalidUri, Nsid, Rkey};
+    use super::{AuthorityError, Encoded};
+    use crate::{
+        AtUri, AtUriTarget, AtUriTargetRef, Did, Handle, Identifier, InvalidNsid, InvalidUri, Nsid,
+        Rkey,
+    };
 
     fn parse(value: &str) -> AtUri {
         match value.parse() {
@@ -259,4 +756,384 @@ mod test {
 
         fail("https://bsky.app", InvalidUri::Scheme);
     }
+
+    #[test]
+    fn test_errors() {
+        use std::error::Error;
+
+        use crate::{InvalidIdentifier, InvalidNsid};
+
+        fail("at://foo.com?x=1", InvalidUri::Query { offset: 12 });
+        fail(
+            "at://foo.com/com.example.foo#x",
+            InvalidUri::Fragment { offset: 28 },
+        );
+        fail("at://alice@foo.com", InvalidUri::Credentials { offset: 10 });
+        fail("at:///com.example.foo", InvalidIdentifier::empty().into());
+
+        let err = "at://foo.com/foo/123".parse::<AtUri>().unwrap_err();
+        assert_eq!(
+            InvalidUri::Collection {
+                offset: 13,
+                error: InvalidNsid::Authority
+            },
+            err
+        );
+        assert_eq!("invalid at:// URI collection at offset 13", err.to_string());
+        assert_eq!(
+            InvalidNsid::Authority.to_string(),
+            err.source().expect("collection error source").to_string()
+        );
+
+        let err = "at://foo/com.example.foo".parse::<AtUri>().unwrap_err();
+        let source = err.source().expect("authority error source");
+        assert!(source.downcast_ref::<InvalidIdentifier>().is_some());
+
+        let err = AtUri::parse_canonical("at://foo.com").unwrap_err();
+        let source = err.source().expect("canonical error source");
+        assert_eq!(Some(&AuthorityError::Handle), source.downcast_ref());
+    }
+
+    #[test]
+    fn test_into_parts() {
+        let uri = parse("at://foo.com/com.example.foo/123");
+        let record = Rkey::Custom("123".to_owned());
+        assert_eq!(
+            (
+                &handle("foo.com"),
+                Some(&nsid("com.example.foo")),
+                Some(&record)
+            ),
+            uri.components()
+        );
+        assert_eq!(
+            (
+                handle("foo.com"),
+                Some(nsid("com.example.foo")),
+                Some(record.clone())
+            ),
+            uri.into_parts()
+        );
+
+        let uri = parse("at://foo.com/com.example.foo");
+        assert_eq!(
+            (handle("foo.com"), Some(nsid("com.example.foo")), None),
+            uri.into_parts()
+        );
+
+        let uri = parse("at://foo.com");
+        assert_eq!((&handle("foo.com"), None, None), uri.components());
+        assert_eq!((handle("foo.com"), None, None), uri.into_parts());
+    }
+
+    #[test]
+    fn test_constructors() {
+        let did: Did = "did:plc:z72i7hdynmk6r22z27h6tvur".parse().unwrap();
+        let record = Rkey::Custom("123".to_owned());
+
+        assert_eq!(
+            parse("at://did:plc:z72i7hdynmk6r22z27h6tvur"),
+            AtUri::for_repo(did.clone())
+        );
+        assert_eq!(
+            parse("at://foo.com/com.example.foo"),
+            AtUri::for_collection(Handle::new("foo.com"), nsid("com.example.foo"))
+        );
+        assert_eq!(
+            parse("at://did:plc:z72i7hdynmk6r22z27h6tvur/com.example.foo/123"),
+            AtUri::for_record(did, nsid("com.example.foo"), record)
+        );
+    }
+
+    #[test]
+    fn test_is_collection() {
+        let post = nsid("app.bsky.feed.post");
+        let like = nsid("app.bsky.feed.like");
+
+        let uri = parse("at://bsky.app/app.bsky.feed.post/3k2la3b");
+        assert!(uri.is_collection(&post));
+        assert!(!uri.is_collection(&like));
+        assert!(parse("at://bsky.app/app.bsky.feed.post").is_collection(&post));
+        assert!(!parse("at://bsky.app").is_collection(&post));
+
+        assert!(uri.in_collection(&handle("bsky.app"), &post));
+        assert!(!uri.in_collection(&handle("bsky.app"), &like));
+        assert!(!uri.in_collection(&handle("example.com"), &post));
+    }
+
+    #[test]
+    fn test_target_ref() {
+        let uri = parse("at://foo.com/com.example.foo/a:b");
+        let key = Rkey::Custom("a:b".to_owned());
+        assert_eq!(
+            AtUriTargetRef::Record(&handle("foo.com"), &nsid("com.example.foo"), &key),
+            uri.target_ref()
+        );
+        assert_eq!(uri.to_string(), uri.target_ref().to_string());
+        assert_eq!(uri.target(), uri.target_ref().into());
+
+        for value in ["at://foo.com/com.example.foo", "at://foo.com"] {
+            let uri = parse(value);
+            assert_eq!(value, uri.target_ref().to_string());
+            assert_eq!(uri.target(), AtUriTarget::from(uri.target_ref()));
+        }
+
+        let uri = parse("at://foo.com");
+        assert!(matches!(uri.target_ref(), AtUriTargetRef::Repository(_)));
+    }
+
+    #[test]
+    fn test_encoded() {
+        use core::fmt::Write;
+
+        let mut value = String::new();
+        let mut encoded = Encoded(&mut value);
+        encoded.write_str("a b/").unwrap();
+        let (user, host) = ("c", "dé");
+        write!(encoded, "{user}@{host}").unwrap();
+        assert_eq!("a%20b%2Fc%40d%C3%A9", value);
+    }
+
+    #[test]
+    fn test_did_authority() {
+        let uri = parse("at://did:web:bsky.app/app.bsky.feed.post/self");
+        assert_eq!(did("did:web:bsky.app"), uri.authority);
+        assert_eq!(Some(nsid("app.bsky.feed.post")), uri.collection().cloned());
+
+        let uri = parse("at://did:plc:ewvi7nxzyoun6zhxrhs64oiz");
+        assert_eq!(did("did:plc:ewvi7nxzyoun6zhxrhs64oiz"), uri.authority);
+        assert_eq!("at://did:plc:ewvi7nxzyoun6zhxrhs64oiz", uri.to_string());
+
+        // The authority is passed to `Identifier` as written (`:` and `%`
+        // included), so a did:web with a port fails as the DID would alone.
+        for authority in ["did:web:example.com%3A3000", "did:web:localhost:3000"] {
+            let expected = authority.parse::<Identifier>().unwrap_err();
+            fail(&format!("at://{authority}"), expected.clone().into());
+            fail(
+                &format!("at://{authority}/app.bsky.feed.post/self"),
+                expected.into(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_record_key() {
+        use crate::InvalidRkey;
+
+        fail(
+            "at://did:plc:z72i7hdynmk6r22z27h6tvur/app.bsky.feed.post/..",
+            InvalidUri::Record {
+                offset: 57,
+                error: InvalidRkey::Reserved,
+            },
+        );
+        fail(
+            "at://foo.com/com.example.foo/a%2Fb",
+            InvalidUri::Record {
+                offset: 29,
+                error: InvalidRkey::Character('/'),
+            },
+        );
+    }
+
+    #[test]
+    fn test_empty_segments() {
+        use crate::InvalidNsid;
+
+        let empty_collection = InvalidUri::Collection {
+            offset: 13,
+            error: InvalidNsid::SegmentEmpty,
+        };
+        fail("at://foo.com/", empty_collection.clone());
+        fail("at://foo.com//123", empty_collection.clone());
+        fail("at://foo.com//", empty_collection);
+        fail(
+            "at://foo.com/com.example.foo/",
+            InvalidUri::Path { offset: 29 },
+        );
+        fail(
+            "at://foo.com/com.example.foo/123/",
+            InvalidUri::Path { offset: 32 },
+        );
+    }
+
+    #[test]
+    fn test_record_encoding() {
+        let cases = [
+            ("50%", "at://foo.com/com.example.foo/50%25", false),
+            ("a~b", "at://foo.com/com.example.foo/a~b", true),
+            ("a:b", "at://foo.com/com.example.foo/a:b", true),
+            (
+                "a@b?c#d",
+                "at://foo.com/com.example.foo/a%40b%3Fc%23d",
+                false,
+            ),
+        ];
+
+        for (key, expected, valid) in cases {
+            let uri = AtUri::new(AtUriTarget::Record(
+                handle("foo.com"),
+                nsid("com.example.foo"),
+                Rkey::Custom(key.to_owned()),
+            ));
+
+            let encoded = uri.to_string();
+            assert_eq!(expected, encoded);
+            if valid {
+                assert_eq!(uri, parse(&encoded), "round-trip {encoded}");
+            } else {
+                let result: Result<AtUri, _> = encoded.parse();
+                assert!(
+                    matches!(result, Err(InvalidUri::Record { offset: 29, .. })),
+                    "{result:?}"
+                );
+            }
+        }
+
+        let result: Result<AtUri, _> = "at://foo.com/com.example.foo/%ff".parse();
+        assert!(
+            matches!(result, Err(InvalidUri::Encoding { offset: 29, .. })),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn test_canonical() {
+        let uri = parse("at://did:plc:ewvi7nxzyoun6zhxrhs64oiz/app.bsky.actor.profile/self");
+        assert_eq!(
+            Ok(&"did:plc:ewvi7nxzyoun6zhxrhs64oiz".parse().unwrap()),
+            uri.require_did()
+        );
+        assert_eq!(Err(AuthorityError::Did), uri.require_handle());
+        assert_eq!(Ok(uri.clone()), AtUri::parse_canonical(&uri.to_string()));
+
+        let uri = parse("at://bsky.app/app.bsky.feed.post");
+        assert_eq!(Ok(&Handle::new("bsky.app")), uri.require_handle());
+        assert_eq!(Err(AuthorityError::Handle), uri.require_did());
+        assert_eq!(
+            Err(InvalidUri::NotCanonical(AuthorityError::Handle)),
+            AtUri::parse_canonical("at://bsky.app/app.bsky.feed.post")
+        );
+
+        let lenient = "at://did:plc:ewvi7nxzyoun6zhxrhs64oiz/app.bsky.feed_post/3k2la3b";
+        assert!(lenient.parse::<AtUri>().is_ok());
+        assert_eq!(
+            Err(InvalidUri::Collection {
+                offset: 38,
+                error: InvalidNsid::Character('_')
+            }),
+            AtUri::parse_canonical(lenient)
+        );
+    }
+
+    #[test]
+    fn test_resolve_authority() {
+        let did: Did = "did:plc:z72i7hdynmk6r22z27h6tvur".parse().unwrap();
+        let bsky = Handle::new("bsky.app");
+        let resolve = |handle: &Handle| (*handle == bsky).then(|| did.clone());
+
+        let uri = parse("at://bsky.app/app.bsky.feed.post/3k2la3b");
+        assert_eq!(
+            Some(parse(
+                "at://did:plc:z72i7hdynmk6r22z27h6tvur/app.bsky.feed.post/3k2la3b"
+            )),
+            uri.resolve_authority(resolve)
+        );
+
+        let uri = parse("at://did:web:example.com/app.bsky.feed.post");
+        assert_eq!(Some(uri.clone()), uri.resolve_authority(resolve));
+
+        let uri = parse("at://example.com/app.bsky.feed.post");
+        assert_eq!(None, uri.resolve_authority(resolve));
+    }
+
+    #[test]
+    fn test_normalized() {
+        let normalized = |value| parse(value).normalized().unwrap();
+
+        let uri = parse("at://Foo.COM/app.bsky.feed.post/3k2la3B");
+        let expected = normalized("at://foo.com/app.bsky.feed.post/3k2la3B");
+        assert_eq!(parse("at://foo.com/app.bsky.feed.post/3k2la3B"), expected);
+        assert_eq!(expected, uri.normalized().unwrap());
+        assert_ne!(uri, expected);
+
+        assert_eq!(
+            parse("at://foo.com/com.example.fooBar"),
+            normalized("at://foo.com/COM.Example.fooBar")
+        );
+        assert_eq!(parse("at://foo.com"), normalized("at://FOO.com"));
+
+        let uri = parse("at://did:web:Example.com/app.bsky.feed.post");
+        assert_eq!(uri, uri.normalized().unwrap());
+
+        assert_eq!(
+            Err(InvalidUri::Collection {
+                offset: 13,
+                error: InvalidNsid::Character('_')
+            }),
+            parse("at://Foo.com/app.bsky.feed_post/3k2la3b").normalized()
+        );
+    }
+
+    #[test]
+    fn test_bsky_url() {
+        let cases = [
+            (
+                "https://bsky.app/profile/bsky.app/post/3k2la3b",
+                "at://bsky.app/app.bsky.feed.post/3k2la3b",
+            ),
+            (
+                "https://bsky.app/profile/did:plc:z72i7hdynmk6r22z27h6tvur/post/3kkqvzbva22jz",
+                "at://did:plc:z72i7hdynmk6r22z27h6tvur/app.bsky.feed.post/3kkqvzbva22jz",
+            ),
+            ("https://bsky.app/profile/bsky.app", "at://bsky.app"),
+        ];
+
+        for (url, expected) in cases {
+            let uri = match AtUri::from_bsky_url(url) {
+                Ok(uri) => uri,
+                Err(err) => panic!("could not parse {url}: {err}"),
+            };
+            assert_eq!(parse(expected), uri, "{url}");
+            assert_eq!(Some(url), uri.to_bsky_url().as_deref());
+        }
+
+        let errors = [
+            ("at://bsky.app", InvalidUri::Scheme),
+            ("https://example.com/profile/bsky.app", InvalidUri::Scheme),
+            ("https://bsky.app/", InvalidUri::Path { offset: 17 }),
+            ("https://bsky.app/profile", InvalidUri::Path { offset: 17 }),
+            (
+                "https://bsky.app/profile/bsky.app/lists/3k2la3b",
+                InvalidUri::Path { offset: 17 },
+            ),
+            (
+                "https://bsky.app/profile/bsky.app/post/",
+                InvalidUri::Path { offset: 39 },
+            ),
+            (
+                "https://bsky.app/profile/bsky.app?x=1",
+                InvalidUri::Query { offset: 33 },
+            ),
+            (
+                "https://bsky.app/profile/bsky.app#x",
+                InvalidUri::Fragment { offset: 33 },
+            ),
+        ];
+
+        for (url, expected) in errors {
+            assert_eq!(Err(expected), AtUri::from_bsky_url(url), "{url}");
+        }
+        assert!(matches!(
+            AtUri::from_bsky_url("https://bsky.app/profile//post/3k2la3b"),
+            Err(InvalidUri::Authority(_))
+        ));
+
+        let uri = parse("at://bsky.app/app.bsky.actor.profile/self");
+        assert_eq!(None, uri.to_bsky_url());
+        assert_eq!(
+            None,
+            parse("at://bsky.app/app.bsky.feed.post").to_bsky_url()
+        );
+    }
 }
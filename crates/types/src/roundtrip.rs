@@ -0,0 +1,208 @@
+//! Round-trip tests over a generated corpus of values: each value must be
+//! unchanged after formatting and parsing it again, and (with `serde`) after
+//! serializing and deserializing it.
+
+use std::{fmt::Debug, fmt::Display, str::FromStr};
+
+use crate::{AtUri, AtUriTarget, Did, Handle, Identifier, Nsid, Rkey, Tid, TypeId};
+
+fn parse<T>(value: &T)
+where
+    T: Display + FromStr + PartialEq + Debug,
+    T::Err: Debug,
+{
+    let s = value.to_string();
+    match s.parse::<T>() {
+        Ok(parsed) => assert_eq!(value, &parsed, "round-trip {s}"),
+        Err(err) => panic!("could not parse {s} ({value:?}): {err:?}"),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn serde<T>(value: &T)
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + Debug,
+{
+    let json = serde_json::to_string(value).expect("serialize");
+    match serde_json::from_str::<T>(&json) {
+        Ok(deserialized) => assert_eq!(value, &deserialized, "round-trip {json}"),
+        Err(err) => panic!("could not deserialize {json} ({value:?}): {err}"),
+    }
+}
+
+/// A deterministic [xorshift][] generator, so failures are reproducible.
+///
+/// [xorshift]: https://en.wikipedia.org/wiki/Xorshift
+struct Random(u64);
+
+impl Iterator for Random {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        Some(self.0)
+    }
+}
+
+fn tids() -> impl Iterator<Item = Tid> {
+    let edges = [0, 1, u64::MAX >> 1, 1 << 62];
+    let random = Random(0x2545_f491_4f6c_dd1d).take(200);
+
+    edges.into_iter().chain(random).map(Tid::from_raw)
+}
+
+fn identifiers() -> Vec<Identifier> {
+    [
+        "bsky.app",
+        "alice.bsky.social",
+        "XX.LCS.MIT.EDU",
+        "xn--ls8h.test",
+        "8.cn",
+        "did:plc:ewvi7nxzyoun6zhxrhs64oiz",
+        "did:plc:z72i7hdynmk6r22z27h6tvur",
+        "did:web:bsky.app",
+    ]
+    .into_iter()
+    .map(|s| s.parse().expect("parse identifier"))
+    .collect()
+}
+
+fn nsids() -> Vec<Nsid> {
+    [
+        "app.bsky.feed.post",
+        "com.atproto.repo.strongRef",
+        "com.example.fooBarV2",
+        "a.b.c",
+        "cn.8.lex.stuff",
+    ]
+    .into_iter()
+    .map(|s| s.parse().expect("parse NSID"))
+    .collect()
+}
+
+fn type_ids() -> Vec<TypeId> {
+    nsids()
+        .into_iter()
+        .flat_map(|ns| {
+            [
+                TypeId::new(ns.clone(), None),
+                TypeId::new(ns.clone(), Some("postView".to_owned())),
+                TypeId::new(ns, Some("replyRef".to_owned())),
+            ]
+        })
+        .collect()
+}
+
+fn rkeys() -> Vec<Rkey> {
+    let custom = ["self", "3", "literal:self", "a:b", "a~b", "a.b-c_d", "..."];
+
+    custom
+        .into_iter()
+        .map(|key| Rkey::try_new(key).expect(key))
+        .chain(tids().take(20).map(Rkey::from))
+        .collect()
+}
+
+fn uris() -> Vec<AtUri> {
+    let mut uris = Vec::new();
+
+    for authority in identifiers() {
+        uris.push(AtUri::new(AtUriTarget::Repository(authority.clone())));
+
+        for collection in nsids() {
+            uris.push(AtUri::new(AtUriTarget::Collection(
+                authority.clone(),
+                collection.clone(),
+            )));
+
+            for record in rkeys() {
+                uris.push(AtUri::new(AtUriTarget::Record(
+                    authority.clone(),
+                    collection.clone(),
+                    record,
+                )));
+            }
+        }
+    }
+
+    uris
+}
+
+#[test]
+fn test_tid() {
+    for tid in tids() {
+        parse(&tid);
+        assert_eq!(Tid::LENGTH, tid.to_string().len());
+        #[cfg(feature = "serde")]
+        serde(&tid);
+    }
+}
+
+#[test]
+fn test_identifier() {
+    for id in identifiers() {
+        parse(&id);
+        #[cfg(feature = "serde")]
+        serde(&id);
+
+        match id {
+            Identifier::Did(did) => {
+                #[cfg(feature = "serde")]
+                if let Did::Plc(plc) = &did {
+                    serde(plc);
+                }
+                parse(&did);
+                #[cfg(feature = "serde")]
+                serde(&did);
+            }
+            Identifier::Handle(handle) => {
+                parse(&handle);
+                #[cfg(feature = "serde")]
+                serde(&handle);
+            }
+        }
+    }
+
+    parse(&Did::from(Handle::new("bsky.app")));
+}
+
+#[test]
+fn test_nsid() {
+    for nsid in nsids() {
+        parse(&nsid);
+        #[cfg(feature = "serde")]
+        serde(&nsid);
+    }
+}
+
+#[test]
+fn test_type_id() {
+    for id in type_ids() {
+        parse(&id);
+        #[cfg(feature = "serde")]
+        serde(&id);
+    }
+
+    let main: TypeId = "app.bsky.feed.post#main".parse().unwrap();
+    assert_eq!("app.bsky.feed.post", main.to_string());
+}
+
+#[test]
+fn test_rkey() {
+    for key in rkeys() {
+        parse(&key);
+        #[cfg(feature = "serde")]
+        serde(&key);
+    }
+}
+
+#[test]
+fn test_uri() {
+    for uri in uris() {
+        parse(&uri);
+        #[cfg(feature = "serde")]
+        serde(&uri);
+    }
+}
@@ -0,0 +1,225 @@
+use alloc::string::{String, ToString};
+use core::ops::Deref;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::TypeId;
+
+/// The value of a Lexicon [`unknown`][spec] field, kept as raw JSON.
+///
+/// Lexicons require an `unknown` value to be an object, which is often tagged
+/// with the [type][Self::type_id] of data it holds, in a `$type` field.
+///
+/// ```
+/// use atprose_types::Unknown;
+///
+/// let value: Unknown = serde_json::from_str(r#"{
+///     "$type": "app.bsky.feed.post",
+///     "text": "hello"
+/// }"#).unwrap();
+/// assert_eq!(value.type_id(), Some("app.bsky.feed.post".parse().unwrap()));
+/// assert_eq!(value["text"], "hello");
+/// ```
+///
+/// [spec]: https://atproto.com/specs/lexicon#unknown
+#[derive(Deserialize, Serialize, PartialEq, Eq, Hash, Clone, Debug)]
+#[serde(transparent)]
+pub struct Unknown(pub Value);
+
+impl Unknown {
+    pub fn new(value: impl Into<Value>) -> Self {
+        Self(value.into())
+    }
+
+    /// The type named by this value's `$type` field, if it is an object with
+    /// a `$type` string that is a valid type ID.
+    pub fn type_id(&self) -> Option<TypeId> {
+        self.0.get("$type")?.as_str()?.parse().ok()
+    }
+
+    /// Check that this value is of the `expected` type, adding a `$type`
+    /// field naming it if the value doesn't have one.
+    ///
+    /// An existing `$type` naming the same type in a different form (like
+    /// `app.bsky.feed.post#main`) is rewritten in its canonical form.
+    ///
+    /// This is for values whose type is implied by their context, like a
+    /// record whose type is its collection, and may be left out.
+    pub fn infer_type(&mut self, expected: &TypeId) -> Result<(), InvalidUnknown> {
+        let Value::Object(object) = &mut self.0 else {
+            return Err(InvalidUnknown::Object);
+        };
+
+        let found = match object.get("$type") {
+            None => None,
+            Some(Value::String(found)) if found.parse().as_ref() == Ok(expected) => None,
+            Some(Value::String(found)) => Some(found.clone()),
+            Some(found) => Some(found.to_string()),
+        };
+
+        if let Some(found) = found {
+            return Err(InvalidUnknown::Type {
+                expected: expected.clone(),
+                found,
+            });
+        }
+
+        object.insert("$type".to_string(), Value::String(expected.to_string()));
+        Ok(())
+    }
+
+    /// Decode this value as a `T`, which has the type `expected`.
+    ///
+    /// As with [`infer_type`][Self::infer_type], a value without a `$type` is
+    /// assumed to be of the `expected` type, and a value with a different
+    /// `$type` is rejected.
+    ///
+    /// ```
+    /// use atprose_types::{TypeId, Unknown};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Post {
+    ///     text: String,
+    /// }
+    ///
+    /// let post: TypeId = "app.bsky.feed.post".parse().unwrap();
+    /// let value = Unknown::new(serde_json::json!({"text": "hello"}));
+    /// assert_eq!("hello", value.decode::<Post>(&post).unwrap().text);
+    /// ```
+    pub fn decode<T: DeserializeOwned>(mut self, expected: &TypeId) -> Result<T, InvalidUnknown> {
+        self.infer_type(expected)?;
+        serde_json::from_value(self.0).map_err(InvalidUnknown::Value)
+    }
+
+    pub fn into_value(self) -> Value {
+        self.0
+    }
+}
+
+impl Deref for Unknown {
+    type Target = Value;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Value> for Unknown {
+    fn from(value: Value) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Unknown> for Value {
+    fn from(value: Unknown) -> Self {
+        value.0
+    }
+}
+
+/// An error decoding an [`Unknown`] value as a specific type.
+#[derive(thiserror::Error, Debug)]
+pub enum InvalidUnknown {
+    #[error("unknown value is not an object")]
+    Object,
+    #[error("expected $type {expected}, found {found}")]
+    Type { expected: TypeId, found: String },
+    #[error("invalid value: {0}")]
+    Value(#[source] serde_json::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use serde::Deserialize;
+
+    use super::{InvalidUnknown, Unknown};
+    use crate::TypeId;
+
+    #[test]
+    fn test_type_id() {
+        let value = Unknown::new(json!({"$type": "app.bsky.embed.images#view", "images": []}));
+        assert_eq!(
+            Some(TypeId::of(
+                &"app.bsky.embed.images".parse().unwrap(),
+                "view"
+            )),
+            value.type_id()
+        );
+
+        let value = Unknown::new(json!({"$type": "app.bsky.feed.post#main"}));
+        assert_eq!(Some("app.bsky.feed.post".parse().unwrap()), value.type_id());
+
+        for value in [
+            json!({"text": "untyped"}),
+            json!({"$type": 1}),
+            json!({"$type": "post"}),
+            json!("app.bsky.feed.post"),
+        ] {
+            assert_eq!(None, Unknown::new(value.clone()).type_id(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_serde() {
+        let json = r#"{"$type":"com.example.thing","nested":{"values":[1,2,3]}}"#;
+        let value: Unknown = serde_json::from_str(json).unwrap();
+        assert_eq!(json, serde_json::to_string(&value).unwrap());
+    }
+
+    #[test]
+    fn test_decode() {
+        #[derive(Deserialize, PartialEq, Debug)]
+        #[serde(tag = "$type")]
+        enum Embed {
+            #[serde(rename = "app.bsky.embed.images")]
+            Images { images: Vec<u32> },
+            #[serde(rename = "app.bsky.embed.external")]
+            External,
+        }
+
+        let images: TypeId = "app.bsky.embed.images".parse().unwrap();
+        let expected = Embed::Images { images: vec![1] };
+
+        for value in [
+            json!({"images": [1]}),
+            json!({"$type": "app.bsky.embed.images", "images": [1]}),
+            json!({"$type": "app.bsky.embed.images#main", "images": [1]}),
+        ] {
+            let decoded = Unknown::new(value.clone()).decode::<Embed>(&images);
+            assert_eq!(expected, decoded.unwrap(), "{value}");
+        }
+
+        let mut value = Unknown::new(json!({"images": []}));
+        value.infer_type(&images).unwrap();
+        assert_eq!(Some(images.clone()), value.type_id());
+
+        let mut value = Unknown::new(json!({"$type": "app.bsky.embed.images#main"}));
+        value.infer_type(&images).unwrap();
+        assert_eq!(
+            json!({"$type": "app.bsky.embed.images"}),
+            value.into_value()
+        );
+
+        let result =
+            Unknown::new(json!({"$type": "app.bsky.embed.external"})).decode::<Embed>(&images);
+        assert!(
+            matches!(&result, Err(InvalidUnknown::Type { expected, found }) if *expected == images && found == "app.bsky.embed.external"),
+            "{result:?}"
+        );
+        let result = Unknown::new(json!({"$type": 1})).decode::<Embed>(&images);
+        assert!(
+            matches!(&result, Err(InvalidUnknown::Type { found, .. }) if found == "1"),
+            "{result:?}"
+        );
+        let result = Unknown::new(json!([])).decode::<Embed>(&images);
+        assert!(matches!(result, Err(InvalidUnknown::Object)), "{result:?}");
+        let result = Unknown::new(json!({"images": "none"})).decode::<Embed>(&images);
+        assert!(
+            matches!(result, Err(InvalidUnknown::Value(_))),
+            "{result:?}"
+        );
+    }
+}